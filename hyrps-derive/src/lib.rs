@@ -0,0 +1,473 @@
+//! `#[derive(RawCPData)]` for codeplug element structs.
+//!
+//! Fields are serialised in declaration order. By default a field is loaded
+//! and stored through its own `RawCPData` implementation. Each field may be
+//! preceded by `#[cp(...)]` items which are processed in order:
+//!
+//! * `magic(T = expr)` - a reserved `T` which must equal `expr`.
+//! * `local(name: T = expr)` - a `T` read into the local `name`, which later
+//!   `compute` expressions can use. `expr` is written back when storing.
+//! * `bitfield(T)` - start packing the following `bits` fields into a `T`.
+//! * `bits(mask)` / `bits(mask, R)` - the field lives in `mask` of the
+//!   current bitfield, converted through `R` (the bitfield type by default).
+//! * `magic_bits(mask = value)` - reserved bits of the current bitfield.
+//! * `local_bits(name: mask = expr)` - bits of the current bitfield read into
+//!   the local `name`.
+//! * `repr(R)` - the field is stored as an `R` using `TryFrom<R>` and
+//!   `From<Field> for R`.
+//! * `compute(expr)` - the field isn't stored directly, it is built from
+//!   locals once everything else has been loaded.
+//!
+//! Items in a `#[cp(...)]` attribute on the struct are serialised after the
+//! last field, except for `check(expr)` which fails the load when `expr`
+//! doesn't hold.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Attribute, Data, DeriveInput, Error, Expr, Fields, Ident, LitInt, Token, Type,
+};
+
+enum Item {
+    Magic(Type, Expr),
+    Local(Ident, Type, Expr),
+    Bitfield(Type),
+    Bits(LitInt, Option<Type>),
+    MagicBits(LitInt, LitInt),
+    LocalBits(Ident, LitInt, Expr),
+    Repr(Type),
+    Compute(Expr),
+    Check(Expr),
+}
+
+impl Parse for Item {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let kw: Ident = input.parse()?;
+        let content;
+        syn::parenthesized!(content in input);
+
+        let item = match kw.to_string().as_str() {
+            "magic" => {
+                let ty = content.parse()?;
+                content.parse::<Token![=]>()?;
+                Item::Magic(ty, content.parse()?)
+            }
+            "local" => {
+                let name = content.parse()?;
+                content.parse::<Token![:]>()?;
+                let ty = content.parse()?;
+                content.parse::<Token![=]>()?;
+                Item::Local(name, ty, content.parse()?)
+            }
+            "bitfield" => Item::Bitfield(content.parse()?),
+            "bits" => {
+                let mask = content.parse()?;
+                let repr = if content.parse::<Option<Token![,]>>()?.is_some() {
+                    Some(content.parse()?)
+                } else {
+                    None
+                };
+                Item::Bits(mask, repr)
+            }
+            "magic_bits" => {
+                let mask = content.parse()?;
+                content.parse::<Token![=]>()?;
+                Item::MagicBits(mask, content.parse()?)
+            }
+            "local_bits" => {
+                let name = content.parse()?;
+                content.parse::<Token![:]>()?;
+                let mask = content.parse()?;
+                content.parse::<Token![=]>()?;
+                Item::LocalBits(name, mask, content.parse()?)
+            }
+            "repr" => Item::Repr(content.parse()?),
+            "compute" => Item::Compute(content.parse()?),
+            "check" => Item::Check(content.parse()?),
+            _ => return Err(Error::new(kw.span(), "unknown cp attribute")),
+        };
+
+        if !content.is_empty() {
+            return Err(content.error("unexpected tokens in cp attribute"));
+        }
+
+        Ok(item)
+    }
+}
+
+fn parse_items(attrs: &[Attribute]) -> syn::Result<Vec<Item>> {
+    let mut items = vec![];
+
+    for attr in attrs.iter().filter(|a| a.path().is_ident("cp")) {
+        items.extend(attr.parse_args_with(Punctuated::<Item, Token![,]>::parse_terminated)?);
+    }
+
+    Ok(items)
+}
+
+fn shift(mask: &LitInt) -> syn::Result<u32> {
+    let v: u64 = mask.base10_parse()?;
+
+    if v == 0 {
+        return Err(Error::new(mask.span(), "bitfield mask can't be empty"));
+    }
+
+    Ok(v.trailing_zeros())
+}
+
+fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Path(p) if p.path.is_ident("bool"))
+}
+
+struct Bitfield {
+    ty: Type,
+    var: Ident,
+    load: Vec<TokenStream2>,
+    store: Vec<TokenStream2>,
+}
+
+#[derive(Default)]
+struct Gen {
+    load: Vec<TokenStream2>,
+    store: Vec<TokenStream2>,
+    computed: Vec<TokenStream2>,
+    checks: Vec<TokenStream2>,
+    bitfield: Option<Bitfield>,
+    bitfields: usize,
+}
+
+impl Gen {
+    fn flush(&mut self) {
+        if let Some(Bitfield {
+            ty,
+            var,
+            load,
+            store,
+        }) = self.bitfield.take()
+        {
+            self.load.push(quote! {
+                let #var = <#ty as crate::codeplug::cp_data::RawCPData>::load(reader)?;
+            });
+            self.load.extend(load);
+
+            self.store.push(quote! { let mut #var: #ty = 0; });
+            self.store.extend(store);
+            self.store.push(quote! {
+                crate::codeplug::cp_data::RawCPData::store(&#var, writer)?;
+            });
+        }
+    }
+
+    fn bitfield(&mut self, span: &LitInt) -> syn::Result<&mut Bitfield> {
+        self.bitfield
+            .as_mut()
+            .ok_or_else(|| Error::new(span.span(), "bits used outside of a bitfield"))
+    }
+
+    fn item(&mut self, item: Item, what: &str) -> syn::Result<()> {
+        match item {
+            Item::Magic(ty, expr) => {
+                self.flush();
+                self.load.push(quote! {
+                    {
+                        let v = <#ty as crate::codeplug::cp_data::RawCPData>::load(reader)?;
+                        if v != (#expr) {
+                            ::anyhow::bail!("Unexpected reserved value {:?} near {}", v, #what);
+                        }
+                    }
+                });
+                self.store.push(quote! {
+                    <#ty as crate::codeplug::cp_data::RawCPData>::store(&(#expr), writer)?;
+                });
+            }
+            Item::Local(name, ty, expr) => {
+                self.flush();
+                self.load.push(quote! {
+                    let #name = <#ty as crate::codeplug::cp_data::RawCPData>::load(reader)?;
+                });
+                self.store.push(quote! {
+                    <#ty as crate::codeplug::cp_data::RawCPData>::store(&(#expr), writer)?;
+                });
+            }
+            Item::Bitfield(ty) => {
+                self.flush();
+                self.bitfield = Some(Bitfield {
+                    ty,
+                    var: format_ident!("bitfield{}", self.bitfields),
+                    load: vec![],
+                    store: vec![],
+                });
+                self.bitfields += 1;
+            }
+            Item::MagicBits(mask, value) => {
+                let shift = shift(&mask)?;
+                let bf = self.bitfield(&mask)?;
+                let (ty, var) = (&bf.ty, &bf.var);
+
+                bf.load.push(quote! {
+                    if (#var & #mask) >> #shift != #value {
+                        ::anyhow::bail!("Unexpected reserved bits 0x{:X} near {}", #var & #mask, #what);
+                    }
+                });
+                bf.store
+                    .push(quote! { #var |= ((#value as #ty) << #shift) & #mask; });
+            }
+            Item::LocalBits(name, mask, expr) => {
+                let shift = shift(&mask)?;
+                let bf = self.bitfield(&mask)?;
+                let (ty, var) = (&bf.ty, &bf.var);
+
+                bf.load
+                    .push(quote! { let #name = (#var & #mask) >> #shift; });
+                bf.store
+                    .push(quote! { #var |= (((#expr) as #ty) << #shift) & #mask; });
+            }
+            Item::Check(expr) => {
+                self.checks.push(quote! {
+                    if !(#expr) {
+                        ::anyhow::bail!("{}: check failed: {}", #what, stringify!(#expr));
+                    }
+                });
+            }
+            Item::Bits(..) | Item::Repr(_) | Item::Compute(_) => {
+                return Err(Error::new(
+                    proc_macro2::Span::call_site(),
+                    "field encoding can only be used on fields",
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn field(&mut self, name: &Ident, ty: &Type, items: Vec<Item>) -> syn::Result<()> {
+        let what = name.to_string();
+        let mut encoding = None;
+
+        for item in items {
+            match item {
+                Item::Bits(..) | Item::Repr(_) | Item::Compute(_) => {
+                    if encoding.is_some() {
+                        return Err(Error::new(name.span(), "field has several encodings"));
+                    }
+                    encoding = Some(item);
+                }
+                Item::Check(_) => {
+                    return Err(Error::new(
+                        name.span(),
+                        "check can only be used on the struct",
+                    ))
+                }
+                item => self.item(item, &what)?,
+            }
+        }
+
+        let invalid = quote! {
+            .map_err(|e| ::anyhow::anyhow!("Invalid {}: {}", #what, e))?
+        };
+
+        match encoding {
+            None => {
+                self.flush();
+                self.load.push(quote! {
+                    let #name = <#ty as crate::codeplug::cp_data::RawCPData>::load(reader)?;
+                });
+                self.store.push(quote! {
+                    crate::codeplug::cp_data::RawCPData::store(&self.#name, writer)?;
+                });
+            }
+            Some(Item::Repr(repr)) => {
+                self.flush();
+                self.load.push(quote! {
+                    let #name = <#ty as ::std::convert::TryFrom<#repr>>::try_from(
+                        <#repr as crate::codeplug::cp_data::RawCPData>::load(reader)?,
+                    )#invalid;
+                });
+                self.store.push(quote! {
+                    crate::codeplug::cp_data::RawCPData::store(&<#repr>::from(self.#name), writer)?;
+                });
+            }
+            Some(Item::Bits(mask, repr)) => {
+                let shift = shift(&mask)?;
+                let bf = self.bitfield(&mask)?;
+                let (bf_ty, var) = (&bf.ty, &bf.var);
+                let repr = repr.unwrap_or_else(|| bf_ty.clone());
+
+                if is_bool(ty) {
+                    bf.load.push(quote! { let #name = (#var & #mask) != 0; });
+                } else {
+                    bf.load.push(quote! {
+                        let #name = <#ty as ::std::convert::TryFrom<#repr>>::try_from(
+                            ((#var & #mask) >> #shift) as #repr,
+                        )#invalid;
+                    });
+                }
+                bf.store.push(quote! {
+                    #var |= ((<#repr>::from(self.#name) as #bf_ty) << #shift) & #mask;
+                });
+            }
+            Some(Item::Compute(expr)) => {
+                self.computed.push(quote! { let #name = #expr; });
+            }
+            Some(_) => unreachable!(),
+        }
+
+        Ok(())
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => &f.named,
+            _ => return Err(Error::new(name.span(), "RawCPData needs named fields")),
+        },
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "RawCPData can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut gen = Gen::default();
+
+    for f in fields {
+        gen.field(f.ident.as_ref().unwrap(), &f.ty, parse_items(&f.attrs)?)?;
+    }
+
+    for item in parse_items(&input.attrs)? {
+        gen.item(item, &name.to_string())?;
+    }
+
+    gen.flush();
+
+    let names = fields.iter().map(|f| f.ident.as_ref().unwrap());
+    let Gen {
+        load,
+        store,
+        computed,
+        checks,
+        ..
+    } = gen;
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl crate::codeplug::cp_data::RawCPData for #name {
+            fn load(reader: &mut impl ::std::io::Read) -> ::anyhow::Result<Self> {
+                #(#load)*
+                #(#computed)*
+                #(#checks)*
+
+                Ok(#name { #(#names),* })
+            }
+
+            fn store(&self, writer: &mut impl ::std::io::Write) -> ::anyhow::Result<()> {
+                #(#store)*
+
+                Ok(())
+            }
+        }
+    })
+}
+
+#[proc_macro_derive(RawCPData, attributes(cp))]
+pub fn derive_raw_cp_data(input: TokenStream) -> TokenStream {
+    expand(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand;
+    use syn::parse_quote;
+
+    fn error(input: syn::DeriveInput) -> String {
+        expand(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn expands_every_item() {
+        let code = expand(parse_quote! {
+            #[cp(check(level != 7), magic(u8 = 0xaa))]
+            struct Sample {
+                #[cp(magic(u16 = 0x1234), local(high: u8 = self.derived >> 1))]
+                id: u16,
+                #[cp(bitfield(u8), bits(0x07))]
+                level: u8,
+                #[cp(magic_bits(0x80 = 1), local_bits(low: 0x40 = self.derived & 1), bits(0x08))]
+                flag: bool,
+                #[cp(repr(u16))]
+                revert: RrsRevertCh,
+                #[cp(compute(high << 1 | low))]
+                derived: u8,
+            }
+        })
+        .unwrap()
+        .to_string();
+
+        for expected in [
+            "impl crate :: codeplug :: cp_data :: RawCPData for Sample",
+            "let high =",
+            "let bitfield0 =",
+            "let low = (bitfield0 & 0x40) >> 6u32",
+            "let flag = (bitfield0 & 0x08) != 0",
+            "TryFrom < u16 >",
+            "let derived = high << 1 | low",
+            "check failed",
+        ] {
+            assert!(code.contains(expected), "{} not in {}", expected, code);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_attributes() {
+        assert_eq!(
+            error(parse_quote! { struct S { #[cp(nonsense(1))] a: u8 } }),
+            "unknown cp attribute"
+        );
+        assert_eq!(
+            error(parse_quote! { struct S { #[cp(bitfield(u8 u16))] a: u8 } }),
+            "unexpected tokens in cp attribute"
+        );
+        assert_eq!(
+            error(parse_quote! { struct S { #[cp(bits(0x01))] a: bool } }),
+            "bits used outside of a bitfield"
+        );
+        assert_eq!(
+            error(parse_quote! { struct S { #[cp(magic_bits(0x01 = 1))] a: u8 } }),
+            "bits used outside of a bitfield"
+        );
+        assert_eq!(
+            error(parse_quote! { struct S { #[cp(bitfield(u8), bits(0x00))] a: u8 } }),
+            "bitfield mask can't be empty"
+        );
+        assert_eq!(
+            error(parse_quote! { struct S { #[cp(repr(u8), compute(1))] a: u8 } }),
+            "field has several encodings"
+        );
+        assert_eq!(
+            error(parse_quote! { struct S { #[cp(check(a > 1))] a: u8 } }),
+            "check can only be used on the struct"
+        );
+        assert_eq!(
+            error(parse_quote! { #[cp(repr(u8))] struct S { a: u8 } }),
+            "field encoding can only be used on fields"
+        );
+        assert_eq!(
+            error(parse_quote! { struct S(u8); }),
+            "RawCPData needs named fields"
+        );
+        assert_eq!(
+            error(parse_quote! { enum E { A } }),
+            "RawCPData can only be derived for structs"
+        );
+    }
+}
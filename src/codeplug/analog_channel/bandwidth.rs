@@ -0,0 +1,21 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive, Clone, Copy)]
+#[repr(u8)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub enum Bandwidth {
+    Narrow = 0,
+    Wide = 1,
+}
+
+impl fmt::Display for Bandwidth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bandwidth::Narrow => write!(f, "12.5 kHz"),
+            Bandwidth::Wide => write!(f, "25 kHz"),
+        }
+    }
+}
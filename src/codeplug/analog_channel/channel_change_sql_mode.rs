@@ -1,9 +1,9 @@
-use num_enum::TryFromPrimitive;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 
-#[derive(Debug, PartialEq, Eq, TryFromPrimitive, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive, Clone, Copy)]
 #[repr(u8)]
 #[cfg_attr(test, derive(Arbitrary))]
 pub enum ChannelChangeSqlMode {
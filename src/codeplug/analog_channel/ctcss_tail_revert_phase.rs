@@ -1,20 +1,11 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive, Clone, Copy)]
 #[repr(u8)]
 #[cfg_attr(test, derive(Arbitrary))]
 pub enum CTCSSTailRevertPhase {
-    Rad120,
-    Rad180,
-}
-
-impl From<u8> for CTCSSTailRevertPhase {
-    fn from(v: u8) -> Self {
-        match v & 0x20 {
-            0 => CTCSSTailRevertPhase::Rad120,
-            0x20 => CTCSSTailRevertPhase::Rad180,
-            _ => unreachable!(),
-        }
-    }
+    Rad120 = 0,
+    Rad180 = 1,
 }
@@ -1,23 +1,29 @@
 use super::{
     analog_channel::{ctcss::Ctcss, signalling_type::SignallingType},
     channel_common::{self, power_level::PowerLevel, ChannelCommon},
-    cp_data::{CPData, RawCPData},
+    cp_data::CPData,
     disp_tabular::DisplayTabular,
+    emergency_system,
+    layout::{CPLayout, Field},
+    signalling::Signalling,
     Codeplug,
 };
-use byteorder::{ReadBytesExt, WriteBytesExt};
-use std::{convert::TryFrom, io::Read};
+use anyhow::{bail, Context};
+use hyrps_derive::RawCPData;
 
 use self::{
-    auto_reset_mode::AutoResetMode, carrier_sql_level::CarrierSqlLevel,
-    channel_change_sql_mode::ChannelChangeSqlMode, ctcss::CTCSSType, emergency::EmergencySystem,
-    sql_mode::SqlMode, tx_admit::TxAdmit,
+    auto_reset_mode::AutoResetMode, bandwidth::Bandwidth, carrier_sql_level::CarrierSqlLevel,
+    channel_change_sql_mode::ChannelChangeSqlMode, ctcss_tail_revert_phase::CTCSSTailRevertPhase,
+    emergency::EmergencySystem, sql_mode::SqlMode, tone::Tone, tx_admit::TxAdmit,
 };
 
+#[cfg(test)]
+use crate::tests::unknown_bits;
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 
 pub mod auto_reset_mode;
+pub mod bandwidth;
 pub mod carrier_sql_level;
 pub mod channel_change_sql_mode;
 pub mod ctcss;
@@ -25,163 +31,79 @@ pub mod ctcss_tail_revert_phase;
 pub mod emergency;
 pub mod signalling_type;
 pub mod sql_mode;
+pub mod tone;
 pub mod tx_admit;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, RawCPData)]
 #[cfg_attr(test, derive(Arbitrary))]
+#[cfg_attr(
+    not(test),
+    cp(check(common.chan_type == channel_common::channel_type::ChannelType::Analog))
+)]
 pub struct AnalogChannel {
     pub common: channel_common::ChannelCommon,
     pub rx_ctcss: Ctcss,
     pub tx_ctcss: Ctcss,
+    #[cp(repr(u8))]
     pub rx_sql_mode: SqlMode,
+    #[cp(repr(u8))]
     pub mon_sql_mode: SqlMode,
+    #[cp(repr(u8))]
     pub channel_change_sql_mode: ChannelChangeSqlMode,
+    /// The bits of byte 51 are provisional. No CPS capture has confirmed
+    /// them yet, and hyrps used to expect the byte to be zero.
+    #[cp(bitfield(u8), bits(0x01, u8))]
+    pub bandwidth: Bandwidth,
+    /// Don't transmit while the channel is busy, as decided by `tx_admit`.
+    #[cp(bits(0x02))]
+    pub busy_lock: bool,
+    /// Shift the CTCSS phase at the end of a transmission, so receivers
+    /// close their squelch without a noise burst.
+    #[cp(bits(0x10))]
+    pub ctcss_tail_revert: bool,
+    #[cp(bits(0x20, u8))]
+    pub ctcss_tail_revert_phase: CTCSSTailRevertPhase,
+    /// Not decoded yet, carried through as read.
+    #[cfg_attr(test, proptest(strategy = "unknown_bits(0xcc)"))]
+    #[cp(bits(0xcc))]
+    pub unk1: u8,
+    #[cp(repr(u8))]
     pub carrier_sql_level: CarrierSqlLevel,
+    #[cp(repr(u8))]
     pub tx_admit: TxAdmit,
     pub tx_timeout: u8,
     pub tot_prealert: u8,
     pub tot_rekey: u8,
     pub tot_reset: u8,
+    #[cp(repr(u8))]
     pub auto_reset_mode: AutoResetMode,
     pub auto_reset_time: u8,
+    /// Not decoded yet, carried through as read. Usually 10.
+    pub unk2: u8,
+    #[cp(repr(u8))]
     pub signalling_type: SignallingType,
+    /// One based index into the systems of `signalling_type`, zero if unset.
+    pub signalling_idx: u8,
     pub emergency: EmergencySystem,
     pub scan_list_idx: u8,
+    #[cp(bitfield(u8), bits(0x01))]
     pub auto_start_scan: bool,
+    #[cp(bits(0x10))]
     pub emph_de_emph: bool,
-    pub scrambler: bool,
+    #[cp(bits(0x20))]
     pub compandor: bool,
+    #[cp(bits(0x40))]
+    pub scrambler: bool,
+    /// Not decoded yet, carried through as read.
+    #[cfg_attr(test, proptest(strategy = "unknown_bits(0x8e)"))]
+    #[cp(bits(0x8e))]
+    pub unk3: u8,
+    #[cp(bitfield(u8), bits(0x80))]
     pub vox: bool,
-}
-
-fn error_xform(e: impl std::string::ToString) -> std::io::Error {
-    std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
-}
-
-impl RawCPData for AnalogChannel {
-    fn load(reader: &mut impl Read) -> anyhow::Result<AnalogChannel> {
-        let common = channel_common::ChannelCommon::load(reader)?;
-
-        #[cfg(not(test))]
-        assert_eq!(
-            common.chan_type,
-            channel_common::channel_type::ChannelType::Analog
-        );
-
-        let rx_ctcss = Ctcss::load(reader)?;
-        let tx_ctcss = Ctcss::load(reader)?;
-
-        let rx_sql_mode = sql_mode::SqlMode::try_from(reader.read_u8()?).map_err(error_xform)?;
-
-        let mon_sql_mode = sql_mode::SqlMode::try_from(reader.read_u8()?).map_err(error_xform)?;
-
-        let channel_change_sql_mode =
-            ChannelChangeSqlMode::try_from(reader.read_u8()?).map_err(error_xform)?;
-
-        assert_eq!(reader.read_u8()?, 0x0);
-
-        let carrier_sql_level =
-            CarrierSqlLevel::try_from(reader.read_u8()?).map_err(error_xform)?;
-
-        let tx_admit = TxAdmit::try_from(reader.read_u8()?).map_err(error_xform)?;
-
-        let tx_timeout = reader.read_u8()?;
-        let tot_prealert = reader.read_u8()?;
-        let tot_rekey = reader.read_u8()?;
-        let tot_reset = reader.read_u8()?;
-
-        let auto_reset_mode = AutoResetMode::try_from(reader.read_u8()?).map_err(error_xform)?;
-
-        let auto_reset_time = reader.read_u8()?;
-
-        assert_eq!(reader.read_u8()?, 10);
-
-        let signalling_type = SignallingType::try_from(reader.read_u8()?).map_err(error_xform)?;
-
-        assert_eq!(reader.read_u8()?, 0);
-
-        let emergency = EmergencySystem::load(reader)?;
-
-        let scan_list_idx = reader.read_u8()?;
-
-        let b1 = reader.read_u8()?;
-
-        let auto_start_scan = (b1 & 0x1) != 0;
-        let emph_de_emph = (b1 & 0x10) != 0;
-        let compandor = (b1 & 0x20) != 0;
-        let scrambler = (b1 & 0x40) != 0;
-
-        let vox = (reader.read_u8()? & 0x80) != 0;
-
-        Ok(AnalogChannel {
-            common,
-            rx_ctcss,
-            tx_ctcss,
-            rx_sql_mode,
-            mon_sql_mode,
-            channel_change_sql_mode,
-            carrier_sql_level,
-            tx_admit,
-            tx_timeout,
-            tot_prealert,
-            tot_rekey,
-            tot_reset,
-            auto_reset_mode,
-            auto_reset_time,
-            signalling_type,
-            emergency,
-            scan_list_idx,
-            auto_start_scan,
-            emph_de_emph,
-            scrambler,
-            compandor,
-            vox,
-        })
-    }
-
-    fn store(&self, writer: &mut impl std::io::Write) -> anyhow::Result<()> {
-        self.common.store(writer)?;
-        self.rx_ctcss.store(writer)?;
-        self.tx_ctcss.store(writer)?;
-
-        writer.write_u8(self.rx_sql_mode as u8)?;
-        writer.write_u8(self.mon_sql_mode as u8)?;
-        writer.write_u8(self.channel_change_sql_mode as u8)?;
-
-        writer.write_u8(0)?;
-
-        writer.write_u8(self.carrier_sql_level as u8)?;
-        writer.write_u8(self.tx_admit as u8)?;
-
-        writer.write_u8(self.tx_timeout)?;
-        writer.write_u8(self.tot_prealert)?;
-        writer.write_u8(self.tot_rekey)?;
-        writer.write_u8(self.tot_reset)?;
-
-        writer.write_u8(self.auto_reset_mode as u8)?;
-        writer.write_u8(self.auto_reset_time)?;
-
-        writer.write_u8(10)?;
-
-        writer.write_u8(self.signalling_type as u8)?;
-
-        writer.write_u8(0)?;
-
-        self.emergency.store(writer)?;
-
-        writer.write_u8(self.scan_list_idx)?;
-
-        writer.write_u8(
-            (self.auto_start_scan as u8)
-                | (self.emph_de_emph as u8) << 4
-                | (self.compandor as u8) << 5
-                | (self.scrambler as u8) << 6,
-        )?;
-
-        writer.write_u8((self.vox as u8) << 7)?;
-
-        Ok(())
-    }
+    /// Not decoded yet, carried through as read.
+    #[cfg_attr(test, proptest(strategy = "unknown_bits(0x7f)"))]
+    #[cp(bits(0x7f))]
+    pub unk4: u8,
 }
 
 impl AnalogChannel {
@@ -191,8 +113,8 @@ impl AnalogChannel {
         rx_freq: u32,
         rx_only: bool,
         power_level: PowerLevel,
-        rx_ctcss: Ctcss,
-        tx_ctcss: Ctcss,
+        rx_tone: Tone,
+        tx_tone: Tone,
     ) -> AnalogChannel {
         AnalogChannel {
             common: ChannelCommon {
@@ -203,11 +125,16 @@ impl AnalogChannel {
                 rx_freq,
                 tx_freq,
             },
-            rx_ctcss,
-            tx_ctcss,
+            rx_ctcss: rx_tone.into(),
+            tx_ctcss: tx_tone.into(),
             rx_sql_mode: SqlMode::Carrier,
             mon_sql_mode: SqlMode::Carrier,
             channel_change_sql_mode: ChannelChangeSqlMode::RxSQLMode,
+            bandwidth: Bandwidth::Narrow,
+            busy_lock: false,
+            ctcss_tail_revert: false,
+            ctcss_tail_revert_phase: CTCSSTailRevertPhase::Rad120,
+            unk1: 0,
             carrier_sql_level: CarrierSqlLevel::Normal,
             tx_admit: TxAdmit::ChannelFree,
             tx_timeout: 0,
@@ -216,7 +143,9 @@ impl AnalogChannel {
             tot_reset: 0,
             auto_reset_mode: AutoResetMode::Disable,
             auto_reset_time: 0,
+            unk2: 10,
             signalling_type: SignallingType::None,
+            signalling_idx: 0,
             emergency: EmergencySystem {
                 idx: 0,
                 alarm_indication: false,
@@ -226,23 +155,158 @@ impl AnalogChannel {
             scan_list_idx: 0,
             auto_start_scan: false,
             emph_de_emph: true,
-            scrambler: false,
             compandor: false,
+            scrambler: false,
+            unk3: 0,
             vox: false,
+            unk4: 0,
         }
     }
+
+    pub fn rx_tone(&self) -> Tone {
+        self.rx_ctcss.into()
+    }
+
+    pub fn tx_tone(&self) -> Tone {
+        self.tx_ctcss.into()
+    }
+
+    pub fn signalling(&self) -> Signalling {
+        Signalling::from_raw(self.signalling_type, self.signalling_idx)
+    }
+
+    /// Set the tone the squelch opens on, `Tone::None` to open on carrier.
+    pub fn set_rx_tone(&mut self, tone: Tone) {
+        let enable = tone != Tone::None;
+
+        self.rx_ctcss = tone.into();
+        self.rx_sql_mode = self.rx_sql_mode.with_tone(enable);
+        self.mon_sql_mode = self.mon_sql_mode.with_tone(enable);
+    }
+
+    /// Make the channel a selective call channel, whose squelch only opens
+    /// when `signalling` decodes. `Signalling::None` turns selective calling
+    /// off again.
+    pub fn set_selective_call(&mut self, signalling: Signalling) -> anyhow::Result<()> {
+        let (kind, idx) = signalling.to_raw()?;
+        let enable = signalling != Signalling::None;
+
+        self.signalling_type = kind;
+        self.signalling_idx = idx;
+        self.rx_sql_mode = self.rx_sql_mode.with_opt_signalling(enable);
+        self.mon_sql_mode = self.mon_sql_mode.with_opt_signalling(enable);
+
+        Ok(())
+    }
 }
 
 impl CPData for AnalogChannel {
     fn cp_section() -> u16 {
         0x27
     }
+
+    fn verify(&self, codeplug: &Codeplug) -> anyhow::Result<()> {
+        self.rx_tone()
+            .verify()
+            .and(self.tx_tone().verify())
+            .with_context(|| format!("Channel '{}'", self.common.name))?;
+
+        if self.busy_lock && self.tx_admit == TxAdmit::Always {
+            bail!(
+                "Channel '{}' has busy lock enabled but always admits TX",
+                self.common.name
+            );
+        }
+
+        if self.ctcss_tail_revert && !matches!(self.tx_tone(), Tone::Ctcss(_)) {
+            bail!(
+                "Channel '{}' has CTCSS tail revert enabled without a CTCSS TX tone",
+                self.common.name
+            );
+        }
+
+        self.signalling()
+            .verify(codeplug)
+            .with_context(|| format!("Channel '{}'", self.common.name))?;
+
+        if self.signalling_type == SignallingType::None
+            && (self.rx_sql_mode.uses_opt_signalling() || self.mon_sql_mode.uses_opt_signalling())
+        {
+            bail!(
+                "Channel '{}' squelches on optional signalling but has no signalling system",
+                self.common.name
+            );
+        }
+
+        emergency_system::EmergencySystem::verify_ref(
+            codeplug,
+            &self.common.name,
+            self.emergency.idx as u16,
+        )
+    }
+}
+
+impl CPLayout for AnalogChannel {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "common.name"),
+        Field::new(32, 1, "common.chan_type"),
+        Field::bits(33, 0x01, "common.rx_only"),
+        Field::bits(33, 0x04, "common.power_level"),
+        Field::unknown_bits(33, 0xfa, "common.unk_flags"),
+        Field::unknown(34, 2, "common.reserved"),
+        Field::new(36, 4, "common.rx_freq"),
+        Field::new(40, 4, "common.tx_freq"),
+        Field::new(44, 2, "rx_ctcss"),
+        Field::new(46, 2, "tx_ctcss"),
+        Field::new(48, 1, "rx_sql_mode"),
+        Field::new(49, 1, "mon_sql_mode"),
+        Field::new(50, 1, "channel_change_sql_mode"),
+        Field::bits(51, 0x01, "bandwidth"),
+        Field::bits(51, 0x02, "busy_lock"),
+        Field::bits(51, 0x10, "ctcss_tail_revert"),
+        Field::bits(51, 0x20, "ctcss_tail_revert_phase"),
+        Field::unknown_bits(51, 0xcc, "unk1"),
+        Field::new(52, 1, "carrier_sql_level"),
+        Field::new(53, 1, "tx_admit"),
+        Field::new(54, 1, "tx_timeout"),
+        Field::new(55, 1, "tot_prealert"),
+        Field::new(56, 1, "tot_rekey"),
+        Field::new(57, 1, "tot_reset"),
+        Field::new(58, 1, "auto_reset_mode"),
+        Field::new(59, 1, "auto_reset_time"),
+        Field::unknown(60, 1, "unk2"),
+        Field::new(61, 1, "signalling_type"),
+        Field::new(62, 1, "signalling_idx"),
+        Field::new(63, 1, "emergency.idx"),
+        Field::bits(64, 0x01, "emergency.alarm_indication"),
+        Field::bits(64, 0x02, "emergency.alarm_ack"),
+        Field::bits(64, 0x04, "emergency.call_indication"),
+        Field::unknown_bits(64, 0xf8, "emergency.unk_flags"),
+        Field::new(65, 1, "scan_list_idx"),
+        Field::bits(66, 0x01, "auto_start_scan"),
+        Field::bits(66, 0x10, "emph_de_emph"),
+        Field::bits(66, 0x20, "compandor"),
+        Field::bits(66, 0x40, "scrambler"),
+        Field::unknown_bits(66, 0x8e, "unk3"),
+        Field::bits(67, 0x80, "vox"),
+        Field::unknown_bits(67, 0x7f, "unk4"),
+    ];
 }
 
 impl DisplayTabular for AnalogChannel {
     fn get_heading() -> Vec<String> {
         let mut headings = ChannelCommon::get_heading();
-        let mut ac_headings = ["TX CTCSS", "RX CCTCSS", "Squench Mode", "VOX"]
+        let mut ac_headings = [
+            "Bandwidth",
+            "TX Tone",
+            "RX Tone",
+            "Tail Revert",
+            "Squench Mode",
+            "Busy Lock",
+            "Signalling",
+            "Emergency",
+            "VOX",
+        ]
             .iter()
             .map(|s| s.to_string())
             .collect();
@@ -255,17 +319,24 @@ impl DisplayTabular for AnalogChannel {
     fn get_row(&self, codeplug: &Codeplug) -> Vec<String> {
         let mut row = self.common.get_row(codeplug);
 
-        let fmt_ctcss = |c: &Ctcss| match c.kind {
-            CTCSSType::None => "<None>".to_string(),
-            CTCSSType::Ctcss => format!("CTCSS ({})", c.freq),
-            CTCSSType::Cdcss => format!("CDCSS ({})", c.freq),
-            CTCSSType::CdcssInvert => format!("CDCSS Revert ({})", c.freq),
+        let fmt_tone = |t: Tone| match t {
+            Tone::None => "<None>".to_string(),
+            t => t.to_string(),
         };
 
         row.append(&mut vec![
-            fmt_ctcss(&self.tx_ctcss),
-            fmt_ctcss(&self.rx_ctcss),
+            self.bandwidth.to_string(),
+            fmt_tone(self.tx_tone()),
+            fmt_tone(self.rx_tone()),
+            match (self.ctcss_tail_revert, self.ctcss_tail_revert_phase) {
+                (false, _) => "Off".to_string(),
+                (true, CTCSSTailRevertPhase::Rad120) => "120°".to_string(),
+                (true, CTCSSTailRevertPhase::Rad180) => "180°".to_string(),
+            },
             format!("{:?}", self.rx_sql_mode),
+            format!("{}", self.busy_lock),
+            self.signalling().describe(codeplug),
+            emergency_system::EmergencySystem::resolve_name(codeplug, self.emergency.idx as u16),
             format!("{}", self.vox),
         ]);
 
@@ -275,9 +346,119 @@ impl DisplayTabular for AnalogChannel {
 
 #[cfg(test)]
 mod tests {
-    use crate::tests::check_serde;
+    use super::{
+        bandwidth::Bandwidth, ctcss_tail_revert_phase::CTCSSTailRevertPhase, sql_mode::SqlMode,
+        tone::Tone, tx_admit::TxAdmit, AnalogChannel,
+    };
+    use crate::{
+        codeplug::{
+            channel_common::power_level::PowerLevel,
+            cp_data::{CPData, RawCPData},
+            disp_tabular::DisplayTabular,
+        },
+        tests::{check_serde, empty_codeplug},
+    };
     use proptest::prelude::*;
 
+    fn channel(tx_tone: Tone) -> AnalogChannel {
+        AnalogChannel::new(
+            "GB3WF".to_string(),
+            145_000_000,
+            145_600_000,
+            false,
+            PowerLevel::High,
+            Tone::None,
+            tx_tone,
+        )
+    }
+
+    #[test]
+    fn busy_lock_and_tail_revert_bits() {
+        let mut ac = channel(Tone::Ctcss(885));
+        ac.busy_lock = true;
+        ac.ctcss_tail_revert = true;
+        ac.ctcss_tail_revert_phase = CTCSSTailRevertPhase::Rad180;
+        ac.unk1 = 0x11;
+
+        let mut raw = vec![];
+        ac.store(&mut raw).unwrap();
+        assert_eq!(raw[51], 0x02 | 0x10 | 0x20 | 0x44);
+
+        raw[51] = 0x02 | 0x20;
+        let ac = AnalogChannel::load(&mut &raw[..]).unwrap();
+
+        assert!(ac.busy_lock);
+        assert!(!ac.ctcss_tail_revert);
+        assert_eq!(ac.ctcss_tail_revert_phase, CTCSSTailRevertPhase::Rad180);
+        assert_eq!(ac.unk1, 0);
+    }
+
+    #[test]
+    fn export_csv() {
+        let cp = empty_codeplug(4, 4);
+        let mut ac = channel(Tone::Ctcss(885));
+        ac.bandwidth = Bandwidth::Wide;
+        ac.busy_lock = true;
+        ac.ctcss_tail_revert = true;
+        ac.ctcss_tail_revert_phase = CTCSSTailRevertPhase::Rad120;
+
+        let mut out = vec![];
+        AnalogChannel::write_csv(&[ac], &cp, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = out.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("Bandwidth,TX Tone,RX Tone,Tail Revert"));
+        assert!(lines[0].contains("Busy Lock"));
+        assert!(lines[1].contains("25 kHz,88.5,<None>,120°"));
+        assert!(lines[1].contains(",true,"));
+    }
+
+    #[test]
+    fn verify_busy_lock_and_tail_revert() {
+        let cp = empty_codeplug(4, 4);
+        let mut ac = channel(Tone::None);
+        assert!(ac.verify(&cp).is_ok());
+
+        ac.busy_lock = true;
+        ac.tx_admit = TxAdmit::Always;
+        assert!(ac.verify(&cp).is_err());
+        ac.tx_admit = TxAdmit::ChannelFree;
+        assert!(ac.verify(&cp).is_ok());
+
+        ac.ctcss_tail_revert = true;
+        assert!(ac.verify(&cp).is_err());
+        ac.tx_ctcss = Tone::Dcs {
+            code: 0o023,
+            inverted: false,
+        }
+        .into();
+        assert!(ac.verify(&cp).is_err());
+        ac.tx_ctcss = Tone::Ctcss(885).into();
+        assert!(ac.verify(&cp).is_ok());
+    }
+
+    #[test]
+    fn set_rx_tone() {
+        let mut ac = channel(Tone::None);
+        ac.mon_sql_mode = SqlMode::OptSignaling;
+
+        ac.set_rx_tone("D023I".parse().unwrap());
+        assert_eq!(
+            ac.rx_tone(),
+            Tone::Dcs {
+                code: 0o023,
+                inverted: true
+            }
+        );
+        assert_eq!(ac.rx_sql_mode, SqlMode::CtcssCdcss);
+        assert_eq!(ac.mon_sql_mode, SqlMode::CtcssCdcssAndOptSig);
+
+        ac.set_rx_tone(Tone::None);
+        assert_eq!(ac.rx_sql_mode, SqlMode::Carrier);
+        assert_eq!(ac.mon_sql_mode, SqlMode::OptSignaling);
+    }
+
     proptest! {
         #[test]
         fn analog_channel_serde(ac in any::<super::AnalogChannel>()) {
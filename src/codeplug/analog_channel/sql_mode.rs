@@ -1,8 +1,8 @@
-use num_enum::TryFromPrimitive;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 
-#[derive(Debug, PartialEq, Eq, TryFromPrimitive, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive, Clone, Copy)]
 #[repr(u8)]
 #[cfg_attr(test, derive(Arbitrary))]
 pub enum SqlMode {
@@ -12,3 +12,36 @@ pub enum SqlMode {
     CtcssCdcssAndOptSig = 3,
     CtcssCdcssOrOptSig = 4,
 }
+
+impl SqlMode {
+    /// Whether the squelch opens on optional signalling, which needs a
+    /// signalling system.
+    pub fn uses_opt_signalling(&self) -> bool {
+        matches!(
+            self,
+            Self::OptSignaling | Self::CtcssCdcssAndOptSig | Self::CtcssCdcssOrOptSig
+        )
+    }
+
+    /// The equivalent mode with optional signalling added or removed.
+    pub fn with_opt_signalling(self, enable: bool) -> Self {
+        match (self, enable) {
+            (Self::Carrier, true) => Self::OptSignaling,
+            (Self::CtcssCdcss, true) => Self::CtcssCdcssAndOptSig,
+            (Self::OptSignaling, false) => Self::Carrier,
+            (Self::CtcssCdcssAndOptSig | Self::CtcssCdcssOrOptSig, false) => Self::CtcssCdcss,
+            (m, _) => m,
+        }
+    }
+
+    /// The equivalent mode with CTCSS/CDCSS added or removed.
+    pub fn with_tone(self, enable: bool) -> Self {
+        match (self, enable) {
+            (Self::Carrier, true) => Self::CtcssCdcss,
+            (Self::OptSignaling, true) => Self::CtcssCdcssAndOptSig,
+            (Self::CtcssCdcss, false) => Self::Carrier,
+            (Self::CtcssCdcssAndOptSig | Self::CtcssCdcssOrOptSig, false) => Self::OptSignaling,
+            (m, _) => m,
+        }
+    }
+}
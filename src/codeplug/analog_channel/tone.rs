@@ -0,0 +1,190 @@
+//! CTCSS tones and DCS codes in the forms radio users know them, `88.5`,
+//! `D023N` and `D023I`.
+
+use super::ctcss::{CTCSSType, Ctcss};
+use anyhow::{bail, Context};
+use std::{fmt, str::FromStr};
+
+/// Standard CTCSS tones, in tenths of a Hz.
+const CTCSS_TONES: [u16; 50] = [
+    670, 693, 719, 744, 770, 797, 825, 854, 885, 915, 948, 974, 1000, 1035, 1072, 1109, 1148, 1188,
+    1230, 1273, 1318, 1365, 1413, 1462, 1514, 1567, 1598, 1622, 1655, 1679, 1713, 1738, 1773, 1799,
+    1835, 1862, 1899, 1928, 1966, 1995, 2035, 2065, 2107, 2181, 2257, 2291, 2336, 2418, 2503, 2541,
+];
+
+/// Standard DCS codes. These are octal, as they're written.
+const DCS_CODES: [u16; 104] = [
+    0o023, 0o025, 0o026, 0o031, 0o032, 0o036, 0o043, 0o047, 0o051, 0o053, 0o054, 0o065, 0o071,
+    0o072, 0o073, 0o074, 0o114, 0o115, 0o116, 0o122, 0o125, 0o131, 0o132, 0o134, 0o143, 0o145,
+    0o152, 0o155, 0o156, 0o162, 0o165, 0o172, 0o174, 0o205, 0o212, 0o223, 0o225, 0o226, 0o243,
+    0o244, 0o245, 0o246, 0o251, 0o252, 0o255, 0o261, 0o263, 0o265, 0o266, 0o271, 0o274, 0o306,
+    0o311, 0o315, 0o325, 0o331, 0o332, 0o343, 0o346, 0o351, 0o356, 0o364, 0o365, 0o371, 0o411,
+    0o412, 0o413, 0o423, 0o431, 0o432, 0o445, 0o446, 0o452, 0o454, 0o455, 0o462, 0o464, 0o465,
+    0o466, 0o503, 0o506, 0o516, 0o523, 0o526, 0o532, 0o546, 0o565, 0o606, 0o612, 0o624, 0o627,
+    0o631, 0o632, 0o654, 0o662, 0o664, 0o703, 0o712, 0o723, 0o731, 0o732, 0o734, 0o743, 0o754,
+];
+
+/// Sub-audible squelch tone of an analog channel.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Tone {
+    None,
+    /// CTCSS tone in tenths of a Hz.
+    Ctcss(u16),
+    /// DCS code, with inverted polarity if `inverted`.
+    Dcs {
+        code: u16,
+        inverted: bool,
+    },
+}
+
+impl Tone {
+    /// Check the tone is one of the standard CTCSS tones or DCS codes.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        match self {
+            Tone::Ctcss(f) if !CTCSS_TONES.contains(f) => {
+                bail!("{} is not a standard CTCSS tone", self)
+            }
+            Tone::Dcs { code, .. } if !DCS_CODES.contains(code) => {
+                bail!("{} is not a standard DCS code", self)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Tone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tone::None => write!(f, "None"),
+            Tone::Ctcss(t) => write!(f, "{}.{}", t / 10, t % 10),
+            Tone::Dcs { code, inverted } => {
+                write!(f, "D{:03o}{}", code, if *inverted { 'I' } else { 'N' })
+            }
+        }
+    }
+}
+
+impl FromStr for Tone {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_ascii_uppercase();
+
+        let tone = if upper.is_empty() || upper == "NONE" {
+            Tone::None
+        } else if let Some(dcs) = upper.strip_prefix('D') {
+            let (code, inverted) = match dcs.split_at(dcs.len().saturating_sub(1)) {
+                (c, "N") => (c, false),
+                (c, "I") => (c, true),
+                _ => (dcs, false),
+            };
+
+            Tone::Dcs {
+                code: u16::from_str_radix(code, 8)
+                    .with_context(|| format!("Invalid DCS code '{}'", s))?,
+                inverted,
+            }
+        } else {
+            let (whole, tenths) = upper.split_once('.').unwrap_or((&upper, "0"));
+
+            if tenths.len() != 1 {
+                bail!("CTCSS tone '{}' should have one decimal place", s);
+            }
+
+            let whole: u16 = whole
+                .parse()
+                .with_context(|| format!("Invalid CTCSS tone '{}'", s))?;
+            let tenths: u16 = tenths
+                .parse()
+                .with_context(|| format!("Invalid CTCSS tone '{}'", s))?;
+
+            Tone::Ctcss(whole * 10 + tenths)
+        };
+
+        tone.verify()?;
+
+        Ok(tone)
+    }
+}
+
+impl From<Ctcss> for Tone {
+    fn from(c: Ctcss) -> Self {
+        match c.kind {
+            CTCSSType::None => Tone::None,
+            CTCSSType::Ctcss => Tone::Ctcss(c.freq),
+            CTCSSType::Cdcss => Tone::Dcs {
+                code: c.freq,
+                inverted: false,
+            },
+            CTCSSType::CdcssInvert => Tone::Dcs {
+                code: c.freq,
+                inverted: true,
+            },
+        }
+    }
+}
+
+impl From<Tone> for Ctcss {
+    fn from(t: Tone) -> Self {
+        let (kind, freq) = match t {
+            Tone::None => (CTCSSType::None, 0),
+            Tone::Ctcss(f) => (CTCSSType::Ctcss, f),
+            Tone::Dcs {
+                code,
+                inverted: false,
+            } => (CTCSSType::Cdcss, code),
+            Tone::Dcs {
+                code,
+                inverted: true,
+            } => (CTCSSType::CdcssInvert, code),
+        };
+
+        Ctcss { freq, kind }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tone, CTCSS_TONES, DCS_CODES};
+
+    #[test]
+    fn parse_tones() {
+        assert_eq!("88.5".parse::<Tone>().unwrap(), Tone::Ctcss(885));
+        assert_eq!("100".parse::<Tone>().unwrap(), Tone::Ctcss(1000));
+        assert_eq!(
+            "d023i".parse::<Tone>().unwrap(),
+            Tone::Dcs {
+                code: 0o23,
+                inverted: true
+            }
+        );
+        assert_eq!("none".parse::<Tone>().unwrap(), Tone::None);
+
+        assert!("88.6".parse::<Tone>().is_err());
+        assert!("88.55".parse::<Tone>().is_err());
+        assert!("D024N".parse::<Tone>().is_err());
+        assert!("D09N".parse::<Tone>().is_err());
+    }
+
+    #[test]
+    fn standard_tones_round_trip() {
+        let ctcss = CTCSS_TONES.iter().map(|t| Tone::Ctcss(*t));
+        let dcs = DCS_CODES
+            .iter()
+            .flat_map(|c| [false, true].map(|inverted| Tone::Dcs { code: *c, inverted }));
+
+        for tone in ctcss.chain(dcs) {
+            assert_eq!(tone.to_string().parse::<Tone>().unwrap(), tone);
+        }
+
+        assert_eq!(Tone::Ctcss(885).to_string(), "88.5");
+        assert_eq!(
+            Tone::Dcs {
+                code: 0o23,
+                inverted: false
+            }
+            .to_string(),
+            "D023N"
+        );
+    }
+}
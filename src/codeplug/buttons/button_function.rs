@@ -0,0 +1,72 @@
+/// A function which can be assigned to a short or long press of a key.
+///
+/// The codes are provisional. They are numbered in the order listed here and
+/// haven't been confirmed against a CPS capture or a radio, so check a code on
+/// the radio before relying on it. Codes outside this list are kept as `Other`
+/// and written back unchanged.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ButtonFunction {
+    Unassigned,
+    ZoneUp,
+    ZoneDown,
+    PowerLevel,
+    Monitor,
+    Scan,
+    Roam,
+    TalkAround,
+    Vox,
+    Emergency,
+    Backlight,
+    KeypadLock,
+    QuickText,
+    Contacts,
+    Privacy,
+    /// A function hyrps doesn't know about, carried through as read.
+    Other(u8),
+}
+
+impl From<u8> for ButtonFunction {
+    fn from(v: u8) -> Self {
+        match v {
+            0 => Self::Unassigned,
+            1 => Self::ZoneUp,
+            2 => Self::ZoneDown,
+            3 => Self::PowerLevel,
+            4 => Self::Monitor,
+            5 => Self::Scan,
+            6 => Self::Roam,
+            7 => Self::TalkAround,
+            8 => Self::Vox,
+            9 => Self::Emergency,
+            10 => Self::Backlight,
+            11 => Self::KeypadLock,
+            12 => Self::QuickText,
+            13 => Self::Contacts,
+            14 => Self::Privacy,
+            v => Self::Other(v),
+        }
+    }
+}
+
+impl From<ButtonFunction> for u8 {
+    fn from(f: ButtonFunction) -> Self {
+        match f {
+            ButtonFunction::Unassigned => 0,
+            ButtonFunction::ZoneUp => 1,
+            ButtonFunction::ZoneDown => 2,
+            ButtonFunction::PowerLevel => 3,
+            ButtonFunction::Monitor => 4,
+            ButtonFunction::Scan => 5,
+            ButtonFunction::Roam => 6,
+            ButtonFunction::TalkAround => 7,
+            ButtonFunction::Vox => 8,
+            ButtonFunction::Emergency => 9,
+            ButtonFunction::Backlight => 10,
+            ButtonFunction::KeypadLock => 11,
+            ButtonFunction::QuickText => 12,
+            ButtonFunction::Contacts => 13,
+            ButtonFunction::Privacy => 14,
+            ButtonFunction::Other(v) => v,
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use self::button_function::ButtonFunction;
+use super::{
+    cp_data::CPData,
+    disp_tabular::DisplayTabular,
+    layout::{CPLayout, Field},
+    Codeplug, DeviceType,
+};
+use hyrps_derive::RawCPData;
+
+#[cfg(test)]
+use proptest::prelude::*;
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+pub mod button_function;
+
+/// Number of programmable keys in the button section.
+pub const NUM_KEYS: usize = 8;
+
+const PORTABLE_KEYS: [&str; NUM_KEYS] = ["SK1", "SK2", "TK", "P1", "P2", "P3", "P4", "P5"];
+const MOBILE_KEYS: [&str; NUM_KEYS] = [
+    "P1", "P2", "P3", "P4", "Mic P1", "Mic P2", "Mic P3", "Mic P4",
+];
+
+#[cfg(test)]
+fn keys_strategy() -> impl Strategy<Value = [KeyAssignment; NUM_KEYS]> {
+    prop::array::uniform8(
+        any::<(u8, u8)>().prop_map(|(s, l)| KeyAssignment::new(s.into(), l.into())),
+    )
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, RawCPData)]
+pub struct KeyAssignment {
+    #[cp(repr(u8))]
+    pub short_press: ButtonFunction,
+    #[cp(repr(u8))]
+    pub long_press: ButtonFunction,
+}
+
+impl KeyAssignment {
+    pub fn new(short_press: ButtonFunction, long_press: ButtonFunction) -> Self {
+        KeyAssignment {
+            short_press,
+            long_press,
+        }
+    }
+}
+
+/// Programmable key assignments. The section holds a single element. Which
+/// physical key each slot is depends on whether the radio is a portable or a
+/// mobile.
+///
+/// The menu configuration section hasn't been identified yet, so menus are
+/// carried through untouched with the other raw sections.
+#[derive(Debug, PartialEq, Eq, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct Buttons {
+    #[cfg_attr(test, proptest(strategy = "keys_strategy()"))]
+    pub keys: [KeyAssignment; NUM_KEYS],
+    /// How long a key must be held for a long press, in units of 100ms.
+    pub long_press_duration: u8,
+    /// Not decoded yet, carried through as read.
+    pub trailer: [u8; 7],
+}
+
+impl Buttons {
+    /// Sensible assignments for the keys of `device_type`. These use the
+    /// unconfirmed `ButtonFunction` codes, so nothing applies them unless asked
+    /// to.
+    pub fn defaults(device_type: DeviceType) -> Self {
+        use ButtonFunction::*;
+
+        let unassigned = KeyAssignment::new(Unassigned, Unassigned);
+        let mut keys = [unassigned; NUM_KEYS];
+
+        let assigned: &[(ButtonFunction, ButtonFunction)] = match device_type {
+            DeviceType::Portable => &[
+                (Monitor, TalkAround),
+                (Scan, Roam),
+                (Emergency, Emergency),
+                (ZoneUp, ZoneDown),
+                (PowerLevel, KeypadLock),
+            ],
+            DeviceType::Mobile => &[
+                (ZoneUp, ZoneDown),
+                (Scan, Roam),
+                (PowerLevel, TalkAround),
+                (Monitor, Emergency),
+            ],
+        };
+
+        for (k, (short, long)) in keys.iter_mut().zip(assigned) {
+            *k = KeyAssignment::new(*short, *long);
+        }
+
+        Buttons {
+            keys,
+            long_press_duration: 10,
+            trailer: [0; 7],
+        }
+    }
+
+    pub fn key_names(device_type: DeviceType) -> [&'static str; NUM_KEYS] {
+        match device_type {
+            DeviceType::Portable => PORTABLE_KEYS,
+            DeviceType::Mobile => MOBILE_KEYS,
+        }
+    }
+}
+
+impl CPData for Buttons {
+    fn cp_section() -> u16 {
+        0x33
+    }
+}
+
+impl CPLayout for Buttons {
+    const LAYOUT: &'static [Field] = &[
+        Field::new(0, 1, "keys[0].short_press"),
+        Field::new(1, 1, "keys[0].long_press"),
+        Field::new(2, 1, "keys[1].short_press"),
+        Field::new(3, 1, "keys[1].long_press"),
+        Field::new(4, 1, "keys[2].short_press"),
+        Field::new(5, 1, "keys[2].long_press"),
+        Field::new(6, 1, "keys[3].short_press"),
+        Field::new(7, 1, "keys[3].long_press"),
+        Field::new(8, 1, "keys[4].short_press"),
+        Field::new(9, 1, "keys[4].long_press"),
+        Field::new(10, 1, "keys[5].short_press"),
+        Field::new(11, 1, "keys[5].long_press"),
+        Field::new(12, 1, "keys[6].short_press"),
+        Field::new(13, 1, "keys[6].long_press"),
+        Field::new(14, 1, "keys[7].short_press"),
+        Field::new(15, 1, "keys[7].long_press"),
+        Field::new(16, 1, "long_press_duration"),
+        Field::unknown(17, 7, "trailer"),
+    ];
+}
+
+impl DisplayTabular for (&str, &KeyAssignment) {
+    fn get_heading() -> Vec<String> {
+        ["Key", "Short Press", "Long Press"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn get_row(&self, _codeplug: &Codeplug) -> Vec<String> {
+        vec![
+            self.0.to_string(),
+            format!("{:?}", self.1.short_press),
+            format!("{:?}", self.1.long_press),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_serde;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn buttons_serde(buttons in any::<super::Buttons>()) {
+            check_serde(&buttons)?;
+        }
+    }
+}
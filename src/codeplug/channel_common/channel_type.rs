@@ -25,3 +25,9 @@ impl TryFrom<u8> for ChannelType {
         }
     }
 }
+
+impl From<ChannelType> for u8 {
+    fn from(v: ChannelType) -> Self {
+        v as u8
+    }
+}
@@ -1,15 +1,13 @@
+use super::channel_common::channel_type::ChannelType;
 use super::disp_tabular::DisplayTabular;
 use super::Codeplug;
-use super::{channel_common::channel_type::ChannelType, cp_data::RawCPData};
 #[cfg(test)]
 use crate::tests::check_serde;
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use hyrps_derive::RawCPData;
 #[cfg(test)]
 use proptest::prelude::*;
 #[cfg(test)]
 use proptest_derive::Arbitrary;
-use std::convert::TryFrom;
-use std::io::{Read, Write};
 
 pub mod channel_type;
 pub mod power_level;
@@ -20,59 +18,22 @@ impl Default for ChannelType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, RawCPData)]
 #[cfg_attr(test, derive(Arbitrary))]
 pub struct ChannelCommon {
     #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
     pub name: String,
+    #[cp(repr(u8))]
     pub chan_type: ChannelType,
+    #[cp(bitfield(u8), bits(0x01))]
     pub rx_only: bool,
+    #[cp(bits(0x04))]
     pub power_level: power_level::PowerLevel,
+    #[cp(magic(u16 = 0))]
     pub rx_freq: u32,
     pub tx_freq: u32,
 }
 
-impl RawCPData for ChannelCommon {
-    fn load(reader: &mut impl Read) -> anyhow::Result<ChannelCommon> {
-        let name = String::load(reader)?;
-
-        let chan_type = ChannelType::try_from(reader.read_u8()?)?;
-
-        let b1 = reader.read_u8()?;
-        let rx_only = (b1 & 0x1) != 0;
-        let power_level = power_level::PowerLevel::from(b1);
-
-        assert_eq!(reader.read_u16::<LittleEndian>()?, 0);
-
-        let rx_freq = reader.read_u32::<LittleEndian>()?;
-        let tx_freq = reader.read_u32::<LittleEndian>()?;
-
-        Ok(ChannelCommon {
-            name,
-            chan_type,
-            rx_only,
-            power_level,
-            rx_freq,
-            tx_freq,
-        })
-    }
-
-    fn store(&self, writer: &mut impl Write) -> anyhow::Result<()> {
-        self.name.store(writer)?;
-
-        writer.write_u8(self.chan_type as u8)?;
-
-        writer.write_u8((self.rx_only as u8) | ((self.power_level as u8) << 2))?;
-
-        writer.write_u16::<LittleEndian>(0)?;
-
-        writer.write_u32::<LittleEndian>(self.rx_freq)?;
-        writer.write_u32::<LittleEndian>(self.tx_freq)?;
-
-        Ok(())
-    }
-}
-
 impl DisplayTabular for ChannelCommon {
     fn get_heading() -> Vec<String> {
         ["Name", "TX Freq", "RX Freq", "Power", "RX Only"]
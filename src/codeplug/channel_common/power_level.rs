@@ -10,10 +10,16 @@ pub enum PowerLevel {
 
 impl From<u8> for PowerLevel {
     fn from(v: u8) -> Self {
-        match v & 0x4 {
+        match v & 0x1 {
             0 => PowerLevel::High,
-            4 => PowerLevel::Low,
+            1 => PowerLevel::Low,
             _ => unreachable!(),
         }
     }
 }
+
+impl From<PowerLevel> for u8 {
+    fn from(v: PowerLevel) -> Self {
+        v as u8
+    }
+}
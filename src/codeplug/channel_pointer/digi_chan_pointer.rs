@@ -57,6 +57,26 @@ impl RawCPData for DigiChannelPointer {
     }
 }
 
+/// Channel fields which may be unset store zero rather than a pointer.
+impl RawCPData for Option<DigiChannelPointer> {
+    fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let idx = reader.read_u16::<LittleEndian>()?;
+
+        if idx == 0 {
+            return Ok(None);
+        }
+
+        DigiChannelPointer::load(&mut &idx.to_le_bytes()[..]).map(Some)
+    }
+
+    fn store(&self, writer: &mut impl std::io::Write) -> anyhow::Result<()> {
+        match self {
+            Some(p) => p.store(writer),
+            None => Ok(writer.write_u16::<LittleEndian>(0)?),
+        }
+    }
+}
+
 impl TryFrom<&ChannelPointer> for DigiChannelPointer {
     type Error = anyhow::Error;
 
@@ -104,6 +124,13 @@ mod tests {
         }
     }
 
+    proptest! {
+        #[test]
+        fn optional_digi_channel_pointer_serde(dcp in any::<Option<DigiChannelPointer>>()) {
+            check_serde(&dcp)?;
+        }
+    }
+
     proptest! {
         #[test]
         fn digi_channel_conversion(dcp in any::<DigiChannelPointer>()) {
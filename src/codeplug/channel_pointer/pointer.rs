@@ -3,6 +3,7 @@ use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 
+use hyrps_derive::RawCPData;
 use std::io::Read;
 
 use crate::codeplug::{cp_data::RawCPData, Codeplug};
@@ -43,9 +44,13 @@ impl RawCPData for ChannelPointer {
             return Ok(ChannelPointer::Selected);
         }
 
+        let idx = idx
+            .checked_sub(1)
+            .ok_or_else(|| anyhow!("Channel pointer to target {} has no index", target))?;
+
         match target {
-            0 => Ok(ChannelPointer::Digital(idx - 1)),
-            1 => Ok(ChannelPointer::Analog(idx - 1)),
+            0 => Ok(ChannelPointer::Digital(idx)),
+            1 => Ok(ChannelPointer::Analog(idx)),
             _ => bail!("Unknown channel pointer target: {}", target),
         }
     }
@@ -71,6 +76,54 @@ impl RawCPData for ChannelPointer {
     }
 }
 
+/// A channel pointer which may be unset, as zones store their default
+/// channel. The flags byte isn't decoded, so it's kept as read, as are
+/// pointers to targets hyrps doesn't know.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct OptionalPointer {
+    idx: u16,
+    target: u8,
+    pub flags: u8,
+}
+
+impl OptionalPointer {
+    pub fn unset() -> Self {
+        OptionalPointer {
+            idx: 0,
+            target: 0,
+            flags: 0,
+        }
+    }
+
+    /// The channel pointed to, if it's set and to a known target.
+    pub fn get(&self) -> Option<ChannelPointer> {
+        match (self.idx, self.target) {
+            (0, _) => None,
+            (0xffff, _) => Some(ChannelPointer::Selected),
+            (i, 0) => Some(ChannelPointer::Digital(i - 1)),
+            (i, 1) => Some(ChannelPointer::Analog(i - 1)),
+            _ => None,
+        }
+    }
+
+    /// Point to `chan`, or unset the pointer with `None`. The flags byte is
+    /// left as it was.
+    pub fn set(&mut self, chan: Option<ChannelPointer>) {
+        (self.idx, self.target) = match chan {
+            None => (0, 0),
+            Some(ChannelPointer::Selected) => (0xffff, 0),
+            Some(ChannelPointer::Digital(i)) => (i + 1, 0),
+            Some(ChannelPointer::Analog(i)) => (i + 1, 1),
+        };
+    }
+
+    /// Whether the pointer is set, but to a target hyrps doesn't know.
+    pub fn is_unknown(&self) -> bool {
+        self.idx != 0 && self.get().is_none()
+    }
+}
+
 impl ChannelPointer {
     pub fn get_chan_name(&self, codeplug: &Codeplug) -> String {
         match self {
@@ -105,7 +158,7 @@ impl RawPointer for ChannelPointer {
                 .ok_or_else(|| anyhow!("Invalid {:?}", self))
                 .map(|_| ()),
             Self::Analog(i) => cp
-                .digi_chans
+                .ana_chans
                 .data
                 .get(*i as usize)
                 .ok_or_else(|| anyhow!("Invalid {:?}", self))
@@ -117,7 +170,7 @@ impl RawPointer for ChannelPointer {
 #[cfg(test)]
 mod tests {
 
-    use crate::tests::check_serde;
+    use crate::{codeplug::cp_data::RawCPData, tests::check_serde};
     use proptest::prelude::*;
 
     proptest! {
@@ -126,4 +179,31 @@ mod tests {
             check_serde(&cp)?;
         }
     }
+
+    proptest! {
+        #[test]
+        fn optional_pointer_serde(cp in any::<super::OptionalPointer>()) {
+            check_serde(&cp)?;
+        }
+    }
+
+    #[test]
+    fn optional_pointer_keeps_raw_bytes() {
+        let raw = [0x03, 0x00, 0x07, 0x5a];
+        let mut ptr = super::OptionalPointer::load(&mut &raw[..]).unwrap();
+        assert!(ptr.is_unknown());
+        assert_eq!(ptr.get(), None);
+
+        let mut stored = vec![];
+        ptr.store(&mut stored).unwrap();
+        assert_eq!(stored, raw);
+
+        ptr.set(Some(super::ChannelPointer::Analog(4)));
+        assert_eq!(ptr.get(), Some(super::ChannelPointer::Analog(4)));
+        assert_eq!(ptr.flags, 0x5a);
+
+        ptr.set(None);
+        assert_eq!(ptr.get(), None);
+        assert!(!ptr.is_unknown());
+    }
 }
@@ -26,3 +26,9 @@ impl TryFrom<u8> for CallType {
         }
     }
 }
+
+impl From<CallType> for u8 {
+    fn from(v: CallType) -> Self {
+        v as u8
+    }
+}
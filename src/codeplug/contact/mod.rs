@@ -1,32 +1,57 @@
 use super::{
-    cp_data::{CPData, RawCPData},
+    cp_data::CPData,
     disp_tabular::DisplayTabular,
+    layout::{CPLayout, Field},
     Codeplug,
 };
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::{convert::TryFrom, fmt::Debug, io::Read};
+use hyrps_derive::RawCPData;
+use std::fmt::{self, Debug};
 
+#[cfg(test)]
+use crate::tests::unknown_bits;
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 
 pub mod call_type;
+pub mod pointer;
+pub mod talkgroups;
+pub mod user_contact;
+pub mod users_csv;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, RawCPData)]
 #[cfg_attr(test, derive(Arbitrary))]
+#[cp(magic(u32 = 0))]
 pub struct Contact {
+    /// Ring played for an incoming call from the contact, zero for none.
+    #[cfg_attr(test, proptest(strategy = "0..16u8"))]
+    #[cp(bitfield(u16), bits(0x000f))]
+    pub ring_style: u8,
+    /// Not decoded yet, carried through as read.
+    #[cfg_attr(test, proptest(strategy = "unknown_bits(0xfff0)"))]
+    #[cp(bits(0xfff0))]
     pub unk1: u16,
+    /// Play a tone when a call from the contact is received.
+    #[cp(bitfield(u16), bits(0x0001))]
+    pub call_receive_tone: bool,
+    /// Not decoded yet, carried through as read.
+    #[cfg_attr(test, proptest(strategy = "unknown_bits(0xfffe)"))]
+    #[cp(bits(0xfffe))]
     pub unk2: u16,
     #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
     pub name: String,
+    #[cp(repr(u8))]
     pub call_type: call_type::CallType,
     pub is_ref: bool,
+    #[cp(magic(u16 = 0))]
     pub id: u32,
 }
 
 impl Contact {
     pub fn new(name: String, call_type: call_type::CallType, id: u32) -> Contact {
         Contact {
+            ring_style: 0,
             unk1: 0,
+            call_receive_tone: false,
             unk2: 0,
             name,
             call_type,
@@ -36,40 +61,27 @@ impl Contact {
     }
 }
 
-impl RawCPData for Contact {
-    fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let unk1 = reader.read_u16::<LittleEndian>()?;
-        let unk2 = reader.read_u16::<LittleEndian>()?;
-        let name = String::load(reader)?;
-        let call_type = call_type::CallType::try_from(reader.read_u8()?)?;
-        let is_ref = reader.read_u8()? != 0;
-        assert_eq!(reader.read_u16::<LittleEndian>()?, 0);
-        let id = reader.read_u32::<LittleEndian>()?;
-        assert_eq!(reader.read_u32::<LittleEndian>()?, 0);
-
-        Ok(Contact {
-            unk1,
-            unk2,
-            name,
-            call_type,
-            is_ref,
-            id,
-        })
-    }
-
-    fn store(&self, writer: &mut impl std::io::Write) -> anyhow::Result<()> {
-        writer.write_u16::<LittleEndian>(self.unk1)?;
-        writer.write_u16::<LittleEndian>(self.unk2)?;
-
-        self.name.store(writer)?;
-
-        writer.write_u8(self.call_type as u8)?;
-        writer.write_u8(self.is_ref as u8)?;
-        writer.write_u16::<LittleEndian>(0)?;
-        writer.write_u32::<LittleEndian>(self.id)?;
-        writer.write_u32::<LittleEndian>(0)?;
+/// What happened to each entry of a bulk contact import.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub added: usize,
+    /// Entries whose ID is already in the list, or earlier in the import.
+    pub duplicates: usize,
+    /// Entries which didn't match the import filter.
+    pub filtered: usize,
+    /// Entries with an ID the radio can't use.
+    pub invalid: usize,
+    /// Entries left out because the section was full.
+    pub no_space: usize,
+}
 
-        Ok(())
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Added {} contacts, skipped {} duplicates, {} filtered, {} invalid and {} which didn't fit",
+            self.added, self.duplicates, self.filtered, self.invalid, self.no_space
+        )
     }
 }
 
@@ -79,9 +91,26 @@ impl CPData for Contact {
     }
 }
 
+impl CPLayout for Contact {
+    const LAYOUT: &'static [Field] = &[
+        Field::bits(0, 0x0f, "ring_style"),
+        Field::unknown_bits(0, 0xf0, "unk1"),
+        Field::unknown(1, 1, "unk1"),
+        Field::bits(2, 0x01, "call_receive_tone"),
+        Field::unknown_bits(2, 0xfe, "unk2"),
+        Field::unknown(3, 1, "unk2"),
+        Field::utf16(4, 32, "name"),
+        Field::new(36, 1, "call_type"),
+        Field::new(37, 1, "is_ref"),
+        Field::unknown(38, 2, "unk3"),
+        Field::new(40, 4, "id"),
+        Field::unknown(44, 4, "unk4"),
+    ];
+}
+
 impl DisplayTabular for Contact {
     fn get_heading() -> Vec<String> {
-        ["Name", "Type", "ID", "Unk1", "Unk2"]
+        ["Name", "Type", "ID", "Ring", "RX Tone"]
             .iter()
             .map(|s| s.to_string())
             .collect()
@@ -92,8 +121,11 @@ impl DisplayTabular for Contact {
             (*self.name).to_string(),
             format!("{:?}", self.call_type),
             self.id.to_string(),
-            format!("{}", self.unk1),
-            format!("{}", self.unk2),
+            match self.ring_style {
+                0 => "Off".to_string(),
+                n => format!("Style {}", n),
+            },
+            format!("{}", self.call_receive_tone),
         ]
     }
 }
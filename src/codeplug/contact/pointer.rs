@@ -0,0 +1,76 @@
+use anyhow::anyhow;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+use std::io::Read;
+
+use crate::codeplug::{channel_pointer::raw_pointer::RawPointer, cp_data::RawCPData, Codeplug};
+
+#[cfg(test)]
+use proptest::strategy::Strategy;
+
+#[cfg(test)]
+fn idx_strategy() -> impl Strategy<Value = u16> {
+    0..(u16::MAX - 1)
+}
+
+/// Index of an entry in the contact section, stored one based.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct ContactPointer(#[cfg_attr(test, proptest(strategy = "idx_strategy()"))] pub u16);
+
+impl ContactPointer {
+    pub fn get_contact_name(&self, codeplug: &Codeplug) -> String {
+        codeplug
+            .contacts
+            .data
+            .get(self.0 as usize)
+            .map_or_else(|| "<Invalid>".to_string(), |c| c.name.clone())
+    }
+}
+
+impl RawCPData for ContactPointer {
+    fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let idx = reader.read_u16::<LittleEndian>()?;
+
+        if idx == 0 {
+            return Err(anyhow!("Null contact pointer"));
+        }
+
+        Ok(ContactPointer(idx - 1))
+    }
+
+    fn store(&self, writer: &mut impl std::io::Write) -> anyhow::Result<()> {
+        writer.write_u16::<LittleEndian>(self.0 + 1)?;
+
+        Ok(())
+    }
+}
+
+impl RawPointer for ContactPointer {
+    fn sz() -> usize {
+        0x2
+    }
+
+    fn verify(&self, cp: &Codeplug) -> anyhow::Result<()> {
+        cp.contacts
+            .data
+            .get(self.0 as usize)
+            .ok_or_else(|| anyhow!("Invalid {:?}", self))
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_serde;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn contact_pointer_serde(cp in any::<super::ContactPointer>()) {
+            check_serde(&cp)?;
+        }
+    }
+}
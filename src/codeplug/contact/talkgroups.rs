@@ -0,0 +1,281 @@
+//! Talkgroup lists saved from a DMR network, for `contacts import-talkgroups`.
+//! JSON may be an object mapping IDs to names (or to objects with a name), or
+//! an array of objects, as served by BrandMeister and TGIF:
+//!
+//! ```text
+//! {"235": "UK Call", "2351": "CQ-UK"}
+//! [{"id": 235, "name": "UK Call"}, {"talkgroup": "2351", "name": "CQ-UK"}]
+//! ```
+//!
+//! Anything else is read as CSV with an ID (or `TALKGROUP`/`TG`) and `NAME`
+//! column.
+
+use crate::codeplug::cp_data::truncate_name;
+use anyhow::{anyhow, bail, Context};
+use serde_json::Value;
+use std::{convert::TryFrom, ops::RangeInclusive, str::FromStr};
+
+/// Contact name template used by default, matching the generated codeplug.
+pub const DEFAULT_NAME_FORMAT: &str = "{id} {name}";
+
+const ID_KEYS: &[&str] = &["id", "talkgroup", "tg"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Talkgroup {
+    pub id: u32,
+    pub name: String,
+}
+
+impl Talkgroup {
+    /// Contact name for the talkgroup, with `{id}` and `{name}` in `format`
+    /// replaced and cut down to fit the codeplug.
+    pub fn contact_name(&self, format: &str) -> String {
+        truncate_name(
+            &format
+                .replace("{id}", &self.id.to_string())
+                .replace("{name}", &self.name),
+        )
+    }
+}
+
+/// An inclusive range of talkgroup IDs, written `80-83` or `235`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdRange(pub RangeInclusive<u32>);
+
+impl FromStr for IdRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<u32>()
+                .map_err(|_| anyhow!("Invalid talkgroup ID: {}", v))
+        };
+
+        let range = match s.split_once('-') {
+            Some((start, end)) => parse(start)?..=parse(end)?,
+            None => parse(s)?..=parse(s)?,
+        };
+
+        if range.is_empty() {
+            bail!("Empty talkgroup range: {}", s);
+        }
+
+        Ok(IdRange(range))
+    }
+}
+
+/// Which talkgroups to import. A talkgroup is imported if it is in any of the
+/// ranges or its name matches any of the patterns, or if both are empty.
+#[derive(Debug, Default)]
+pub struct TalkgroupFilter {
+    pub ranges: Vec<IdRange>,
+    /// Case insensitive names where `*` matches any run of characters and `?`
+    /// any single one.
+    pub patterns: Vec<String>,
+}
+
+/// Match `name` against the glob `pattern`, ignoring case.
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|i| glob_match(rest, &name[i..])),
+        Some((p, rest)) => match name.split_first() {
+            Some((n, name)) => (*p == '?' || p.eq_ignore_ascii_case(n)) && glob_match(rest, name),
+            None => false,
+        },
+    }
+}
+
+impl TalkgroupFilter {
+    pub fn matches(&self, tg: &Talkgroup) -> bool {
+        if self.ranges.is_empty() && self.patterns.is_empty() {
+            return true;
+        }
+
+        let name: Vec<char> = tg.name.chars().collect();
+
+        self.ranges.iter().any(|r| r.0.contains(&tg.id))
+            || self.patterns.iter().any(|p| {
+                let pattern: Vec<char> = p.chars().collect();
+                glob_match(&pattern, &name)
+            })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Talkgroups {
+    pub talkgroups: Vec<Talkgroup>,
+    /// Entries whose ID couldn't be read.
+    pub invalid: usize,
+}
+
+/// Read an ID given either as a number or a string.
+fn json_id(v: &Value) -> Option<u32> {
+    match v {
+        Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Look up the first of `keys` in `obj`, ignoring case.
+fn json_field<'a>(obj: &'a serde_json::Map<String, Value>, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter().find_map(|k| {
+        obj.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(k))
+            .map(|(_, v)| v)
+    })
+}
+
+fn json_name(v: Option<&Value>) -> String {
+    match v {
+        Some(Value::String(s)) => s.trim().to_string(),
+        Some(Value::Object(obj)) => json_name(json_field(obj, &["name"])),
+        _ => String::new(),
+    }
+}
+
+impl Talkgroups {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        match text.trim_start().chars().next() {
+            Some('{') | Some('[') => {
+                Self::parse_json(serde_json::from_str(text).context("Could not read JSON")?)
+            }
+            _ => Self::parse_csv(text),
+        }
+    }
+
+    fn parse_json(json: Value) -> anyhow::Result<Self> {
+        let mut parsed = Talkgroups::default();
+        let mut add = |id: Option<u32>, name: String| match id {
+            Some(id) => parsed.talkgroups.push(Talkgroup { id, name }),
+            None => parsed.invalid += 1,
+        };
+
+        match json {
+            Value::Object(obj) => {
+                for (id, v) in obj.iter() {
+                    add(id.trim().parse().ok(), json_name(Some(v)));
+                }
+            }
+            Value::Array(arr) => {
+                for v in arr.iter() {
+                    match v {
+                        Value::Object(obj) => add(
+                            json_field(obj, ID_KEYS).and_then(json_id),
+                            json_name(json_field(obj, &["name"])),
+                        ),
+                        _ => add(None, String::new()),
+                    }
+                }
+            }
+            _ => bail!("JSON talkgroup list must be an object or an array"),
+        }
+
+        Ok(parsed)
+    }
+
+    fn parse_csv(text: &str) -> anyhow::Result<Self> {
+        let mut csv = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(text.as_bytes());
+        let headers = csv.headers().context("Could not read CSV header")?.clone();
+        let column = |names: &[&str]| {
+            headers
+                .iter()
+                .position(|h| names.iter().any(|n| h.trim().eq_ignore_ascii_case(n)))
+        };
+
+        let (id, name) = match (column(ID_KEYS), column(&["name"])) {
+            (Some(id), Some(name)) => (id, name),
+            _ => bail!("CSV needs ID and NAME columns"),
+        };
+
+        let mut parsed = Talkgroups::default();
+
+        for (n, record) in csv.records().enumerate() {
+            let record = record.with_context(|| format!("Line {}", n + 2))?;
+
+            match record.get(id).unwrap_or("").trim().parse() {
+                Ok(id) => parsed.talkgroups.push(Talkgroup {
+                    id,
+                    name: record.get(name).unwrap_or("").trim().to_string(),
+                }),
+                Err(_) => parsed.invalid += 1,
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdRange, Talkgroup, TalkgroupFilter, Talkgroups, DEFAULT_NAME_FORMAT};
+
+    fn ids(tgs: &Talkgroups) -> Vec<u32> {
+        tgs.talkgroups.iter().map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn parse_json() {
+        let map =
+            Talkgroups::parse(r#"{"235": "UK Call", "2351": {"Name": "CQ-UK"}, "x": ""}"#).unwrap();
+
+        assert_eq!(ids(&map), [235, 2351]);
+        assert_eq!(map.talkgroups[1].name, "CQ-UK");
+        assert_eq!(map.invalid, 1);
+
+        let arr = Talkgroups::parse(
+            r#"[{"id": 91, "name": "Worldwide"}, {"TalkGroup": "80", "name": "UK UA"}, 3]"#,
+        )
+        .unwrap();
+
+        assert_eq!(ids(&arr), [91, 80]);
+        assert_eq!(arr.talkgroups[1].name, "UK UA");
+        assert_eq!(arr.invalid, 1);
+        assert!(Talkgroups::parse("[1,").is_err());
+    }
+
+    #[test]
+    fn parse_csv() {
+        let csv = Talkgroups::parse("TG,Name,Country\n235,UK Call,GB\nbad,X,\n9,Local,\n").unwrap();
+
+        assert_eq!(ids(&csv), [235, 9]);
+        assert_eq!(csv.invalid, 1);
+        assert!(Talkgroups::parse("ID,CALLSIGN\n1,A\n").is_err());
+    }
+
+    #[test]
+    fn filter_and_name() {
+        let tg = |id, name: &str| Talkgroup {
+            id,
+            name: name.to_string(),
+        };
+        let filter = TalkgroupFilter {
+            ranges: vec!["80-83".parse().unwrap(), "235".parse().unwrap()],
+            patterns: vec!["cq-*".to_string(), "Echo?".to_string()],
+        };
+
+        assert!(filter.matches(&tg(81, "UK UA")));
+        assert!(filter.matches(&tg(235, "UK Call")));
+        assert!(filter.matches(&tg(2351, "CQ-UK")));
+        assert!(!filter.matches(&tg(9990, "Echo")));
+        assert!(filter.matches(&tg(9991, "Echo2")));
+        assert!(!filter.matches(&tg(84, "UK UA")));
+        assert!(TalkgroupFilter::default().matches(&tg(84, "UK UA")));
+
+        assert!("83-80".parse::<IdRange>().is_err());
+        assert!("a-80".parse::<IdRange>().is_err());
+
+        assert_eq!(
+            tg(235, "UK Call").contact_name(DEFAULT_NAME_FORMAT),
+            "235 UK Call"
+        );
+        assert_eq!(
+            tg(2351, "CQ-UK Calling Channel").contact_name("TG{id} {name}"),
+            "TG2351 CQ-UK Cal"
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use crate::codeplug::{
+    cp_data::CPData,
+    disp_tabular::DisplayTabular,
+    layout::{CPLayout, Field},
+    settings::MAX_RADIO_ID,
+    Codeplug,
+};
+use anyhow::bail;
+use hyrps_derive::RawCPData;
+
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+/// An entry in the digital contact list, which holds many more private call
+/// contacts than the contacts section. The radio uses it to show names for
+/// incoming calls and for manual dialling.
+#[derive(Debug, Clone, PartialEq, Eq, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct UserContact {
+    #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
+    pub name: String,
+    pub id: u32,
+}
+
+impl CPData for UserContact {
+    fn cp_section() -> u16 {
+        0x2f
+    }
+
+    fn verify(&self, _codeplug: &Codeplug) -> anyhow::Result<()> {
+        if self.id == 0 || self.id > MAX_RADIO_ID {
+            bail!("Digital contact '{}' has invalid ID {}", self.name, self.id);
+        }
+
+        Ok(())
+    }
+}
+
+impl CPLayout for UserContact {
+    const LAYOUT: &'static [Field] = &[Field::utf16(0, 32, "name"), Field::new(32, 4, "id")];
+}
+
+impl DisplayTabular for UserContact {
+    fn get_heading() -> Vec<String> {
+        ["Name", "ID"].iter().map(|s| s.to_string()).collect()
+    }
+
+    fn get_row(&self, _codeplug: &Codeplug) -> Vec<String> {
+        vec![self.name.clone(), self.id.to_string()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_serde;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn user_contact_serde(contact in any::<super::UserContact>()) {
+            check_serde(&contact)?;
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! DMR user database CSV, as downloaded from radioid.net, for `contacts
+//! import-users`. Columns are found by their header, so only `RADIO_ID` and
+//! `CALLSIGN` are required:
+//!
+//! ```text
+//! RADIO_ID,CALLSIGN,FIRST_NAME,LAST_NAME,CITY,STATE,COUNTRY
+//! 2350001,M0ABC,Alice,Smith,London,England,United Kingdom
+//! ```
+
+use crate::codeplug::cp_data::truncate_name;
+use anyhow::{bail, Context};
+use std::io::Read;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub id: u32,
+    pub callsign: String,
+    pub first_name: String,
+    pub region: String,
+    pub country: String,
+}
+
+impl User {
+    /// Contact name for the user, cut down to fit the codeplug.
+    pub fn contact_name(&self) -> String {
+        truncate_name(&format!("{} {}", self.callsign, self.first_name))
+    }
+}
+
+/// Which users to import. Each list matches if it is empty or any of its
+/// entries match, and a user must match every list.
+#[derive(Debug, Default)]
+pub struct UserFilter {
+    pub countries: Vec<String>,
+    pub regions: Vec<String>,
+    pub prefixes: Vec<String>,
+}
+
+impl UserFilter {
+    pub fn matches(&self, user: &User) -> bool {
+        let any = |list: &[String], f: &dyn Fn(&str) -> bool| {
+            list.is_empty() || list.iter().any(|v| f(&v.to_ascii_uppercase()))
+        };
+
+        any(&self.countries, &|c| user.country.to_ascii_uppercase() == c)
+            && any(&self.regions, &|r| user.region.to_ascii_uppercase() == r)
+            && any(&self.prefixes, &|p| {
+                user.callsign.to_ascii_uppercase().starts_with(p)
+            })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct UsersCsv {
+    pub users: Vec<User>,
+    /// Rows whose radio ID couldn't be read.
+    pub invalid: usize,
+}
+
+/// Index of the first column whose header is one of `names`.
+pub(crate) fn column(headers: &csv::StringRecord, names: &[&str]) -> Option<usize> {
+    headers
+        .iter()
+        .position(|h| names.iter().any(|n| h.trim().eq_ignore_ascii_case(n)))
+}
+
+impl UsersCsv {
+    pub fn parse(reader: impl Read) -> anyhow::Result<Self> {
+        let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+        let headers = csv.headers().context("Could not read CSV header")?.clone();
+
+        let (id, callsign) = match (
+            column(&headers, &["RADIO_ID", "ID"]),
+            column(&headers, &["CALLSIGN"]),
+        ) {
+            (Some(id), Some(callsign)) => (id, callsign),
+            _ => bail!("CSV needs RADIO_ID and CALLSIGN columns"),
+        };
+        let first_name = column(&headers, &["FIRST_NAME", "NAME"]);
+        let region = column(&headers, &["STATE", "REGION"]);
+        let country = column(&headers, &["COUNTRY"]);
+
+        let mut parsed = UsersCsv::default();
+
+        for (n, record) in csv.records().enumerate() {
+            let record = record.with_context(|| format!("Line {}", n + 2))?;
+            let field = |i: Option<usize>| {
+                i.and_then(|i| record.get(i))
+                    .unwrap_or("")
+                    .trim()
+                    .to_string()
+            };
+
+            match field(Some(id)).parse() {
+                Ok(id) => parsed.users.push(User {
+                    id,
+                    callsign: field(Some(callsign)),
+                    first_name: field(first_name),
+                    region: field(region),
+                    country: field(country),
+                }),
+                Err(_) => parsed.invalid += 1,
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UserFilter, UsersCsv};
+
+    const CSV: &str = "RADIO_ID,CALLSIGN,FIRST_NAME,LAST_NAME,CITY,STATE,COUNTRY\n\
+        2350001,M0ABC,Alice,Smith,London,England,United Kingdom\n\
+        2350002,GM0XYZ,Bartholomew-Jones,Brown,\"Edinburgh, Leith\",Scotland,United Kingdom\n\
+        bad,M0BAD,Eve,,,,\n\
+        3100001,K1ABC,Carol,,Boston,Massachusetts,United States\n";
+
+    #[test]
+    fn parse_users() {
+        let csv = UsersCsv::parse(CSV.as_bytes()).unwrap();
+
+        assert_eq!(csv.users.len(), 3);
+        assert_eq!(csv.invalid, 1);
+        assert_eq!(csv.users[1].region, "Scotland");
+        assert_eq!(csv.users[0].contact_name(), "M0ABC Alice");
+        assert_eq!(csv.users[1].contact_name(), "GM0XYZ Bartholom");
+    }
+
+    #[test]
+    fn filter_users() {
+        let csv = UsersCsv::parse(CSV.as_bytes()).unwrap();
+        let filter = UserFilter {
+            countries: vec!["united kingdom".to_string()],
+            prefixes: vec!["M".to_string(), "2E".to_string()],
+            ..Default::default()
+        };
+
+        let ids: Vec<u32> = csv
+            .users
+            .iter()
+            .filter(|u| filter.matches(u))
+            .map(|u| u.id)
+            .collect();
+
+        assert_eq!(ids, [2350001]);
+        assert!(UsersCsv::parse("ID,NAME\n1,A\n".as_bytes()).is_err());
+    }
+}
@@ -5,7 +5,10 @@ use super::{
 };
 use anyhow::bail;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::io::{Cursor, Error, ErrorKind, Read, Write};
+use std::{
+    convert::TryInto,
+    io::{Cursor, Error, ErrorKind, Read, Write},
+};
 
 pub trait RawCPData: Sized {
     fn load(reader: &mut impl Read) -> anyhow::Result<Self>;
@@ -29,11 +32,29 @@ pub trait CPData: RawCPData {
             .ok_or_else(|| Error::new(ErrorKind::NotFound, "No section found"))
     }
 
+    /// Whether elements are stored at exactly the section's element size.
+    /// Channel pointer lists only store the mappings for the channels they
+    /// hold, and are padded out to the element size.
+    fn fixed_size() -> bool {
+        true
+    }
+
     fn fetch_elm(sections: &Sections, idx: u16) -> anyhow::Result<Self> {
         let sec = Self::get_section(sections)?;
         let data = sec.get_data_chunk(idx)?;
         let mut cursor = Cursor::new(&data);
-        Self::load(&mut cursor)
+        let elm = Self::load(&mut cursor)?;
+
+        if Self::fixed_size() && cursor.position() as usize != data.len() {
+            bail!(
+                "{} elements are {} bytes, but only {} were decoded",
+                std::any::type_name::<Self>(),
+                data.len(),
+                cursor.position()
+            );
+        }
+
+        Ok(elm)
     }
 
     fn verify(&self, _codeplug: &Codeplug) -> anyhow::Result<()> {
@@ -52,33 +73,231 @@ pub trait CPData: RawCPData {
             data,
         })
     }
+
+    /// Like `fetch_section`, but for sections which not every codeplug image
+    /// contains.
+    fn fetch_optional_section(
+        sections: &Sections,
+    ) -> anyhow::Result<Option<CodeplugSection<Self>>> {
+        if !sections.contains_key(&Self::cp_section()) {
+            return Ok(None);
+        }
+
+        Self::fetch_section(sections).map(Some)
+    }
+}
+
+/// Longest name a `String` field can hold, in UTF-16 characters.
+pub const NAME_LEN: usize = 16;
+
+/// Cut `s` down to the longest prefix which fits in a `String` field.
+pub fn truncate_name(s: &str) -> String {
+    let mut len = 0;
+
+    s.trim()
+        .chars()
+        .take_while(|c| {
+            len += c.len_utf16();
+            len <= NAME_LEN
+        })
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+fn load_utf16(reader: &mut impl Read, len: usize) -> anyhow::Result<String> {
+    let mut buf = vec![0; len];
+
+    reader.read_u16_into::<LittleEndian>(&mut buf)?;
+
+    let p = buf
+        .split_at(buf.iter().position(|v| *v == 0x0).unwrap_or(buf.len()))
+        .0;
+
+    Ok(String::from_utf16(p).map_err(|e| Error::new(ErrorKind::InvalidData, e))?)
+}
+
+fn store_utf16(s: &str, writer: &mut impl Write, len: usize) -> anyhow::Result<()> {
+    let mut buf: Vec<u16> = s.encode_utf16().collect();
+
+    if buf.len() > len {
+        bail!("String '{s}' is too long");
+    }
+
+    buf.resize(len, 0);
+
+    buf.iter()
+        .try_for_each(|x| writer.write_u16::<LittleEndian>(*x))?;
+
+    Ok(())
 }
 
 impl RawCPData for String {
     fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let mut buf: [u16; 16] = [0; 16];
+        load_utf16(reader, NAME_LEN)
+    }
+
+    fn store(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        store_utf16(self, writer, NAME_LEN)
+    }
+}
+
+/// A null padded UTF-16 string of up to `N` characters, for fields which are
+/// longer than the 16 characters of a `String`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LongString<const N: usize>(pub String);
 
-        reader.read_u16_into::<LittleEndian>(&mut buf)?;
+impl<const N: usize> LongString<N> {
+    pub fn new(s: &str) -> anyhow::Result<Self> {
+        if s.encode_utf16().count() > N {
+            bail!("'{}' is longer than {} characters", s, N);
+        }
 
-        let p = buf
-            .split_at(buf.iter().position(|v| *v == 0x0).unwrap_or(buf.len()))
-            .0;
+        Ok(LongString(s.to_string()))
+    }
+}
 
-        Ok(String::from_utf16(p).map_err(|e| Error::new(ErrorKind::InvalidData, e))?)
+impl<const N: usize> std::fmt::Display for LongString<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<const N: usize> RawCPData for LongString<N> {
+    fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
+        load_utf16(reader, N).map(LongString)
     }
 
     fn store(&self, writer: &mut impl Write) -> anyhow::Result<()> {
-        let mut s: Vec<u16> = self.encode_utf16().collect();
+        store_utf16(&self.0, writer, N)
+    }
+}
 
-        if s.len() > 16 {
-            bail!("String '{self}' is too long");
+impl RawCPData for u8 {
+    fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
+        Ok(reader.read_u8()?)
+    }
+
+    fn store(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        Ok(writer.write_u8(*self)?)
+    }
+}
+
+impl RawCPData for u16 {
+    fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
+        Ok(reader.read_u16::<LittleEndian>()?)
+    }
+
+    fn store(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        Ok(writer.write_u16::<LittleEndian>(*self)?)
+    }
+}
+
+impl RawCPData for u32 {
+    fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
+        Ok(reader.read_u32::<LittleEndian>()?)
+    }
+
+    fn store(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        Ok(writer.write_u32::<LittleEndian>(*self)?)
+    }
+}
+
+impl RawCPData for bool {
+    fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
+        Ok(reader.read_u8()? != 0)
+    }
+
+    fn store(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        Ok(writer.write_u8(*self as u8)?)
+    }
+}
+
+/// Elements stored back to back, such as bytes which are carried through
+/// unmodified.
+impl<T: RawCPData, const N: usize> RawCPData for [T; N] {
+    fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let items = (0..N)
+            .map(|_| T::load(reader))
+            .collect::<anyhow::Result<Vec<T>>>()?;
+
+        Ok(items
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("loaded {} elements", N)))
+    }
+
+    fn store(&self, writer: &mut impl Write) -> anyhow::Result<()> {
+        self.iter().try_for_each(|x| x.store(writer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawCPData;
+    use crate::codeplug::digital_channel::{rrs_revert_ch::RrsRevertCh, tx_admit::TxAdmit};
+    use hyrps_derive::RawCPData;
+
+    #[derive(Debug, PartialEq, Eq, RawCPData)]
+    #[cp(check(level != 7), magic(u8 = 0xaa))]
+    struct Sample {
+        #[cp(magic(u16 = 0x1234), local(high: u8 = self.derived >> 1))]
+        id: u16,
+        #[cp(bitfield(u8), bits(0x07))]
+        level: u8,
+        #[cp(
+            magic_bits(0x80 = 1),
+            local_bits(low: 0x40 = self.derived & 1),
+            bits(0x08)
+        )]
+        flag: bool,
+        #[cp(bits(0x30))]
+        admit: TxAdmit,
+        #[cp(repr(u8))]
+        fallback_admit: TxAdmit,
+        #[cp(repr(u16))]
+        revert: RrsRevertCh,
+        #[cp(compute(high << 1 | low))]
+        derived: u8,
+    }
+
+    const RAW: [u8; 10] = [0x34, 0x12, 0x05, 0x78, 0x56, 0xed, 0x01, 0x07, 0x00, 0xaa];
+
+    fn sample() -> Sample {
+        Sample {
+            id: 0x5678,
+            level: 5,
+            flag: true,
+            admit: TxAdmit::ColourCode,
+            fallback_admit: TxAdmit::Channel,
+            revert: RrsRevertCh::Idx(7),
+            derived: 0x0b,
         }
+    }
 
-        s.resize(16, 0);
+    #[test]
+    fn derived_round_trip() {
+        let mut raw = vec![];
+        sample().store(&mut raw).unwrap();
 
-        s.iter()
-            .try_for_each(|x| writer.write_u16::<LittleEndian>(*x))?;
+        assert_eq!(raw, RAW);
+        assert_eq!(Sample::load(&mut &RAW[..]).unwrap(), sample());
+    }
 
-        Ok(())
+    #[test]
+    fn derived_load_errors() {
+        let corrupt = |offset: usize, f: fn(u8) -> u8| {
+            let mut raw = RAW;
+            raw[offset] = f(raw[offset]);
+
+            Sample::load(&mut &raw[..]).unwrap_err().to_string()
+        };
+
+        assert!(corrupt(0, |_| 0).contains("reserved value"));
+        assert!(corrupt(5, |b| b & !0x80).contains("reserved bits"));
+        assert!(corrupt(5, |b| b | 0x30).contains("Invalid admit"));
+        assert!(corrupt(6, |_| 3).contains("Invalid fallback_admit"));
+        assert!(corrupt(5, |b| b | 0x07).contains("check failed"));
+        assert!(corrupt(9, |_| 0).contains("reserved value"));
+        assert!(Sample::load(&mut &RAW[..8]).is_err());
     }
 }
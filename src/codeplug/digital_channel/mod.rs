@@ -1,5 +1,5 @@
-use anyhow::bail;
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use anyhow::{bail, Context};
+use hyrps_derive::RawCPData;
 
 use crate::codeplug::digital_channel::rrs_revert_ch::RrsRevertCh;
 
@@ -7,12 +7,17 @@ use self::{slrl_pointer::SLRLPointer, timeslot::Timeslot};
 
 use super::{
     channel_common::{self, power_level::PowerLevel, ChannelCommon},
+    channel_pointer::{digi_chan_pointer::DigiChannelPointer, raw_pointer::RawPointer},
     contact::Contact,
-    cp_data::{CPData, RawCPData},
+    cp_data::CPData,
     disp_tabular::DisplayTabular,
+    emergency_system::EmergencySystem,
+    layout::{CPLayout, Field},
+    phone_system::PhoneSystem,
+    privacy::{self, privacy_type::PrivacyType},
+    rx_group::RxGroup,
     Codeplug, CodeplugSection,
 };
-use std::{convert::TryFrom, io::Read};
 
 #[cfg(test)]
 use proptest_derive::Arbitrary;
@@ -34,42 +39,103 @@ impl SLRLPointer {
     fn get_type(&self) -> u8 {
         match self {
             Self::None => 0,
-            Self::ScanList(_) => 0x10,
-            Self::RoamList(_) => 0x20,
+            Self::ScanList(_) => 1,
+            Self::RoamList(_) => 2,
         }
     }
+
+    fn from_raw(kind: u8, idx: u8) -> anyhow::Result<Self> {
+        let list_idx = || {
+            idx.checked_sub(1)
+                .ok_or_else(|| anyhow::anyhow!("SLRL pointer of type {} has no index", kind))
+        };
+
+        Ok(match kind {
+            0 => SLRLPointer::None,
+            1 => SLRLPointer::ScanList(list_idx()?),
+            2 => SLRLPointer::RoamList(list_idx()?),
+            _ => bail!("Unknown SLRL pointer type: {}", kind),
+        })
+    }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, RawCPData)]
 #[cfg_attr(test, derive(Arbitrary))]
+#[cfg_attr(
+    not(test),
+    cp(check(common.chan_type == channel_common::channel_type::ChannelType::Digial))
+)]
+#[cp(
+    check(repeater_bit == (common.rx_freq != common.tx_freq) as u8),
+    check(repeater_byte == (common.rx_freq != common.tx_freq) as u8)
+)]
 pub struct DigitalChannel {
     pub common: channel_common::ChannelCommon,
+    #[cp(repr(u8))]
     pub tx_admit: tx_admit::TxAdmit,
     pub tx_timeout: u8,
     pub tx_timeout_prealert: u8,
     pub tx_timeout_rekey: u8,
     pub tx_timeout_reset: u8,
     #[cfg_attr(test, proptest(strategy = "0..15u8"))]
+    #[cp(bitfield(u8), bits(0x0f))]
     pub colour_code: u8,
+    #[cp(bits(0x20))]
     pub priority_interrupt_encode: bool,
+    #[cp(bits(0x40))]
     pub priority_interrupt_decode: bool,
     pub tx_contact_idx: u16,
     pub rx_group_list_idx: u16,
     pub emergency_system_idx: u16,
+    #[cp(
+        local(slrl_idx: u8 = self.slrl_pointer.get_idx()),
+        bitfield(u8),
+        magic_bits(0x80 = 1),
+        local_bits(repeater_bit: 0x40 = self.repeater_mode()),
+        bits(0x01)
+    )]
+    pub auto_start_scan: bool,
+    #[cp(bits(0x20))]
+    pub ip_multi_site_connect: bool,
+    #[cp(bitfield(u8), bits(0x03))]
     pub timeslot: Timeslot,
-    pub slrl_pointer: SLRLPointer,
+    #[cp(bits(0x04))]
+    pub auto_start_roam: bool,
+    #[cp(local_bits(slrl_type: 0x30 = self.slrl_pointer.get_type()), bits(0x40))]
     pub vox: bool,
+    #[cp(bits(0x80))]
     pub has_option_board: bool,
-    pub loc_rev_channel_idx: u16,
+    #[cp(compute(SLRLPointer::from_raw(slrl_type, slrl_idx)?))]
+    pub slrl_pointer: SLRLPointer,
+    #[cp(local(repeater_byte: u8 = self.repeater_mode()), repr(u8))]
+    pub privacy_type: PrivacyType,
+    /// Zero based index into the keys of `privacy_type`.
+    pub privacy_key_idx: u8,
+    // Unsure about this byte. It seems to be 0xff most of the time, but
+    // I've seen values of 75 and 0:
+    //
+    // dPMR CH 32 255
+    // EL TG 9 S2 Local 0
+    // EL 235 UK Call 75
+    // EL TG 80 UK UA 255
+    pub unk2: u8,
+    /// Channel location reports are sent on. Stored as zero when unset.
+    pub loc_rev_channel: Option<DigiChannelPointer>,
+    /// One based index into the phone systems, zero if unset.
+    #[cp(magic(u16 = 1))]
     pub phone_system_idx: u16,
     pub pseudo_trunk_tx: u8,
+    #[cp(magic(u16 = 0), repr(u16))]
     pub rrs_revert_ch: RrsRevertCh,
-    pub auto_start_scan: bool,
-    pub auto_start_roam: bool,
-    pub ip_multi_site_connect: bool,
 }
 
 impl DigitalChannel {
+    /// 1 when the channel transmits on a different frequency to the one it
+    /// receives on.
+    fn repeater_mode(&self) -> u8 {
+        (self.common.rx_freq != self.common.tx_freq) as u8
+    }
+
     pub fn resolve_tx_contact<'a>(
         &self,
         contacts: &'a CodeplugSection<Contact>,
@@ -81,6 +147,20 @@ impl DigitalChannel {
         }
     }
 
+    /// The RX group list the channel listens to, if any.
+    pub fn resolve_rx_group<'a>(&self, codeplug: &'a Codeplug) -> Option<&'a RxGroup> {
+        if self.rx_group_list_idx == 0 {
+            None
+        } else {
+            codeplug
+                .rx_groups
+                .as_ref()?
+                .data
+                .data
+                .get(self.rx_group_list_idx as usize - 1)
+        }
+    }
+
     pub fn new(
         name: String,
         tx_freq: u32,
@@ -115,7 +195,10 @@ impl DigitalChannel {
             vox: false,
             slrl_pointer: SLRLPointer::None,
             has_option_board: false,
-            loc_rev_channel_idx: 0,
+            privacy_type: PrivacyType::None,
+            privacy_key_idx: 0,
+            unk2: 0,
+            loc_rev_channel: None,
             phone_system_idx: 0,
             pseudo_trunk_tx: 0,
             rrs_revert_ch: RrsRevertCh::None,
@@ -126,195 +209,105 @@ impl DigitalChannel {
     }
 }
 
-impl RawCPData for DigitalChannel {
-    fn load(reader: &mut impl Read) -> anyhow::Result<DigitalChannel> {
-        let common = channel_common::ChannelCommon::load(reader)?;
-
-        #[cfg(not(test))]
-        assert_eq!(
-            common.chan_type,
-            channel_common::channel_type::ChannelType::Digial
-        );
-
-        let tx_admit = tx_admit::TxAdmit::try_from(reader.read_u8()?)?;
-
-        let tx_timeout = reader.read_u8()?;
-        let tx_timeout_prealert = reader.read_u8()?;
-        let tx_timeout_rekey = reader.read_u8()?;
-        let tx_timeout_reset = reader.read_u8()?;
-
-        let b2 = reader.read_u8()?;
-
-        let colour_code = b2 & 0x0f;
-        let priority_interrupt_encode = (b2 & 0x20) != 0;
-        let priority_interrupt_decode = (b2 & 0x40) != 0;
-
-        let tx_contact_idx = reader.read_u16::<LittleEndian>()?;
-        let rx_group_list_idx = reader.read_u16::<LittleEndian>()?;
-        let emergency_system_idx = reader.read_u16::<LittleEndian>()?;
-        let slrl_idx = reader.read_u8()?;
-
-        let b2 = reader.read_u8()?;
-
-        assert_eq!(b2 & 0x80, 0x80);
-        assert_eq!(
-            (b2 & 0x40) >> 6,
-            if common.rx_freq == common.tx_freq {
-                0
-            } else {
-                1
-            }
-        );
-
-        let auto_start_scan = b2 & 0x1 != 0;
-        let ip_multi_site_connect = b2 & 0x20 != 0;
-
-        let b3 = reader.read_u8()?;
-
-        let timeslot = Timeslot::from(b3 & 0x3);
-        let auto_start_roam = (b3 & 0x4) != 0;
-        let slrl_type = b3 & 0x30;
-        let vox = (b3 & 0x40) != 0;
-        let has_option_board = (b3 & 0x80) != 0;
-
-        let slrl_pointer = match slrl_type {
-            0 => SLRLPointer::None,
-            0x10 => SLRLPointer::ScanList(slrl_idx - 1),
-            0x20 => SLRLPointer::RoamList(slrl_idx - 1),
-            _ => bail!("Unknown SLRL pointer type: {}", slrl_type),
-        };
-
-        assert_eq!(
-            reader.read_u8()?,
-            if common.rx_freq == common.tx_freq {
-                0
-            } else {
-                1
-            }
-        );
-        assert_eq!(reader.read_u16::<LittleEndian>()?, 0);
-
-        // Unsure about this byte. It seems to be 0xff most of the time, but
-        // I've seen values of 75 and 0:
-        //
-        // dPMR CH 32 255
-        // EL TG 9 S2 Local 0
-        // EL 235 UK Call 75
-        // EL TG 80 UK UA 255
-        reader.read_u8()?;
-
-        let loc_rev_channel_idx = reader.read_u16::<LittleEndian>()?;
-
-        assert_eq!(reader.read_u16::<LittleEndian>()?, 1);
-
-        let phone_system_idx = reader.read_u16::<LittleEndian>()?;
-        let pseudo_trunk_tx = reader.read_u8()?;
-
-        assert_eq!(reader.read_u16::<LittleEndian>()?, 0);
-
-        let rrs_revert_ch = RrsRevertCh::from(reader.read_u16::<LittleEndian>()?);
-
-        Ok(DigitalChannel {
-            common,
-            tx_admit,
-            tx_timeout,
-            tx_timeout_prealert,
-            tx_timeout_rekey,
-            tx_timeout_reset,
-            colour_code,
-            priority_interrupt_encode,
-            priority_interrupt_decode,
-            tx_contact_idx,
-            rx_group_list_idx,
-            emergency_system_idx,
-            timeslot,
-            slrl_pointer,
-            vox,
-            has_option_board,
-            loc_rev_channel_idx,
-            phone_system_idx,
-            pseudo_trunk_tx,
-            rrs_revert_ch,
-            auto_start_scan,
-            auto_start_roam,
-            ip_multi_site_connect,
-        })
+impl CPData for DigitalChannel {
+    fn cp_section() -> u16 {
+        0x26
     }
 
-    fn store(&self, writer: &mut impl std::io::Write) -> anyhow::Result<()> {
-        let direct_or_repeater_mode: u8 = if self.common.rx_freq == self.common.tx_freq {
-            0
-        } else {
-            1
-        };
-
-        self.common.store(writer)?;
-
-        writer.write_u8(self.tx_admit as u8)?;
-        writer.write_u8(self.tx_timeout)?;
-        writer.write_u8(self.tx_timeout_prealert)?;
-        writer.write_u8(self.tx_timeout_rekey)?;
-        writer.write_u8(self.tx_timeout_reset)?;
-
-        writer.write_u8(
-            self.colour_code
-                | (self.priority_interrupt_encode as u8) << 5
-                | (self.priority_interrupt_decode as u8) << 6,
-        )?;
-
-        writer.write_u16::<LittleEndian>(self.tx_contact_idx)?;
-        writer.write_u16::<LittleEndian>(self.rx_group_list_idx)?;
-        writer.write_u16::<LittleEndian>(self.emergency_system_idx)?;
-        writer.write_u8(self.slrl_pointer.get_idx())?;
-
-        writer.write_u8(
-            0x80 | (direct_or_repeater_mode << 6)
-                | (self.ip_multi_site_connect as u8) << 5
-                | self.auto_start_scan as u8,
-        )?;
-
-        writer.write_u8(
-            u8::from(self.timeslot)
-                | self.slrl_pointer.get_type()
-                | (self.auto_start_roam as u8) << 2
-                | (self.vox as u8) << 6
-                | (self.has_option_board as u8) << 7,
-        )?;
-
-        writer.write_u8(direct_or_repeater_mode)?;
-
-        writer.write_u16::<LittleEndian>(0)?;
-
-        writer.write_u8(0)?;
-
-        writer.write_u16::<LittleEndian>(self.loc_rev_channel_idx as u16)?;
-
-        writer.write_u16::<LittleEndian>(1)?;
-
-        writer.write_u16::<LittleEndian>(self.phone_system_idx)?;
-        writer.write_u8(self.pseudo_trunk_tx)?;
-
-        writer.write_u16::<LittleEndian>(0)?;
+    fn verify(&self, codeplug: &Codeplug) -> anyhow::Result<()> {
+        if codeplug.rx_groups.is_some()
+            && self.rx_group_list_idx != 0
+            && self.resolve_rx_group(codeplug).is_none()
+        {
+            bail!(
+                "Channel '{}' references missing RX group list {}",
+                self.common.name,
+                self.rx_group_list_idx
+            );
+        }
 
-        writer.write_u16::<LittleEndian>(u16::from(self.rrs_revert_ch))?;
+        if let Some(p) = &self.loc_rev_channel {
+            p.verify(codeplug).with_context(|| {
+                format!("Channel '{}' has a bad location revert channel", self.common.name)
+            })?;
+        }
 
-        Ok(())
+        EmergencySystem::verify_ref(codeplug, &self.common.name, self.emergency_system_idx)?;
+        PhoneSystem::verify_ref(codeplug, &self.common.name, self.phone_system_idx)?;
+        privacy::verify_ref(
+            codeplug,
+            &self.common.name,
+            self.privacy_type,
+            self.privacy_key_idx,
+        )
     }
 }
 
-impl CPData for DigitalChannel {
-    fn cp_section() -> u16 {
-        0x26
-    }
+impl CPLayout for DigitalChannel {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "common.name"),
+        Field::new(32, 1, "common.chan_type"),
+        Field::bits(33, 0x01, "common.rx_only"),
+        Field::bits(33, 0x04, "common.power_level"),
+        Field::unknown_bits(33, 0xfa, "common.unk_flags"),
+        Field::unknown(34, 2, "common.reserved"),
+        Field::new(36, 4, "common.rx_freq"),
+        Field::new(40, 4, "common.tx_freq"),
+        Field::new(44, 1, "tx_admit"),
+        Field::new(45, 1, "tx_timeout"),
+        Field::new(46, 1, "tx_timeout_prealert"),
+        Field::new(47, 1, "tx_timeout_rekey"),
+        Field::new(48, 1, "tx_timeout_reset"),
+        Field::bits(49, 0x0f, "colour_code"),
+        Field::bits(49, 0x20, "priority_interrupt_encode"),
+        Field::bits(49, 0x40, "priority_interrupt_decode"),
+        Field::unknown_bits(49, 0x90, "unk_flags"),
+        Field::new(50, 2, "tx_contact_idx"),
+        Field::new(52, 2, "rx_group_list_idx"),
+        Field::new(54, 2, "emergency_system_idx"),
+        Field::new(56, 1, "slrl_pointer"),
+        Field::bits(57, 0x01, "auto_start_scan"),
+        Field::unknown_bits(57, 0x1e, "unk_bits"),
+        Field::bits(57, 0x20, "ip_multi_site_connect"),
+        Field::bits(57, 0x40, "repeater_mode"),
+        Field::unknown_bits(57, 0x80, "unk_flag"),
+        Field::bits(58, 0x03, "timeslot"),
+        Field::bits(58, 0x04, "auto_start_roam"),
+        Field::unknown_bits(58, 0x08, "unk_bit"),
+        Field::bits(58, 0x30, "slrl_pointer"),
+        Field::bits(58, 0x40, "vox"),
+        Field::bits(58, 0x80, "has_option_board"),
+        Field::new(59, 1, "repeater_mode"),
+        Field::new(60, 1, "privacy_type"),
+        Field::new(61, 1, "privacy_key_idx"),
+        Field::unknown(62, 1, "unk2"),
+        Field::new(63, 2, "loc_rev_channel"),
+        Field::unknown(65, 2, "unk3"),
+        Field::new(67, 2, "phone_system_idx"),
+        Field::new(69, 1, "pseudo_trunk_tx"),
+        Field::unknown(70, 2, "unk4"),
+        Field::new(72, 2, "rrs_revert_ch"),
+    ];
 }
 
 impl DisplayTabular for DigitalChannel {
     fn get_heading() -> Vec<String> {
         let mut headings = ChannelCommon::get_heading();
-        let mut dc_headings = ["TX Contact", "Colour Code", "Scan List", "Timeslot", "Vox"]
-            .iter()
-            .map(|s| s.to_string())
-            .collect();
+        let mut dc_headings = [
+            "TX Contact",
+            "RX Group",
+            "Colour Code",
+            "Scan List",
+            "Emergency",
+            "Phone System",
+            "Loc Revert",
+            "Privacy",
+            "Timeslot",
+            "Vox",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
 
         headings.append(&mut dc_headings);
 
@@ -329,6 +322,10 @@ impl DisplayTabular for DigitalChannel {
                 Some(c) => c.name.clone(),
                 _ => "<None>".to_string(),
             },
+            match self.resolve_rx_group(codeplug) {
+                Some(g) => g.name.clone(),
+                _ => "<None>".to_string(),
+            },
             self.colour_code.to_string(),
             match self.slrl_pointer {
                 SLRLPointer::None => "<None>".to_string(),
@@ -339,6 +336,13 @@ impl DisplayTabular for DigitalChannel {
                     format!("RL {}", codeplug.roam_list.data.data[i as usize].name)
                 }
             },
+            EmergencySystem::resolve_name(codeplug, self.emergency_system_idx),
+            PhoneSystem::resolve_name(codeplug, self.phone_system_idx),
+            match &self.loc_rev_channel {
+                Some(p) => p.get_chan_name(codeplug),
+                None => "<None>".to_string(),
+            },
+            privacy::describe(codeplug, self.privacy_type, self.privacy_key_idx),
             format!("{:?}", self.timeslot),
             format!("{:?}", self.vox),
         ]);
@@ -349,9 +353,38 @@ impl DisplayTabular for DigitalChannel {
 
 #[cfg(test)]
 mod tests {
-    use crate::tests::check_serde;
+    use super::{timeslot::Timeslot, DigitalChannel};
+    use crate::{
+        codeplug::{channel_common::power_level::PowerLevel, cp_data::RawCPData},
+        tests::check_serde,
+    };
     use proptest::prelude::*;
 
+    #[test]
+    fn repeater_mode_mismatch() {
+        let dc = DigitalChannel::new(
+            "LE 9 Local".to_string(),
+            430_662_500,
+            439_662_500,
+            false,
+            PowerLevel::High,
+            2,
+            0,
+            Timeslot::Slot2,
+        );
+        let mut raw = vec![];
+        dc.store(&mut raw).unwrap();
+
+        assert_eq!(DigitalChannel::load(&mut &raw[..]).unwrap(), dc);
+
+        for (offset, bit) in [(57, 0x40), (59, 0x01)] {
+            let mut bad = raw.clone();
+            bad[offset] ^= bit;
+
+            assert!(DigitalChannel::load(&mut &bad[..]).is_err());
+        }
+    }
+
     proptest! {
         #[test]
         fn digital_channel_serde(ac in any::<super::DigitalChannel>()) {
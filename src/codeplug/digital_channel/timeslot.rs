@@ -1,3 +1,6 @@
+use anyhow::bail;
+use std::{convert::TryFrom, str::FromStr};
+
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 
@@ -9,13 +12,30 @@ pub enum Timeslot {
     PseudoTrunk,
 }
 
-impl From<u8> for Timeslot {
-    fn from(v: u8) -> Self {
+impl TryFrom<u8> for Timeslot {
+    type Error = anyhow::Error;
+
+    fn try_from(v: u8) -> Result<Self, Self::Error> {
         match v {
-            0 => Timeslot::Slot1,
-            1 => Timeslot::Slot2,
-            3 => Timeslot::PseudoTrunk,
-            _ => panic!("Unexpected timeslot value: {}", v),
+            0 => Ok(Timeslot::Slot1),
+            1 => Ok(Timeslot::Slot2),
+            3 => Ok(Timeslot::PseudoTrunk),
+            _ => bail!("Unexpected timeslot value: {}", v),
+        }
+    }
+}
+
+impl FromStr for Timeslot {
+    type Err = anyhow::Error;
+
+    /// Parse `1` or `2`, optionally written `TS1` or `S1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_ascii_uppercase();
+
+        match upper.trim_start_matches("TS").trim_start_matches('S') {
+            "1" => Ok(Timeslot::Slot1),
+            "2" => Ok(Timeslot::Slot2),
+            _ => bail!("Invalid timeslot: {}", s),
         }
     }
 }
@@ -27,3 +27,9 @@ impl TryFrom<u8> for TxAdmit {
         }
     }
 }
+
+impl From<TxAdmit> for u8 {
+    fn from(v: TxAdmit) -> Self {
+        v as u8
+    }
+}
@@ -1,4 +1,5 @@
 use comfy_table::{presets::UTF8_BORDERS_ONLY, Table};
+use std::io::Write;
 
 use super::Codeplug;
 
@@ -24,4 +25,21 @@ pub trait DisplayTabular {
 
         println!("{table}");
     }
+
+    /// Write the same rows as `print_table` as CSV, with the headings as the
+    /// header row.
+    fn write_csv(objs: &[Self], codeplug: &Codeplug, writer: impl Write) -> anyhow::Result<()>
+    where
+        Self: Sized,
+    {
+        let mut csv = csv::Writer::from_writer(writer);
+
+        csv.write_record(Self::get_heading())?;
+
+        for obj in objs.iter() {
+            csv.write_record(obj.get_row(codeplug))?;
+        }
+
+        Ok(csv.flush()?)
+    }
 }
@@ -0,0 +1,56 @@
+//! DTMF digit strings, which are stored as null padded ASCII.
+
+use anyhow::bail;
+
+const DIGITS: &[u8] = b"0123456789*#ABCD";
+
+pub fn to_string(code: &[u8]) -> String {
+    code.iter()
+        .take_while(|c| **c != 0)
+        .map(|c| *c as char)
+        .collect()
+}
+
+/// Encode `s` into a field of `N` bytes.
+pub fn from_str<const N: usize>(s: &str) -> anyhow::Result<[u8; N]> {
+    let mut code = [0; N];
+
+    if s.len() > N {
+        bail!("DTMF code '{}' is longer than {} digits", s, N);
+    }
+
+    code[..s.len()].copy_from_slice(s.to_ascii_uppercase().as_bytes());
+    verify(&code)?;
+
+    Ok(code)
+}
+
+pub fn verify(code: &[u8]) -> anyhow::Result<()> {
+    let digits = code.iter().take_while(|c| **c != 0);
+
+    for c in digits.clone() {
+        if !DIGITS.contains(c) {
+            bail!("Invalid DTMF digit 0x{:X}", c);
+        }
+    }
+
+    if code[digits.count()..].iter().any(|c| *c != 0) {
+        bail!("Malformed DTMF code");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn dtmf_codes() {
+        let code = super::from_str::<6>("12a#").unwrap();
+
+        assert_eq!(&code, b"12A#\0\0");
+        assert_eq!(super::to_string(&code), "12A#");
+        assert!(super::from_str::<6>("12E").is_err());
+        assert!(super::from_str::<2>("123").is_err());
+        assert!(super::verify(b"1\x002\0").is_err());
+    }
+}
@@ -0,0 +1,31 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive, Clone, Copy)]
+#[repr(u8)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub enum AlarmType {
+    Disable = 0,
+    Regular = 1,
+    Silent = 2,
+    SilentWithVoice = 3,
+}
+
+impl FromStr for AlarmType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "disable" => AlarmType::Disable,
+            "regular" => AlarmType::Regular,
+            "silent" => AlarmType::Silent,
+            "silent-voice" => AlarmType::SilentWithVoice,
+            _ => anyhow::bail!(
+                "Unknown alarm type '{}', expected disable, regular, silent or silent-voice",
+                s
+            ),
+        })
+    }
+}
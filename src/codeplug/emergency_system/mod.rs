@@ -0,0 +1,170 @@
+use self::{alarm_mode::AlarmMode, alarm_type::AlarmType};
+use super::{
+    channel_pointer::{pointer::ChannelPointer, raw_pointer::RawPointer},
+    contact::Contact,
+    cp_data::CPData,
+    disp_tabular::DisplayTabular,
+    layout::{CPLayout, Field},
+    Codeplug,
+};
+use anyhow::bail;
+use hyrps_derive::RawCPData;
+
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+pub mod alarm_mode;
+pub mod alarm_type;
+
+#[derive(Debug, PartialEq, Eq, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct EmergencySystem {
+    #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
+    pub name: String,
+    #[cp(repr(u8))]
+    pub alarm_type: AlarmType,
+    #[cp(repr(u8))]
+    pub alarm_mode: AlarmMode,
+    /// Channel switched to when the alarm is raised. `Selected` stays on
+    /// the current channel.
+    pub revert_channel: ChannelPointer,
+    /// One based index into the contacts, zero if unset.
+    pub tx_contact_idx: u16,
+    /// Number of times the alarm is sent.
+    pub alarm_cycles: u8,
+}
+
+impl EmergencySystem {
+    pub fn new(name: String, alarm_type: AlarmType, tx_contact_idx: u16) -> Self {
+        EmergencySystem {
+            name,
+            alarm_type,
+            alarm_mode: AlarmMode::Alarm,
+            revert_channel: ChannelPointer::Selected,
+            tx_contact_idx: tx_contact_idx + 1,
+            alarm_cycles: 5,
+        }
+    }
+
+    /// Look up the emergency system referenced by a channel's one based
+    /// index.
+    pub fn resolve(codeplug: &Codeplug, idx: u16) -> Option<&EmergencySystem> {
+        if idx == 0 {
+            None
+        } else {
+            codeplug
+                .emergency_systems
+                .as_ref()?
+                .data
+                .get(idx as usize - 1)
+        }
+    }
+
+    /// Name of the emergency system referenced by `idx`, for tabular output.
+    pub fn resolve_name(codeplug: &Codeplug, idx: u16) -> String {
+        match (idx, Self::resolve(codeplug, idx)) {
+            (0, _) => "<None>".to_string(),
+            (_, Some(e)) => e.name.clone(),
+            (_, None) => "<Invalid>".to_string(),
+        }
+    }
+
+    /// Check a channel's reference to an emergency system. References can't
+    /// be checked when the section isn't present.
+    pub fn verify_ref(codeplug: &Codeplug, chan_name: &str, idx: u16) -> anyhow::Result<()> {
+        if idx != 0
+            && codeplug.emergency_systems.is_some()
+            && Self::resolve(codeplug, idx).is_none()
+        {
+            bail!(
+                "Channel '{}' references missing emergency system {}",
+                chan_name,
+                idx
+            );
+        }
+
+        Ok(())
+    }
+
+    fn resolve_tx_contact<'a>(&self, codeplug: &'a Codeplug) -> Option<&'a Contact> {
+        if self.tx_contact_idx == 0 {
+            None
+        } else {
+            codeplug.contacts.data.get(self.tx_contact_idx as usize - 1)
+        }
+    }
+}
+
+impl CPData for EmergencySystem {
+    fn cp_section() -> u16 {
+        0x2d
+    }
+
+    fn verify(&self, codeplug: &Codeplug) -> anyhow::Result<()> {
+        self.revert_channel.verify(codeplug)?;
+
+        if self.tx_contact_idx != 0 && self.resolve_tx_contact(codeplug).is_none() {
+            bail!(
+                "Emergency system '{}' references missing contact {}",
+                self.name,
+                self.tx_contact_idx
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl CPLayout for EmergencySystem {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "name"),
+        Field::new(32, 1, "alarm_type"),
+        Field::new(33, 1, "alarm_mode"),
+        Field::new(34, 4, "revert_channel"),
+        Field::new(38, 2, "tx_contact_idx"),
+        Field::new(40, 1, "alarm_cycles"),
+    ];
+}
+
+impl DisplayTabular for EmergencySystem {
+    fn get_heading() -> Vec<String> {
+        [
+            "Name",
+            "Alarm Type",
+            "Mode",
+            "Revert Channel",
+            "TX Contact",
+            "Alarm Cycles",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    fn get_row(&self, codeplug: &Codeplug) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            format!("{:?}", self.alarm_type),
+            format!("{:?}", self.alarm_mode),
+            self.revert_channel.get_chan_name(codeplug),
+            match self.resolve_tx_contact(codeplug) {
+                Some(c) => c.name.clone(),
+                _ => "<None>".to_string(),
+            },
+            self.alarm_cycles.to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_serde;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn emergency_system_serde(es in any::<super::EmergencySystem>()) {
+            check_serde(&es)?;
+        }
+    }
+}
@@ -0,0 +1,275 @@
+/// How the bytes of a field are interpreted when annotating a dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// Little-endian integer, masked and shifted by the field's bitmask.
+    Int,
+    /// Null terminated UTF-16 string.
+    Utf16,
+    /// Opaque bytes.
+    Bytes,
+    /// Key material, whose bytes are never shown.
+    Secret,
+}
+
+/// A field within the serialised form of a codeplug element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    pub offset: usize,
+    pub width: usize,
+    /// Bits of each byte which belong to the field. Fields packed into a
+    /// bitfield share a byte but have disjoint masks.
+    pub mask: u8,
+    /// Fields sharing a name hold the parts of a single value.
+    pub name: &'static str,
+    pub kind: FieldKind,
+    /// False for bytes whose meaning hasn't been worked out yet.
+    pub known: bool,
+}
+
+impl Field {
+    pub const fn new(offset: usize, width: usize, name: &'static str) -> Self {
+        Field {
+            offset,
+            width,
+            mask: 0xff,
+            name,
+            kind: FieldKind::Int,
+            known: true,
+        }
+    }
+
+    pub const fn bits(offset: usize, mask: u8, name: &'static str) -> Self {
+        Field {
+            mask,
+            ..Field::new(offset, 1, name)
+        }
+    }
+
+    pub const fn utf16(offset: usize, width: usize, name: &'static str) -> Self {
+        Field {
+            kind: FieldKind::Utf16,
+            ..Field::new(offset, width, name)
+        }
+    }
+
+    pub const fn bytes(offset: usize, width: usize, name: &'static str) -> Self {
+        Field {
+            kind: FieldKind::Bytes,
+            ..Field::new(offset, width, name)
+        }
+    }
+
+    pub const fn secret(offset: usize, width: usize, name: &'static str) -> Self {
+        Field {
+            kind: FieldKind::Secret,
+            ..Field::new(offset, width, name)
+        }
+    }
+
+    pub const fn unknown(offset: usize, width: usize, name: &'static str) -> Self {
+        Field {
+            known: false,
+            ..Field::bytes(offset, width, name)
+        }
+    }
+
+    pub const fn unknown_bits(offset: usize, mask: u8, name: &'static str) -> Self {
+        Field {
+            known: false,
+            ..Field::bits(offset, mask, name)
+        }
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        (self.offset..self.offset + self.width).contains(&offset)
+    }
+
+    /// Decode the field from an element's raw bytes for display.
+    pub fn value(&self, data: &[u8]) -> Option<String> {
+        let raw = data.get(self.offset..self.offset + self.width)?;
+
+        match self.kind {
+            FieldKind::Int if self.width <= 4 => {
+                let v = raw
+                    .iter()
+                    .rev()
+                    .fold(0u32, |acc, b| (acc << 8) | (b & self.mask) as u32)
+                    >> self.mask.trailing_zeros();
+
+                Some(format!("{v} (0x{v:X})"))
+            }
+            FieldKind::Utf16 => {
+                let s: Vec<u16> = raw
+                    .chunks_exact(2)
+                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                    .take_while(|c| *c != 0)
+                    .collect();
+
+                Some(format!("'{}'", String::from_utf16_lossy(&s)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Describes the byte layout of a type implementing `RawCPData`. Several
+/// fields may share a byte when they are packed into a bitfield.
+pub trait CPLayout {
+    const LAYOUT: &'static [Field];
+}
+
+/// Layout of a `ChannelPointerSection`, as used by the zone, scan and roam
+/// channel lists, holding `data_sz` bytes of `ptr_sz` sized pointers.
+pub const fn channel_list_layout(data_sz: usize, ptr_sz: usize) -> [Field; 7] {
+    [
+        Field::unknown(0x0, 2, "header.unk1"),
+        Field::new(0x2, 2, "header.capacity"),
+        Field::new(0x4, 2, "header.no_channels"),
+        Field::new(0x6, 4, "header.data_sz"),
+        Field::new(0xa, 4, "header.mappings_offset"),
+        Field::bytes(0xe, data_sz, "channels"),
+        Field::bytes(0xe + data_sz, data_sz / ptr_sz * 6, "mappings"),
+    ]
+}
+
+/// Whether the byte at `offset` in an element described by `layout` is key
+/// material.
+pub fn is_secret(layout: &[Field], offset: usize) -> bool {
+    layout
+        .iter()
+        .any(|f| f.kind == FieldKind::Secret && f.contains(offset))
+}
+
+/// Field names at `offset` in an element described by `layout` whose bitmask
+/// overlaps `changed`, joined with '/' when several bitfields changed.
+pub fn field_names(layout: &[Field], offset: usize, changed: u8) -> Option<String> {
+    let names: Vec<_> = layout
+        .iter()
+        .filter(|f| f.contains(offset) && f.mask & changed != 0)
+        .map(|f| f.name)
+        .collect();
+
+    if names.is_empty() {
+        None
+    } else {
+        Some(names.join("/"))
+    }
+}
+
+/// A line of an annotated dump of an element.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Annotation {
+    pub offset: usize,
+    pub width: usize,
+    /// Names and decoded values of the fields starting at `offset`. Empty if
+    /// the bytes aren't covered by the layout.
+    pub fields: Vec<(&'static str, Option<String>)>,
+    /// Bits of the bytes at `offset` which don't belong to a known field.
+    pub unknown_mask: u8,
+    /// The bytes are key material and mustn't be shown.
+    pub secret: bool,
+}
+
+/// Walk an element's bytes, pairing them with the fields described by
+/// `layout`. Bytes not covered by any field are reported as unknown.
+pub fn annotate(layout: &[Field], data: &[u8]) -> Vec<Annotation> {
+    let mut ret = vec![];
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let fields: Vec<_> = layout.iter().filter(|f| f.offset == pos).collect();
+
+        if fields.is_empty() {
+            let next = layout
+                .iter()
+                .map(|f| f.offset)
+                .filter(|o| *o > pos)
+                .min()
+                .unwrap_or(data.len())
+                .min(data.len());
+
+            ret.push(Annotation {
+                offset: pos,
+                width: next - pos,
+                fields: vec![],
+                unknown_mask: 0xff,
+                secret: false,
+            });
+            pos = next;
+            continue;
+        }
+
+        let width = fields.iter().map(|f| f.width).max().unwrap();
+        let known_mask = fields
+            .iter()
+            .filter(|f| f.known)
+            .fold(0u8, |acc, f| acc | f.mask);
+
+        ret.push(Annotation {
+            offset: pos,
+            width: width.min(data.len() - pos),
+            fields: fields.iter().map(|f| (f.name, f.value(data))).collect(),
+            unknown_mask: !known_mask,
+            secret: fields.iter().any(|f| f.kind == FieldKind::Secret),
+        });
+        pos += width;
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{annotate, field_names, is_secret, Field};
+
+    const LAYOUT: &[Field] = &[
+        Field::utf16(0, 4, "name"),
+        Field::bits(4, 0x0f, "colour_code"),
+        Field::bits(4, 0x20, "flag"),
+        Field::new(5, 2, "idx"),
+    ];
+
+    #[test]
+    fn bitfield_names() {
+        assert_eq!(field_names(LAYOUT, 4, 0x20).unwrap(), "flag");
+        assert_eq!(field_names(LAYOUT, 4, 0x21).unwrap(), "colour_code/flag");
+        assert_eq!(field_names(LAYOUT, 4, 0x80), None);
+        assert_eq!(field_names(LAYOUT, 6, 0x01).unwrap(), "idx");
+    }
+
+    #[test]
+    fn field_values() {
+        let data = [0x41, 0x00, 0x00, 0x00, 0x2a, 0x34, 0x12];
+
+        assert_eq!(LAYOUT[0].value(&data).unwrap(), "'A'");
+        assert_eq!(LAYOUT[1].value(&data).unwrap(), "10 (0xA)");
+        assert_eq!(LAYOUT[2].value(&data).unwrap(), "1 (0x1)");
+        assert_eq!(LAYOUT[3].value(&data).unwrap(), "4660 (0x1234)");
+    }
+
+    #[test]
+    fn annotate_gaps() {
+        let data = [0x41, 0x00, 0x00, 0x00, 0xaa, 0x34, 0x12, 0xff, 0xff];
+        let a = annotate(LAYOUT, &data);
+
+        assert_eq!(a.len(), 4);
+        assert_eq!(a[1].fields.len(), 2);
+        assert_eq!(a[1].unknown_mask, 0xd0);
+        assert_eq!(a[3].offset, 7);
+        assert_eq!(a[3].width, 2);
+        assert!(a[3].fields.is_empty());
+    }
+
+    #[test]
+    fn secret_fields() {
+        const KEYED: &[Field] = &[Field::new(0, 1, "key_id"), Field::secret(1, 4, "key")];
+        let data = [0x01, 0xde, 0xad, 0xbe, 0xef];
+        let a = annotate(KEYED, &data);
+
+        assert!(!a[0].secret);
+        assert!(a[1].secret);
+        assert_eq!(a[1].fields, [("key", None)]);
+        assert!(!is_secret(KEYED, 0));
+        assert!(is_secret(KEYED, 4));
+    }
+}
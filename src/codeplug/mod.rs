@@ -1,37 +1,73 @@
 use crate::codeplug::disp_tabular::DisplayTabular;
 use std::{
+    collections::HashSet,
     fmt::Display,
     io::{Read, Seek, SeekFrom, Write},
 };
 
 use self::{
-    analog_channel::AnalogChannel,
-    contact::Contact,
+    analog_channel::{tone::Tone, AnalogChannel},
+    buttons::{Buttons, KeyAssignment},
+    channel_pointer::pointer::ChannelPointer,
+    contact::{
+        call_type::CallType,
+        pointer::ContactPointer,
+        user_contact::UserContact,
+        talkgroups::{Talkgroup, TalkgroupFilter},
+        users_csv::{User, UserFilter},
+        Contact, ImportReport,
+    },
     cp_data::CPData,
     digital_channel::DigitalChannel,
+    emergency_system::EmergencySystem,
+    phone_system::PhoneSystem,
+    privacy::{keys_file::KeysFile, BasicKey, EnhancedKey},
+    quick_text::QuickText,
+    radio_model::{RadioModel, GENERIC},
     roam::{list::RoamList, Roam},
+    rx_group::{list::RxGroupList, RxGroup},
     scan::Scan,
     scan_list::ScanList,
-    section::Section,
+    section::{Section, Sections},
+    settings::Settings,
+    signalling::{dtmf_system::DtmfSystem, two_tone::TwoToneSystem, Signalling},
     zone::Zone,
     zone_list::ZoneList,
 };
 
 use anyhow::{bail, Context, Result};
+use itertools::Itertools;
 
 pub mod analog_channel;
+pub mod buttons;
 pub mod channel_common;
 pub mod channel_pointer;
 pub mod contact;
 pub mod cp_data;
 pub mod digital_channel;
 pub mod disp_tabular;
+pub mod dtmf;
+pub mod emergency_system;
+pub mod layout;
+pub mod phone_system;
+pub mod position;
+pub mod privacy;
+pub mod quick_text;
+pub mod radio_model;
+pub mod re_diff;
+mod reindex;
+pub mod repeaters;
 pub mod roam;
+pub mod rx_group;
 pub mod scan;
 pub mod scan_list;
 pub mod section;
+pub mod section_registry;
+pub mod settings;
+pub mod signalling;
 pub mod zone;
 pub mod zone_list;
+pub mod zone_scan;
 
 pub struct CodeplugSectionWithChanList<T: CPData, M: CPData> {
     pub data: CodeplugSection<T>,
@@ -39,6 +75,22 @@ pub struct CodeplugSectionWithChanList<T: CPData, M: CPData> {
 }
 
 impl<T: CPData, M: CPData> CodeplugSectionWithChanList<T, M> {
+    /// Load a pair of sections which may be missing from the image. Either
+    /// both or neither must be present.
+    fn fetch_optional(sections: &Sections) -> Result<Option<Self>> {
+        match (
+            T::fetch_optional_section(sections)?,
+            M::fetch_optional_section(sections)?,
+        ) {
+            (Some(data), Some(channels)) => Ok(Some(Self { data, channels })),
+            (None, None) => Ok(None),
+            _ => bail!(
+                "Only one of the sections for {} is present",
+                std::any::type_name::<T>()
+            ),
+        }
+    }
+
     fn clear(&mut self) {
         self.data.clear();
         self.channels.clear();
@@ -85,11 +137,46 @@ impl<T: CPData> CodeplugSection<T> {
         n
     }
 
+    /// How many more elements the section can hold.
+    pub fn free_space(&self) -> usize {
+        (self.sec.header.capacity as usize).saturating_sub(self.data.len() + 1)
+    }
+
+    /// Append many elements at once, failing without adding any if they
+    /// don't all fit.
+    pub fn extend(&mut self, items: Vec<T>) -> Result<()> {
+        if items.len() > self.free_space() {
+            bail!(
+                "{} elements don't fit in the {} section, which has space for {}",
+                items.len(),
+                std::any::type_name::<T>(),
+                self.free_space()
+            );
+        }
+
+        self.data.extend(items);
+        self.sec.header.elements_in_use = self.data.len() as u16;
+
+        Ok(())
+    }
+
+    /// Remove the element at `idx`, shifting later elements down by one.
+    pub fn remove(&mut self, idx: usize) -> T {
+        self.sec.header.elements_in_use -= 1;
+        self.data.remove(idx)
+    }
+
     fn clear(&mut self) {
         self.sec.header.elements_in_use = 0;
         self.data.clear();
     }
 
+    /// Replace all elements, failing if they don't fit in the section.
+    fn replace(&mut self, data: Vec<T>) -> Result<()> {
+        self.clear();
+        self.extend(data)
+    }
+
     fn verify(&self, cp: &Codeplug) -> Result<()> {
         if self.sec.header.elements_in_use as usize != self.data.len() {
             bail!("Section header does not match number of data elements")
@@ -99,12 +186,23 @@ impl<T: CPData> CodeplugSection<T> {
     }
 
     fn write(&self, writer: &mut (impl Write + Seek)) -> Result<()> {
-        let mut buf = vec![];
+        let mut elements = vec![];
 
         for obj in self.data.iter() {
             let mut obj_buf = vec![];
             obj.store(&mut obj_buf).context("Could not store object")?;
 
+            if obj_buf.len() > self.sec.get_element_sz()
+                || (T::fixed_size() && obj_buf.len() != self.sec.get_element_sz())
+            {
+                bail!(
+                    "{} stored as {} bytes, but the section's elements are {} bytes",
+                    std::any::type_name::<T>(),
+                    obj_buf.len(),
+                    self.sec.get_element_sz()
+                );
+            }
+
             if obj_buf.len() != self.sec.get_element_sz() {
                 println!(
                     "WARNING: Padding {} object to correct size",
@@ -113,51 +211,100 @@ impl<T: CPData> CodeplugSection<T> {
                 obj_buf.resize(self.sec.get_element_sz(), 0);
             }
 
-            buf.append(&mut obj_buf);
+            elements.push(obj_buf);
         }
 
-        assert!(buf.len() < self.sec.header.byte_size as usize);
-
-        buf.resize(self.sec.header.byte_size as usize, 0_u8);
-
-        writer
-            .seek(std::io::SeekFrom::Start(self.sec.addr))
-            .context("Could not seek to position")?;
-
-        self.sec
-            .header
-            .write(writer)
-            .context("Error writing header")?;
-
-        writer
-            .write_all(&buf)
-            .context("Failed to write section data")?;
+        let mut sec = self.sec.clone();
 
-        for i in 0..(self.sec.header.capacity) {
-            self.sec
-                .get_mapping(i)
-                .write(writer)
-                .context("Failed to write section mapping")?;
-        }
+        sec.set_elements(&elements)
+            .context("Could not set section elements")?;
 
-        Ok(())
+        sec.write(writer).context("Failed to write section")
     }
 }
 
-#[derive(PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum DeviceType {
     Portable,
     Mobile,
 }
 
 pub struct Codeplug {
+    /// Not present in every image.
+    pub settings: Option<CodeplugSection<Settings>>,
     pub contacts: CodeplugSection<Contact>,
+    /// Not present in every image.
+    pub user_contacts: Option<CodeplugSection<UserContact>>,
     pub zones: CodeplugSectionWithChanList<Zone, ZoneList>,
     pub digi_chans: CodeplugSection<DigitalChannel>,
     pub ana_chans: CodeplugSection<AnalogChannel>,
     pub scan_list: CodeplugSectionWithChanList<Scan, ScanList>,
     pub roam_list: CodeplugSectionWithChanList<Roam, RoamList>,
+    /// Not present in every image.
+    pub rx_groups: Option<CodeplugSectionWithChanList<RxGroup, RxGroupList>>,
+    /// Not present in every image.
+    pub emergency_systems: Option<CodeplugSection<EmergencySystem>>,
+    /// Not present in every image.
+    pub phone_systems: Option<CodeplugSection<PhoneSystem>>,
+    /// Not present in every image.
+    pub two_tone_systems: Option<CodeplugSection<TwoToneSystem>>,
+    /// Not present in every image.
+    pub dtmf_systems: Option<CodeplugSection<DtmfSystem>>,
+    /// Not present in every image.
+    pub basic_keys: Option<CodeplugSection<BasicKey>>,
+    /// Not present in every image.
+    pub enhanced_keys: Option<CodeplugSection<EnhancedKey>>,
+    /// Not present in every image.
+    pub quick_texts: Option<CodeplugSection<QuickText>>,
+    /// Not present in every image.
+    pub buttons: Option<CodeplugSection<Buttons>>,
+    /// Sections which hyrps can't decode. These are written back untouched.
+    pub raw_sections: Vec<Section>,
     model: String,
+    profile: &'static RadioModel,
+}
+
+/// Append the `items` whose ID isn't already in `section`, or earlier in
+/// `items`, for as many as fit.
+fn import_new<T: CPData>(
+    section: &mut CodeplugSection<T>,
+    items: Vec<T>,
+    id: impl Fn(&T) -> u32,
+    report: &mut ImportReport,
+) -> Result<()> {
+    let mut ids: HashSet<u32> = section.data.iter().map(&id).collect();
+    let count = items.len();
+    let mut new: Vec<T> = items.into_iter().filter(|i| ids.insert(id(i))).collect();
+
+    report.duplicates += count - new.len();
+
+    report.no_space += new.len().saturating_sub(section.free_space());
+    new.truncate(section.free_space());
+    report.added += new.len();
+
+    section.extend(new)
+}
+
+/// Load a section whose ID and layout haven't been confirmed against a real
+/// image. If it fails to decode, its `section_types` are added to `undecoded`
+/// so they are written back as raw data, rather than the whole codeplug
+/// failing to load.
+fn fetch_provisional<S>(
+    fetched: Result<Option<S>>,
+    section_types: &[u16],
+    undecoded: &mut Vec<u16>,
+) -> Option<S> {
+    match fetched {
+        Ok(s) => s,
+        Err(e) => {
+            println!(
+                "WARNING: Keeping sections {:#x?} as raw data: {:#}",
+                section_types, e
+            );
+            undecoded.extend_from_slice(section_types);
+            None
+        }
+    }
 }
 
 impl Codeplug {
@@ -171,9 +318,84 @@ impl Codeplug {
         let roam = Roam::fetch_section(&sections)?;
         let roam_list =
             RoamList::fetch_section(&sections).context("Could not read roam channel list")?;
+        let mut undecoded = vec![];
+        let rx_groups = fetch_provisional(
+            CodeplugSectionWithChanList::fetch_optional(&sections)
+                .context("Could not read RX group lists"),
+            &[RxGroup::cp_section(), RxGroupList::cp_section()],
+            &mut undecoded,
+        );
+        let emergency_systems = fetch_provisional(
+            EmergencySystem::fetch_optional_section(&sections)
+                .context("Could not read emergency systems"),
+            &[EmergencySystem::cp_section()],
+            &mut undecoded,
+        );
+        let user_contacts = fetch_provisional(
+            UserContact::fetch_optional_section(&sections)
+                .context("Could not read digital contact list"),
+            &[UserContact::cp_section()],
+            &mut undecoded,
+        );
+        let phone_systems = fetch_provisional(
+            PhoneSystem::fetch_optional_section(&sections).context("Could not read phone systems"),
+            &[PhoneSystem::cp_section()],
+            &mut undecoded,
+        );
+        let two_tone_systems = fetch_provisional(
+            TwoToneSystem::fetch_optional_section(&sections)
+                .context("Could not read two-tone systems"),
+            &[TwoToneSystem::cp_section()],
+            &mut undecoded,
+        );
+        let dtmf_systems = fetch_provisional(
+            DtmfSystem::fetch_optional_section(&sections).context("Could not read DTMF systems"),
+            &[DtmfSystem::cp_section()],
+            &mut undecoded,
+        );
+        let settings = fetch_provisional(
+            Settings::fetch_optional_section(&sections).context("Could not read general settings"),
+            &[Settings::cp_section()],
+            &mut undecoded,
+        );
+        let basic_keys = fetch_provisional(
+            BasicKey::fetch_optional_section(&sections)
+                .context("Could not read basic privacy keys"),
+            &[BasicKey::cp_section()],
+            &mut undecoded,
+        );
+        let enhanced_keys = fetch_provisional(
+            EnhancedKey::fetch_optional_section(&sections)
+                .context("Could not read enhanced privacy keys"),
+            &[EnhancedKey::cp_section()],
+            &mut undecoded,
+        );
+        let quick_texts = fetch_provisional(
+            QuickText::fetch_optional_section(&sections)
+                .context("Could not read quick text messages"),
+            &[QuickText::cp_section()],
+            &mut undecoded,
+        );
+        let buttons = fetch_provisional(
+            Buttons::fetch_optional_section(&sections).context("Could not read buttons"),
+            &[Buttons::cp_section()],
+            &mut undecoded,
+        );
+
+        let raw_sections = sections
+            .values()
+            .filter(|s| {
+                !section_registry::is_decoded(s.header.section_type)
+                    || undecoded.contains(&s.header.section_type)
+            })
+            .sorted_by_key(|s| s.addr)
+            .cloned()
+            .collect();
 
         Ok(Codeplug {
+            raw_sections,
             contacts: Contact::fetch_section(&sections)?,
+            user_contacts,
             digi_chans: DigitalChannel::fetch_section(&sections)?,
             ana_chans: AnalogChannel::fetch_section(&sections)?,
             zones: CodeplugSectionWithChanList {
@@ -188,6 +410,17 @@ impl Codeplug {
                 data: roam,
                 channels: roam_list,
             },
+            rx_groups,
+            emergency_systems,
+            phone_systems,
+            two_tone_systems,
+            dtmf_systems,
+            settings,
+            basic_keys,
+            enhanced_keys,
+            quick_texts,
+            buttons,
+            profile: RadioModel::lookup(&model).unwrap_or(&GENERIC),
             model,
         })
     }
@@ -204,11 +437,22 @@ impl Codeplug {
         Ok(ret)
     }
 
+    pub fn radio_model(&self) -> &'static RadioModel {
+        self.profile
+    }
+
+    /// The model string read from the image.
+    pub fn model(&self) -> &str {
+        self.model.trim_matches(char::from(0))
+    }
+
+    /// Models which aren't known are taken to be mobiles if their model
+    /// string starts with 'm'.
     pub fn radio_type(&self) -> DeviceType {
-        if self.model.starts_with('m') {
+        if self.profile.is_generic() && self.model.starts_with('m') {
             DeviceType::Mobile
         } else {
-            DeviceType::Portable
+            self.profile.device_type
         }
     }
 
@@ -237,17 +481,540 @@ impl Codeplug {
 
         self.roam_list
             .write(writer)
-            .context("Failed to write roam lists section")
+            .context("Failed to write roam lists section")?;
+
+        if let Some(rx_groups) = &self.rx_groups {
+            rx_groups
+                .write(writer)
+                .context("Failed to write RX group lists section")?;
+        }
+
+        if let Some(emergency_systems) = &self.emergency_systems {
+            emergency_systems
+                .write(writer)
+                .context("Failed to write emergency systems section")?;
+        }
+
+        if let Some(user_contacts) = &self.user_contacts {
+            user_contacts
+                .write(writer)
+                .context("Failed to write digital contact list section")?;
+        }
+
+        if let Some(phone_systems) = &self.phone_systems {
+            phone_systems
+                .write(writer)
+                .context("Failed to write phone systems section")?;
+        }
+
+        if let Some(two_tone_systems) = &self.two_tone_systems {
+            two_tone_systems
+                .write(writer)
+                .context("Failed to write two-tone systems section")?;
+        }
+
+        if let Some(dtmf_systems) = &self.dtmf_systems {
+            dtmf_systems
+                .write(writer)
+                .context("Failed to write DTMF systems section")?;
+        }
+
+        if let Some(settings) = &self.settings {
+            settings
+                .write(writer)
+                .context("Failed to write general settings section")?;
+        }
+
+        if let Some(basic_keys) = &self.basic_keys {
+            basic_keys
+                .write(writer)
+                .context("Failed to write basic privacy keys section")?;
+        }
+
+        if let Some(enhanced_keys) = &self.enhanced_keys {
+            enhanced_keys
+                .write(writer)
+                .context("Failed to write enhanced privacy keys section")?;
+        }
+
+        if let Some(quick_texts) = &self.quick_texts {
+            quick_texts
+                .write(writer)
+                .context("Failed to write quick text messages section")?;
+        }
+
+        if let Some(buttons) = &self.buttons {
+            buttons
+                .write(writer)
+                .context("Failed to write buttons section")?;
+        }
+
+        self.raw_sections.iter().try_for_each(|s| {
+            s.write(writer).with_context(|| {
+                format!("Failed to write raw section 0x{:X}", s.header.section_type)
+            })
+        })
     }
 
     pub fn verify(&self) -> anyhow::Result<()> {
+        self.verify_model_limits()?;
         self.contacts.verify(self)?;
         self.ana_chans.verify(self)?;
         self.digi_chans.verify(self)?;
         self.scan_list.verify(self)?;
+
+        for (scan, list) in self
+            .scan_list
+            .data
+            .data
+            .iter()
+            .zip(&self.scan_list.channels.data)
+        {
+            scan.verify_priority(list)?;
+        }
+
+        for (zone, list) in self.zones.data.data.iter().zip(&self.zones.channels.data) {
+            zone.verify_channels(list)?;
+        }
+
+        if let Some(rx_groups) = &self.rx_groups {
+            rx_groups.verify(self)?;
+        }
+
+        if let Some(emergency_systems) = &self.emergency_systems {
+            emergency_systems.verify(self)?;
+        }
+
+        if let Some(user_contacts) = &self.user_contacts {
+            user_contacts.verify(self)?;
+        }
+
+        if let Some(phone_systems) = &self.phone_systems {
+            phone_systems.verify(self)?;
+        }
+
+        if let Some(two_tone_systems) = &self.two_tone_systems {
+            two_tone_systems.verify(self)?;
+        }
+
+        if let Some(dtmf_systems) = &self.dtmf_systems {
+            dtmf_systems.verify(self)?;
+        }
+
+        if let Some(settings) = &self.settings {
+            settings.verify(self)?;
+        }
+
+        if let Some(enhanced_keys) = &self.enhanced_keys {
+            enhanced_keys.verify(self)?;
+        }
+
         self.zones.verify(self)
     }
 
+    /// Add an RX group list listening to `contacts`, returning its index.
+    pub fn add_rx_group(&mut self, name: String, contacts: &[ContactPointer]) -> Result<usize> {
+        let rx_groups = match &mut self.rx_groups {
+            Some(g) if g.data.free_space() == 0 => bail!("No space for RX group list '{}'", name),
+            Some(g) => g,
+            None => bail!("Codeplug has no RX group list section"),
+        };
+
+        let list = RxGroupList::new(contacts);
+
+        Ok(rx_groups.insert(RxGroup::new(name, &list), list))
+    }
+
+    /// The general radio settings, for editing.
+    pub fn settings_mut(&mut self) -> Result<&mut Settings> {
+        match &mut self.settings {
+            Some(s) if s.data.len() == 1 => Ok(&mut s.data[0]),
+            Some(_) => bail!("General settings section should hold a single element"),
+            None => bail!("Codeplug has no general settings section"),
+        }
+    }
+
+    /// Replace the privacy keys with those from a keys file. Key types which
+    /// the file doesn't contain are left untouched.
+    pub fn import_keys(&mut self, keys: KeysFile) -> Result<()> {
+        if !keys.basic.is_empty() {
+            match &mut self.basic_keys {
+                Some(s) => s.replace(keys.basic)?,
+                None => bail!("Codeplug has no basic privacy key section"),
+            }
+        }
+
+        if !keys.enhanced.is_empty() {
+            match &mut self.enhanced_keys {
+                Some(s) => s.replace(keys.enhanced)?,
+                None => bail!("Codeplug has no enhanced privacy key section"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Assign the default functions for this type of radio to its keys.
+    pub fn apply_default_buttons(&mut self) -> Result<()> {
+        let defaults = Buttons::defaults(self.radio_type());
+
+        match &mut self.buttons {
+            Some(b) if b.data.len() == 1 => {
+                b.data[0] = Buttons {
+                    trailer: b.data[0].trailer,
+                    ..defaults
+                };
+                Ok(())
+            }
+            Some(_) => bail!("Buttons section should hold a single element"),
+            None => bail!("Codeplug has no buttons section"),
+        }
+    }
+
+    /// Set the RX and TX tones of the analog channel `name`, leaving those
+    /// not given unchanged.
+    pub fn set_analog_tones(
+        &mut self,
+        name: &str,
+        rx_tone: Option<Tone>,
+        tx_tone: Option<Tone>,
+    ) -> Result<()> {
+        let ac = match self.find_channel(name)? {
+            ChannelPointer::Analog(i) => &mut self.ana_chans.data[i as usize],
+            _ => bail!("Tones can only be set on an analog channel"),
+        };
+
+        if let Some(tone) = rx_tone {
+            ac.set_rx_tone(tone);
+        }
+
+        if let Some(tone) = tx_tone {
+            ac.tx_ctcss = tone.into();
+        }
+
+        Ok(())
+    }
+
+    /// Make the analog channel `name` a selective call channel using
+    /// `signalling`, which must exist.
+    pub fn set_selective_call(&mut self, name: &str, signalling: Signalling) -> Result<()> {
+        let idx = match self.find_channel(name)? {
+            ChannelPointer::Analog(i) => i as usize,
+            _ => bail!("Selective calling needs an analog channel"),
+        };
+
+        let (present, len) = match signalling {
+            Signalling::None => (true, usize::MAX),
+            Signalling::TwoTone(_) => (
+                self.two_tone_systems.is_some(),
+                self.two_tone_systems.as_ref().map_or(0, |s| s.data.len()),
+            ),
+            Signalling::Dtmf(_) => (
+                self.dtmf_systems.is_some(),
+                self.dtmf_systems.as_ref().map_or(0, |s| s.data.len()),
+            ),
+        };
+
+        match signalling {
+            _ if !present => bail!("Codeplug has no section for {:?}", signalling),
+            Signalling::TwoTone(i) | Signalling::Dtmf(i) if i >= len => {
+                bail!("Signalling system {} doesn't exist", i + 1)
+            }
+            _ => (),
+        }
+
+        self.ana_chans.data[idx].set_selective_call(signalling)
+    }
+
+    /// Set the DTMF digits the radio answers to on DTMF system `idx`.
+    pub fn set_dtmf_id(&mut self, idx: usize, id: &str) -> Result<()> {
+        match self.dtmf_systems.as_mut().and_then(|s| s.data.get_mut(idx)) {
+            Some(s) => {
+                s.own_id = dtmf::from_str(id)?;
+                Ok(())
+            }
+            None => bail!("DTMF system {} doesn't exist", idx + 1),
+        }
+    }
+
+    /// Add a quick text message, returning its index.
+    pub fn add_quick_text(&mut self, body: &str) -> Result<usize> {
+        let qt = QuickText::new(body)?;
+
+        match &mut self.quick_texts {
+            Some(q) if q.data.len() + 1 >= q.sec.header.capacity as usize => {
+                bail!("No space for another quick text message")
+            }
+            Some(q) => Ok(q.insert(qt)),
+            None => bail!("Codeplug has no quick text message section"),
+        }
+    }
+
+    pub fn remove_quick_text(&mut self, idx: usize) -> Result<QuickText> {
+        match &mut self.quick_texts {
+            Some(q) if idx < q.data.len() => Ok(q.remove(idx)),
+            Some(_) => bail!("Quick text message index {} is out of range", idx),
+            None => bail!("Codeplug has no quick text message section"),
+        }
+    }
+
+    /// Add private call contacts for `users` which pass `filter` and aren't
+    /// already in the list, for as many as fit. They go in the digital contact
+    /// list if `digital_list`, otherwise in the contacts.
+    pub fn import_users(
+        &mut self,
+        users: &[User],
+        filter: &UserFilter,
+        digital_list: bool,
+    ) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let mut new = vec![];
+
+        for user in users {
+            if !filter.matches(user) {
+                report.filtered += 1;
+            } else if user.id == 0 || user.id > settings::MAX_RADIO_ID {
+                report.invalid += 1;
+            } else {
+                new.push(user);
+            }
+        }
+
+        match &mut self.user_contacts {
+            Some(u) if digital_list => import_new(
+                u,
+                new.iter()
+                    .map(|u| UserContact {
+                        name: u.contact_name(),
+                        id: u.id,
+                    })
+                    .collect(),
+                |c| c.id,
+                &mut report,
+            )?,
+            None if digital_list => bail!("Codeplug has no digital contact list section"),
+            _ => import_new(
+                &mut self.contacts,
+                new.iter()
+                    .map(|u| Contact::new(u.contact_name(), CallType::Private, u.id))
+                    .collect(),
+                |c| c.id,
+                &mut report,
+            )?,
+        }
+
+        Ok(report)
+    }
+
+    /// Add group call contacts for `talkgroups` which pass `filter` and
+    /// aren't already in the contacts, for as many as fit. Names are made from
+    /// `name_format`, see `Talkgroup::contact_name`.
+    pub fn import_talkgroups(
+        &mut self,
+        talkgroups: &[Talkgroup],
+        filter: &TalkgroupFilter,
+        name_format: &str,
+    ) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let mut new = vec![];
+
+        for tg in talkgroups {
+            if !filter.matches(tg) {
+                report.filtered += 1;
+            } else if tg.id == 0 || tg.id > settings::MAX_RADIO_ID {
+                report.invalid += 1;
+            } else {
+                new.push(Contact::new(
+                    tg.contact_name(name_format),
+                    CallType::Group,
+                    tg.id,
+                ));
+            }
+        }
+
+        import_new(&mut self.contacts, new, |c| c.id, &mut report)?;
+
+        Ok(report)
+    }
+
+    /// Add a zone of `channels`, returning its index.
+    pub fn add_zone(&mut self, name: String, channels: Vec<ChannelPointer>) -> Result<usize> {
+        if channels.is_empty() || channels.len() > zone_list::MAX_CHANNELS {
+            bail!(
+                "Zone '{}' has {} channels, but must have 1 to {}",
+                name,
+                channels.len(),
+                zone_list::MAX_CHANNELS
+            );
+        }
+
+        if self.zones.data.free_space() == 0 {
+            bail!("No space for zone '{}'", name);
+        }
+
+        let zone = Zone::new(name, &channels);
+
+        Ok(self.zones.insert(zone, ZoneList { channels }))
+    }
+
+    /// Find a zone by name, returning its index.
+    pub fn find_zone(&self, name: &str) -> Result<usize> {
+        match self
+            .zones
+            .data
+            .data
+            .iter()
+            .positions(|z| z.name == name)
+            .collect::<Vec<_>>()[..]
+        {
+            [i] => Ok(i),
+            [] => bail!("No zone named '{}'", name),
+            _ => bail!("Several zones are named '{}'", name),
+        }
+    }
+
+    /// Find a scan list by name, returning its index.
+    pub fn find_scan_list(&self, name: &str) -> Result<usize> {
+        match self
+            .scan_list
+            .data
+            .data
+            .iter()
+            .positions(|s| s.name == name)
+            .collect::<Vec<_>>()[..]
+        {
+            [i] => Ok(i),
+            [] => bail!("No scan list named '{}'", name),
+            _ => bail!("Several scan lists are named '{}'", name),
+        }
+    }
+
+    /// Add an emergency system, returning its index.
+    pub fn add_emergency_system(&mut self, es: EmergencySystem) -> Result<usize> {
+        match &mut self.emergency_systems {
+            Some(e) if e.free_space() == 0 => bail!("No space for emergency system '{}'", es.name),
+            Some(e) => Ok(e.insert(es)),
+            None => bail!("Codeplug has no emergency system section"),
+        }
+    }
+
+    /// Point a channel at the emergency system `es_idx`, or clear it with
+    /// `None`.
+    pub fn assign_emergency_system(
+        &mut self,
+        chan: ChannelPointer,
+        es_idx: Option<usize>,
+    ) -> Result<()> {
+        let n = self.emergency_systems.as_ref().map_or(0, |e| e.data.len());
+        let idx = match es_idx {
+            Some(i) if i >= n => bail!("No emergency system {}", i),
+            Some(i) => i + 1,
+            None => 0,
+        };
+
+        match chan {
+            ChannelPointer::Digital(i) => match self.digi_chans.data.get_mut(i as usize) {
+                Some(dc) => dc.emergency_system_idx = idx as u16,
+                None => bail!("No digital channel {}", i),
+            },
+            ChannelPointer::Analog(i) => match self.ana_chans.data.get_mut(i as usize) {
+                Some(ac) => ac.emergency.idx = idx as u8,
+                None => bail!("No analog channel {}", i),
+            },
+            ChannelPointer::Selected => bail!("Can't assign emergency system to selected channel"),
+        }
+
+        Ok(())
+    }
+
+    fn verify_model_limits(&self) -> anyhow::Result<()> {
+        let model = self.profile;
+
+        if model.is_generic() {
+            return Ok(());
+        }
+
+        let no_channels = self.digi_chans.data.len() + self.ana_chans.data.len();
+
+        if no_channels > model.max_channels {
+            bail!(
+                "{} channels exceed the {} limit of {}",
+                no_channels,
+                model.name,
+                model.max_channels
+            );
+        }
+
+        if self.zones.data.data.len() > model.max_zones {
+            bail!(
+                "{} zones exceed the {} limit of {}",
+                self.zones.data.data.len(),
+                model.name,
+                model.max_zones
+            );
+        }
+
+        if self.contacts.data.len() > model.max_contacts {
+            bail!(
+                "{} contacts exceed the {} limit of {}",
+                self.contacts.data.len(),
+                model.name,
+                model.max_contacts
+            );
+        }
+
+        let channels = self
+            .digi_chans
+            .data
+            .iter()
+            .map(|c| &c.common)
+            .chain(self.ana_chans.data.iter().map(|c| &c.common));
+
+        for c in channels {
+            if !model.in_band(c.rx_freq) || (!c.rx_only && !model.in_band(c.tx_freq)) {
+                bail!(
+                    "Channel '{}' frequencies are outside of the {} bands",
+                    c.name,
+                    model.name
+                );
+            }
+
+            if !model.supports_power(c.power_level) {
+                bail!(
+                    "Channel '{}' power level {:?} is not supported by the {}",
+                    c.name,
+                    c.power_level,
+                    model.name
+                );
+            }
+        }
+
+        let names = self
+            .digi_chans
+            .data
+            .iter()
+            .map(|c| &c.common.name)
+            .chain(self.ana_chans.data.iter().map(|c| &c.common.name))
+            .chain(self.zones.data.data.iter().map(|z| &z.name))
+            .chain(self.contacts.data.iter().map(|c| &c.name));
+
+        for name in names {
+            if name.encode_utf16().count() > model.name_len {
+                bail!(
+                    "Name '{}' is longer than the {} limit of {} characters",
+                    name,
+                    model.name,
+                    model.name_len
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Empty the sections that `write-custom-codeplug` regenerates, along with
+    /// those holding references into them. Standalone data such as the user
+    /// contact phone book, phone systems and signalling systems are left alone.
     pub fn clear_codeplug(&mut self) {
         self.contacts.clear();
         self.ana_chans.clear();
@@ -255,15 +1022,42 @@ impl Codeplug {
         self.scan_list.clear();
         self.zones.clear();
         self.roam_list.clear();
+
+        if let Some(rx_groups) = &mut self.rx_groups {
+            rx_groups.clear();
+        }
+
+        // Emergency systems aren't regenerated, so keep them but drop their
+        // references to the cleared channels and contacts.
+        if let Some(emergency_systems) = &mut self.emergency_systems {
+            for es in &mut emergency_systems.data {
+                es.revert_channel = ChannelPointer::Selected;
+                es.tx_contact_idx = 0;
+            }
+        }
     }
 }
 
 impl Display for Codeplug {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Radio Model: {}\n", self.radio_model().name)?;
+
+        if let Some(settings) = &self.settings {
+            writeln!(f, "General Settings")?;
+            writeln!(f, "================")?;
+            Settings::print_table(&settings.data, self);
+        }
+
         write!(f, "Contacts\n")?;
         write!(f, "========\n")?;
         Contact::print_table(&self.contacts.data, self);
 
+        if let Some(user_contacts) = &self.user_contacts {
+            writeln!(f, "Digital Contact List")?;
+            writeln!(f, "====================")?;
+            UserContact::print_table(&user_contacts.data, self);
+        }
+
         write!(f, "Digital Channels\n")?;
         write!(f, "================\n")?;
         DigitalChannel::print_table(&self.digi_chans.data, self);
@@ -272,6 +1066,64 @@ impl Display for Codeplug {
         write!(f, "===============\n")?;
         AnalogChannel::print_table(&self.ana_chans.data, self);
 
+        if let Some(emergency_systems) = &self.emergency_systems {
+            writeln!(f, "Emergency Systems")?;
+            writeln!(f, "=================")?;
+            EmergencySystem::print_table(&emergency_systems.data, self);
+        }
+
+        if let Some(phone_systems) = &self.phone_systems {
+            writeln!(f, "Phone Systems")?;
+            writeln!(f, "=============")?;
+            PhoneSystem::print_table(&phone_systems.data, self);
+        }
+
+        if let Some(two_tone_systems) = &self.two_tone_systems {
+            writeln!(f, "Two-Tone Systems")?;
+            writeln!(f, "================")?;
+            TwoToneSystem::print_table(&two_tone_systems.data, self);
+        }
+
+        if let Some(dtmf_systems) = &self.dtmf_systems {
+            writeln!(f, "DTMF Systems")?;
+            writeln!(f, "============")?;
+            DtmfSystem::print_table(&dtmf_systems.data, self);
+        }
+
+        if let Some(basic_keys) = &self.basic_keys {
+            writeln!(f, "Basic Privacy Keys")?;
+            writeln!(f, "==================")?;
+            BasicKey::print_table(&basic_keys.data, self);
+        }
+
+        if let Some(enhanced_keys) = &self.enhanced_keys {
+            writeln!(f, "Enhanced Privacy Keys")?;
+            writeln!(f, "=====================")?;
+            EnhancedKey::print_table(&enhanced_keys.data, self);
+        }
+
+        if let Some(quick_texts) = &self.quick_texts {
+            writeln!(f, "Quick Text Messages")?;
+            writeln!(f, "===================")?;
+            QuickText::print_table(&quick_texts.data, self);
+        }
+
+        if let Some(buttons) = &self.buttons {
+            writeln!(f, "Buttons")?;
+            writeln!(f, "=======")?;
+
+            for b in buttons.data.iter() {
+                <(&str, &KeyAssignment)>::print_table(
+                    &Buttons::key_names(self.radio_type())
+                        .iter()
+                        .copied()
+                        .zip(b.keys.iter())
+                        .collect::<Vec<_>>(),
+                    self,
+                );
+            }
+        }
+
         write!(f, "Scan Lists\n")?;
         write!(f, "==========\n")?;
         <(&Scan, &ScanList)>::print_table(
@@ -298,6 +1150,20 @@ impl Display for Codeplug {
             self,
         );
 
+        if let Some(rx_groups) = &self.rx_groups {
+            writeln!(f, "RX Group Lists")?;
+            writeln!(f, "==============")?;
+            <(&RxGroup, &RxGroupList)>::print_table(
+                &rx_groups
+                    .data
+                    .data
+                    .iter()
+                    .zip(&rx_groups.channels.data)
+                    .collect::<Vec<_>>(),
+                self,
+            );
+        }
+
         write!(f, "Zones\n")?;
         write!(f, "=====\n")?;
         <(&Zone, &ZoneList)>::print_table(
@@ -314,3 +1180,236 @@ impl Display for Codeplug {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        channel_pointer::pointer::ChannelPointer,
+        contact::{
+            call_type::CallType,
+            pointer::ContactPointer,
+            users_csv::{User, UserFilter},
+            Contact, ImportReport,
+        },
+        cp_data::CPData,
+        emergency_system::{alarm_type::AlarmType, EmergencySystem},
+        radio_model::GENERIC,
+        section::Section,
+        settings::MAX_RADIO_ID,
+        Codeplug, DeviceType,
+    };
+    use crate::tests::{empty_codeplug, empty_image};
+    use std::io::Cursor;
+
+    fn user(id: u32, country: &str) -> User {
+        User {
+            id,
+            callsign: format!("M{}", id),
+            first_name: "Al".to_string(),
+            region: String::new(),
+            country: country.to_string(),
+        }
+    }
+
+    #[test]
+    fn radio_type() {
+        let mut cp = empty_codeplug(4, 4);
+        assert_eq!(cp.radio_type(), DeviceType::Portable);
+
+        cp.profile = &GENERIC;
+        cp.model = "md999".to_string();
+        assert_eq!(cp.radio_type(), DeviceType::Mobile);
+
+        cp.model = "pd999".to_string();
+        assert_eq!(cp.radio_type(), DeviceType::Portable);
+    }
+
+    #[test]
+    fn assign_emergency_system_to_missing_channel() {
+        let mut cp = empty_codeplug(4, 4);
+
+        assert!(cp
+            .assign_emergency_system(ChannelPointer::Digital(0), None)
+            .is_err());
+        assert!(cp
+            .assign_emergency_system(ChannelPointer::Analog(3), None)
+            .is_err());
+        assert!(cp
+            .assign_emergency_system(ChannelPointer::Digital(0), Some(0))
+            .is_err());
+    }
+
+    #[test]
+    fn clear_keeps_emergency_systems() {
+        let mut cp = empty_codeplug(4, 4);
+        let mut es = EmergencySystem::new("Local".to_string(), AlarmType::Regular, 0);
+        es.revert_channel = ChannelPointer::Digital(0);
+        cp.add_emergency_system(es).unwrap();
+
+        cp.clear_codeplug();
+
+        let systems = &cp.emergency_systems.as_ref().unwrap().data;
+        assert_eq!(systems.len(), 1);
+        assert_eq!(systems[0].name, "Local");
+        assert_eq!(systems[0].revert_channel, ChannelPointer::Selected);
+        assert_eq!(systems[0].tx_contact_idx, 0);
+    }
+
+    #[test]
+    fn add_emergency_system_when_full() {
+        let mut cp = empty_codeplug(4, 3);
+        let es = |name: &str| EmergencySystem::new(name.to_string(), AlarmType::Regular, 0);
+
+        assert_eq!(cp.add_emergency_system(es("A")).unwrap(), 0);
+        assert_eq!(cp.add_emergency_system(es("B")).unwrap(), 1);
+        assert!(cp.add_emergency_system(es("C")).is_err());
+        assert_eq!(cp.emergency_systems.as_ref().unwrap().data.len(), 2);
+
+        cp.emergency_systems = None;
+        assert!(cp.add_emergency_system(es("D")).is_err());
+    }
+
+    #[test]
+    fn add_rx_group_when_full() {
+        let mut cp = empty_codeplug(4, 3);
+        let contacts = [ContactPointer(0)];
+
+        assert_eq!(cp.add_rx_group("A".to_string(), &contacts).unwrap(), 0);
+        assert_eq!(cp.add_rx_group("B".to_string(), &contacts).unwrap(), 1);
+        assert!(cp.add_rx_group("C".to_string(), &contacts).is_err());
+        assert_eq!(cp.rx_groups.as_ref().unwrap().data.data.len(), 2);
+    }
+
+    #[test]
+    fn undecodable_section_kept_raw() {
+        let mut image = empty_image(4, 2);
+        let mut sections = Section::load_sections(&mut Cursor::new(&image)).unwrap();
+        let emergency = sections.get_mut(&EmergencySystem::cp_section()).unwrap();
+        emergency.header.elements_in_use = 1;
+        // Not a valid alarm type.
+        emergency.data[32] = 0xff;
+        emergency.write(&mut Cursor::new(&mut image)).unwrap();
+
+        let mut cp = Codeplug::read_codeplug(&mut Cursor::new(&image)).unwrap();
+        assert!(cp.emergency_systems.is_none());
+        assert!(cp
+            .raw_sections
+            .iter()
+            .any(|s| s.header.section_type == EmergencySystem::cp_section()));
+
+        let mut out = image.clone();
+        cp.write_codeplug(&mut Cursor::new(&mut out)).unwrap();
+        assert_eq!(out, image);
+    }
+
+    #[test]
+    fn element_size_mismatch() {
+        let image = empty_image(4, 2);
+        let mut sections = Section::load_sections(&mut Cursor::new(&image)).unwrap();
+        let emergency = sections.get_mut(&EmergencySystem::cp_section()).unwrap();
+        emergency.header.elements_in_use = 1;
+        emergency.data[32] = AlarmType::Regular.into();
+        // Revert to the selected channel.
+        emergency.data[34..36].fill(0xff);
+        assert!(EmergencySystem::fetch_section(&sections).is_ok());
+
+        // Elements longer than the struct would lose their trailing bytes.
+        let emergency = sections.get_mut(&EmergencySystem::cp_section()).unwrap();
+        emergency.header.byte_size = 2 * 48;
+        emergency.data.resize(2 * 48, 0);
+        let err = EmergencySystem::fetch_section(&sections).err().unwrap();
+        assert!(err.to_string().contains("only 41 were decoded"));
+
+        let mut cp = empty_codeplug(4, 2);
+        let mut es = EmergencySystem::new("Local".to_string(), AlarmType::Regular, 0);
+        es.tx_contact_idx = 0;
+        cp.add_emergency_system(es).unwrap();
+        let emergency = &mut cp.emergency_systems.as_mut().unwrap().sec;
+        emergency.header.byte_size = 2 * 48;
+        emergency.data.resize(2 * 48, 0);
+
+        let mut out = image.clone();
+        let err = cp.write_codeplug(&mut Cursor::new(&mut out)).err().unwrap();
+        assert!(format!("{:#}", err).contains("stored as 41 bytes"));
+    }
+
+    #[test]
+    fn import_users() {
+        let mut cp = empty_codeplug(4, 4);
+        cp.contacts
+            .insert(Contact::new("Old".to_string(), CallType::Group, 100));
+
+        let users = [
+            user(100, "GB"),
+            user(200, "GB"),
+            user(200, "GB"),
+            user(0, "GB"),
+            user(MAX_RADIO_ID + 1, "GB"),
+            user(250, "US"),
+            user(300, "GB"),
+            user(400, "GB"),
+        ];
+        let filter = UserFilter {
+            countries: vec!["gb".to_string()],
+            ..UserFilter::default()
+        };
+
+        let report = cp.import_users(&users, &filter, false).unwrap();
+
+        assert_eq!(
+            report,
+            ImportReport {
+                added: 2,
+                duplicates: 2,
+                filtered: 1,
+                invalid: 2,
+                no_space: 1,
+            }
+        );
+
+        let ids: Vec<_> = cp.contacts.data.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [100, 200, 300]);
+        assert_eq!(cp.contacts.data[1].name, "M200 Al");
+        assert_eq!(cp.contacts.data[1].call_type, CallType::Private);
+
+        let report = cp.import_users(&users, &filter, true).unwrap();
+        let user_contacts = cp.user_contacts.as_ref().unwrap();
+
+        assert_eq!(
+            (report.added, report.duplicates, report.no_space),
+            (3, 1, 1)
+        );
+        assert_eq!(user_contacts.data.len(), 3);
+        assert_eq!(user_contacts.data[0].id, 100);
+        assert_eq!(cp.contacts.data.len(), 3);
+
+        cp.user_contacts = None;
+        assert!(cp.import_users(&users, &filter, true).is_err());
+    }
+
+    #[test]
+    fn extend_section() {
+        let mut cp = empty_codeplug(4, 4);
+        let contact = |id| Contact::new(format!("TG{}", id), CallType::Group, id);
+
+        assert_eq!(cp.contacts.free_space(), 3);
+        assert!(cp.contacts.extend((1..5).map(contact).collect()).is_err());
+        assert!(cp.contacts.data.is_empty());
+        assert_eq!(cp.contacts.sec.header.elements_in_use, 0);
+
+        cp.contacts.extend((1..3).map(contact).collect()).unwrap();
+        assert_eq!(cp.contacts.free_space(), 1);
+        assert_eq!(cp.contacts.sec.header.elements_in_use, 2);
+
+        cp.contacts.insert(contact(3));
+        assert_eq!(cp.contacts.free_space(), 0);
+        assert!(cp.contacts.extend(vec![contact(4)]).is_err());
+        assert!(cp.contacts.extend(vec![]).is_ok());
+
+        assert!(cp.contacts.replace((1..5).map(contact).collect()).is_err());
+        cp.contacts.replace((7..10).map(contact).collect()).unwrap();
+        let ids: Vec<_> = cp.contacts.data.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [7, 8, 9]);
+        assert_eq!(cp.contacts.sec.header.elements_in_use, 3);
+    }
+}
@@ -0,0 +1,117 @@
+use super::{
+    cp_data::CPData,
+    disp_tabular::DisplayTabular,
+    dtmf,
+    layout::{CPLayout, Field},
+    Codeplug,
+};
+use anyhow::{bail, Context};
+use hyrps_derive::RawCPData;
+
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+/// Length of the access and de-access codes, which are stored as null padded
+/// ASCII DTMF digits.
+const CODE_LEN: usize = 10;
+
+#[derive(Debug, PartialEq, Eq, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct PhoneSystem {
+    #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
+    pub name: String,
+    /// Radio ID of the telephone interconnect gateway.
+    pub gateway_id: u32,
+    pub access_code: [u8; CODE_LEN],
+    pub deaccess_code: [u8; CODE_LEN],
+    /// Not decoded yet, carried through as read.
+    pub unk1: [u8; 4],
+}
+
+impl PhoneSystem {
+    /// Look up the phone system referenced by a channel's one based index.
+    pub fn resolve(codeplug: &Codeplug, idx: u16) -> Option<&PhoneSystem> {
+        if idx == 0 {
+            None
+        } else {
+            codeplug.phone_systems.as_ref()?.data.get(idx as usize - 1)
+        }
+    }
+
+    /// Name of the phone system referenced by `idx`, for tabular output.
+    pub fn resolve_name(codeplug: &Codeplug, idx: u16) -> String {
+        match (idx, Self::resolve(codeplug, idx)) {
+            (0, _) => "<None>".to_string(),
+            (_, Some(p)) => p.name.clone(),
+            (_, None) => "<Invalid>".to_string(),
+        }
+    }
+
+    /// Check a channel's reference to a phone system. References can't be
+    /// checked when the section isn't present.
+    pub fn verify_ref(codeplug: &Codeplug, chan_name: &str, idx: u16) -> anyhow::Result<()> {
+        if idx != 0 && codeplug.phone_systems.is_some() && Self::resolve(codeplug, idx).is_none() {
+            bail!(
+                "Channel '{}' references missing phone system {}",
+                chan_name,
+                idx
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl CPData for PhoneSystem {
+    fn cp_section() -> u16 {
+        0x2e
+    }
+
+    fn verify(&self, _codeplug: &Codeplug) -> anyhow::Result<()> {
+        dtmf::verify(&self.access_code)
+            .with_context(|| format!("Phone system '{}' access code", self.name))?;
+        dtmf::verify(&self.deaccess_code)
+            .with_context(|| format!("Phone system '{}' de-access code", self.name))
+    }
+}
+
+impl CPLayout for PhoneSystem {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "name"),
+        Field::new(32, 4, "gateway_id"),
+        Field::bytes(36, CODE_LEN, "access_code"),
+        Field::bytes(36 + CODE_LEN, CODE_LEN, "deaccess_code"),
+        Field::unknown(36 + 2 * CODE_LEN, 4, "unk1"),
+    ];
+}
+
+impl DisplayTabular for PhoneSystem {
+    fn get_heading() -> Vec<String> {
+        ["Name", "Gateway ID", "Access Code", "De-access Code"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn get_row(&self, _codeplug: &Codeplug) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.gateway_id.to_string(),
+            dtmf::to_string(&self.access_code),
+            dtmf::to_string(&self.deaccess_code),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_serde;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn phone_system_serde(ps in any::<super::PhoneSystem>()) {
+            check_serde(&ps)?;
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Positions on the earth, given either as latitude and longitude or as a
+//! Maidenhead locator, for building zones by distance.
+
+use anyhow::{anyhow, bail};
+use std::str::FromStr;
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    /// Degrees north.
+    pub lat: f64,
+    /// Degrees east.
+    pub lon: f64,
+}
+
+impl Position {
+    pub fn new(lat: f64, lon: f64) -> anyhow::Result<Self> {
+        if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+            bail!("Position {}, {} is out of range", lat, lon);
+        }
+
+        Ok(Position { lat, lon })
+    }
+
+    /// Great circle distance to `other` in kilometres.
+    pub fn distance_km(&self, other: &Position) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.lon - self.lon).to_radians();
+
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+
+    /// The four character Maidenhead grid square, e.g. `IO93`.
+    pub fn grid_square(&self) -> String {
+        // Keep the poles and antimeridian inside the last square.
+        let lon = (self.lon + 180.0).min(359.999_999);
+        let lat = (self.lat + 90.0).min(179.999_999);
+
+        let field = |v: f64, size: f64| (b'A' + (v / size) as u8) as char;
+        let square = |v: f64, size: f64| (b'0' + ((v % (size * 10.0)) / size) as u8) as char;
+
+        [
+            field(lon, 20.0),
+            field(lat, 10.0),
+            square(lon, 2.0),
+            square(lat, 1.0),
+        ]
+        .iter()
+        .collect()
+    }
+
+    /// Parse a Maidenhead locator of 2, 4 or 6 characters, giving the centre
+    /// of its square.
+    pub fn from_locator(locator: &str) -> anyhow::Result<Self> {
+        let chars: Vec<char> = locator.trim().to_ascii_uppercase().chars().collect();
+        let invalid = || anyhow!("Invalid Maidenhead locator: {}", locator);
+
+        if !matches!(chars.len(), 2 | 4 | 6) {
+            return Err(invalid());
+        }
+
+        // Each pair is (base, number of divisions, degrees of longitude per
+        // division), latitude divisions being half the size.
+        let pairs = [('A', 18, 20.0), ('0', 10, 2.0), ('A', 24, 2.0 / 24.0)];
+        let (mut lon, mut lat, mut size) = (-180.0, -90.0, 360.0);
+
+        for (pair, (base, divisions, step)) in chars.chunks(2).zip(pairs.iter()) {
+            let digit = |c: char| {
+                (c as u32)
+                    .checked_sub(*base as u32)
+                    .filter(|d| *d < *divisions)
+                    .ok_or_else(invalid)
+            };
+
+            lon += digit(pair[0])? as f64 * step;
+            lat += digit(pair[1])? as f64 * step / 2.0;
+            size = *step;
+        }
+
+        Position::new(lat + size / 4.0, lon + size / 2.0)
+    }
+}
+
+impl FromStr for Position {
+    type Err = anyhow::Error;
+
+    /// Parse `lat,lon` in degrees or a Maidenhead locator.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(',') {
+            Some((lat, lon)) => {
+                let parse = |v: &str| {
+                    v.trim()
+                        .parse::<f64>()
+                        .map_err(|_| anyhow!("Invalid position: {}", s))
+                };
+
+                Position::new(parse(lat)?, parse(lon)?)
+            }
+            None => Position::from_locator(s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Position;
+
+    #[test]
+    fn grid_square() {
+        let leeds: Position = "53.7997,-1.5492".parse().unwrap();
+        assert_eq!(leeds.grid_square(), "IO93");
+
+        let sydney = Position::new(-33.8688, 151.2093).unwrap();
+        assert_eq!(sydney.grid_square(), "QF56");
+
+        assert_eq!(Position::new(90.0, 180.0).unwrap().grid_square(), "RR99");
+        assert_eq!(Position::new(-90.0, -180.0).unwrap().grid_square(), "AA00");
+    }
+
+    #[test]
+    fn locators() {
+        let io93 = Position::from_locator("IO93").unwrap();
+        assert!((io93.lat - 53.5).abs() < 1e-9);
+        assert!((io93.lon - -1.0).abs() < 1e-9);
+
+        let io93fs: Position = "io93fs".parse().unwrap();
+        assert_eq!(io93fs.grid_square(), "IO93");
+        assert!((io93fs.lat - 53.770833).abs() < 1e-5);
+        assert!((io93fs.lon - -1.541667).abs() < 1e-5);
+
+        assert!(Position::from_locator("IO9").is_err());
+        assert!(Position::from_locator("SO93").is_err());
+        assert!(Position::from_locator("IO93zz").is_err());
+        assert!("91,0".parse::<Position>().is_err());
+        assert!("53.8,x".parse::<Position>().is_err());
+    }
+
+    #[test]
+    fn distance() {
+        let leeds = Position::new(53.7997, -1.5492).unwrap();
+        let manchester = Position::new(53.4808, -2.2426).unwrap();
+
+        assert!((leeds.distance_km(&manchester) - 58.0).abs() < 1.0);
+        assert_eq!(leeds.distance_km(&leeds), 0.0);
+    }
+}
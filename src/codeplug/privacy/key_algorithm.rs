@@ -0,0 +1,23 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+#[derive(Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive, Clone, Copy)]
+#[repr(u8)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub enum KeyAlgorithm {
+    Arc4 = 0,
+    Aes128 = 1,
+    Aes256 = 2,
+}
+
+impl KeyAlgorithm {
+    /// Number of bytes of key material used by the algorithm.
+    pub fn key_len(&self) -> usize {
+        match self {
+            Self::Arc4 => 5,
+            Self::Aes128 => 16,
+            Self::Aes256 => 32,
+        }
+    }
+}
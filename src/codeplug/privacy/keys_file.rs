@@ -0,0 +1,127 @@
+//! Privacy keys file, as read by `import-keys`. Each non-blank line which
+//! doesn't start with `#` describes one key:
+//!
+//! ```text
+//! basic <hex key>
+//! enhanced <name> <key id> <arc4|aes128|aes256> <hex key>
+//! ```
+//!
+//! Errors never include key material.
+
+use super::{key_algorithm::KeyAlgorithm, BasicKey, EnhancedKey, BASIC_KEY_LEN};
+use anyhow::{bail, Context};
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct KeysFile {
+    pub basic: Vec<BasicKey>,
+    pub enhanced: Vec<EnhancedKey>,
+}
+
+/// Decode a hex key into the start of `out`, which it must fit in.
+fn parse_hex(s: &str, out: &mut [u8]) -> anyhow::Result<()> {
+    if !s.len().is_multiple_of(2) || s.len() / 2 > out.len() {
+        bail!(
+            "Key must be an even number of hex digits, at most {}",
+            out.len() * 2
+        );
+    }
+
+    if !s.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("Key contains a non hex digit");
+    }
+
+    for (i, o) in out.iter_mut().enumerate().take(s.len() / 2) {
+        *o = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16)?;
+    }
+
+    Ok(())
+}
+
+fn parse_algorithm(s: &str) -> anyhow::Result<KeyAlgorithm> {
+    Ok(match s.to_ascii_lowercase().as_str() {
+        "arc4" => KeyAlgorithm::Arc4,
+        "aes128" => KeyAlgorithm::Aes128,
+        "aes256" => KeyAlgorithm::Aes256,
+        _ => bail!("Unknown key algorithm '{}'", s),
+    })
+}
+
+fn parse_line(keys: &mut KeysFile, fields: &[&str]) -> anyhow::Result<()> {
+    match fields {
+        ["basic", hex] => {
+            let mut key = [0; BASIC_KEY_LEN];
+            parse_hex(hex, &mut key)?;
+
+            keys.basic.push(BasicKey { key });
+        }
+        ["enhanced", name, key_id, algorithm, hex] => {
+            let algorithm = parse_algorithm(algorithm)?;
+            let mut key = [0; 32];
+            parse_hex(hex, &mut key[..algorithm.key_len()])?;
+
+            keys.enhanced.push(EnhancedKey {
+                name: name.to_string(),
+                key_id: key_id.parse().context("Invalid key ID")?,
+                algorithm,
+                key,
+            });
+        }
+        [kind, ..] if *kind == "basic" || *kind == "enhanced" => {
+            bail!("Wrong number of fields for a {} key", kind)
+        }
+        [kind, ..] => bail!("Unknown key type '{}'", kind),
+        [] => (),
+    }
+
+    Ok(())
+}
+
+impl KeysFile {
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        let mut keys = KeysFile::default();
+
+        for (n, line) in s.lines().enumerate() {
+            let line = line.trim();
+
+            if line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            parse_line(&mut keys, &fields).with_context(|| format!("Line {}", n + 1))?;
+        }
+
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyAlgorithm, KeysFile};
+
+    #[test]
+    fn parse_keys() {
+        let keys =
+            KeysFile::parse("# Fleet keys\n\nbasic 0102030405\nenhanced Ops 3 arc4 a1b2c3d4e5\n")
+                .unwrap();
+
+        assert_eq!(keys.basic.len(), 1);
+        assert_eq!(keys.basic[0].key, [1, 2, 3, 4, 5]);
+        assert_eq!(keys.enhanced[0].name, "Ops");
+        assert_eq!(keys.enhanced[0].key_id, 3);
+        assert_eq!(keys.enhanced[0].algorithm, KeyAlgorithm::Arc4);
+        assert_eq!(keys.enhanced[0].key[..6], [0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0]);
+    }
+
+    #[test]
+    fn errors_hide_keys() {
+        let e = KeysFile::parse("enhanced Ops 3 arc4 a1b2c3d4e5f6\n").unwrap_err();
+        let msg = format!("{:#}", e);
+
+        assert!(msg.starts_with("Line 1"));
+        assert!(!msg.contains("a1b2"));
+
+        assert!(KeysFile::parse("basic 01zz\n").is_err());
+    }
+}
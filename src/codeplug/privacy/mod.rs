@@ -0,0 +1,197 @@
+use self::{key_algorithm::KeyAlgorithm, privacy_type::PrivacyType};
+use super::{
+    cp_data::CPData,
+    disp_tabular::DisplayTabular,
+    layout::{CPLayout, Field},
+    Codeplug,
+};
+use anyhow::bail;
+use hyrps_derive::RawCPData;
+use std::fmt;
+
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+pub mod key_algorithm;
+pub mod keys_file;
+pub mod privacy_type;
+
+/// Shown in place of key material, which is never printed.
+pub const HIDDEN: &str = "<hidden>";
+
+pub const BASIC_KEY_LEN: usize = 5;
+
+#[derive(PartialEq, Eq, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+#[cp(magic([u8; 3] = [0; 3]))]
+pub struct BasicKey {
+    pub key: [u8; BASIC_KEY_LEN],
+}
+
+impl fmt::Debug for BasicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicKey").field("key", &HIDDEN).finish()
+    }
+}
+
+impl CPData for BasicKey {
+    fn cp_section() -> u16 {
+        0x30
+    }
+}
+
+impl CPLayout for BasicKey {
+    const LAYOUT: &'static [Field] = &[
+        Field::secret(0, BASIC_KEY_LEN, "key"),
+        Field::unknown(BASIC_KEY_LEN, 3, "padding"),
+    ];
+}
+
+impl DisplayTabular for BasicKey {
+    fn get_heading() -> Vec<String> {
+        ["Key"].iter().map(|s| s.to_string()).collect()
+    }
+
+    fn get_row(&self, _codeplug: &Codeplug) -> Vec<String> {
+        vec![HIDDEN.to_string()]
+    }
+}
+
+#[derive(PartialEq, Eq, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct EnhancedKey {
+    #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
+    pub name: String,
+    pub key_id: u8,
+    #[cp(repr(u8))]
+    pub algorithm: KeyAlgorithm,
+    /// Only the first `algorithm.key_len()` bytes are used, the rest are
+    /// zero.
+    pub key: [u8; 32],
+}
+
+impl fmt::Debug for EnhancedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnhancedKey")
+            .field("name", &self.name)
+            .field("key_id", &self.key_id)
+            .field("algorithm", &self.algorithm)
+            .field("key", &HIDDEN)
+            .finish()
+    }
+}
+
+impl CPData for EnhancedKey {
+    fn cp_section() -> u16 {
+        0x31
+    }
+
+    fn verify(&self, _codeplug: &Codeplug) -> anyhow::Result<()> {
+        if self.key[self.algorithm.key_len()..].iter().any(|b| *b != 0) {
+            bail!(
+                "Key '{}' is longer than {:?} allows",
+                self.name,
+                self.algorithm
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl CPLayout for EnhancedKey {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "name"),
+        Field::new(32, 1, "key_id"),
+        Field::new(33, 1, "algorithm"),
+        Field::secret(34, 32, "key"),
+    ];
+}
+
+impl DisplayTabular for EnhancedKey {
+    fn get_heading() -> Vec<String> {
+        ["Name", "Key ID", "Algorithm", "Key"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn get_row(&self, _codeplug: &Codeplug) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.key_id.to_string(),
+            format!("{:?}", self.algorithm),
+            HIDDEN.to_string(),
+        ]
+    }
+}
+
+/// Describe a channel's privacy setting for tabular output.
+pub fn describe(codeplug: &Codeplug, kind: PrivacyType, idx: u8) -> String {
+    let idx = idx as usize;
+
+    match kind {
+        PrivacyType::None => "<None>".to_string(),
+        PrivacyType::Basic => format!("Basic {}", idx + 1),
+        PrivacyType::Enhanced => match codeplug
+            .enhanced_keys
+            .as_ref()
+            .and_then(|k| k.data.get(idx))
+        {
+            Some(k) => format!("Enhanced {}", k.name),
+            None => "Enhanced <Invalid>".to_string(),
+        },
+    }
+}
+
+/// Check a channel's reference to a privacy key. References can't be checked
+/// when the key section isn't present.
+pub fn verify_ref(
+    codeplug: &Codeplug,
+    chan_name: &str,
+    kind: PrivacyType,
+    idx: u8,
+) -> anyhow::Result<()> {
+    let no_keys = match kind {
+        PrivacyType::None => return Ok(()),
+        PrivacyType::Basic => codeplug.basic_keys.as_ref().map(|k| k.data.len()),
+        PrivacyType::Enhanced => codeplug.enhanced_keys.as_ref().map(|k| k.data.len()),
+    };
+
+    match no_keys {
+        Some(n) if idx as usize >= n => bail!(
+            "Channel '{}' references missing {:?} privacy key {}",
+            chan_name,
+            kind,
+            idx
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_serde;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn basic_key_serde(key in any::<super::BasicKey>()) {
+            check_serde(&key)?;
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn enhanced_key_serde(key in any::<super::EnhancedKey>()) {
+            check_serde(&key)?;
+        }
+    }
+
+    #[test]
+    fn keys_not_debug_printed() {
+        let key = super::BasicKey { key: [0xab; 5] };
+
+        assert_eq!(format!("{:?}", key), "BasicKey { key: \"<hidden>\" }");
+    }
+}
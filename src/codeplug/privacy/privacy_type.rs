@@ -0,0 +1,12 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+#[derive(Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive, Clone, Copy)]
+#[repr(u8)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub enum PrivacyType {
+    None = 0,
+    Basic = 1,
+    Enhanced = 2,
+}
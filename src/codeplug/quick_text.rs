@@ -0,0 +1,64 @@
+use super::{
+    cp_data::{CPData, LongString},
+    disp_tabular::DisplayTabular,
+    layout::{CPLayout, Field},
+    Codeplug,
+};
+use hyrps_derive::RawCPData;
+
+#[cfg(test)]
+use proptest::prelude::*;
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+/// Longest message body, in UTF-16 characters.
+pub const MAX_LEN: usize = 128;
+
+/// A preset text message which can be sent from the radio's menu.
+#[derive(Debug, PartialEq, Eq, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct QuickText {
+    #[cfg_attr(test, proptest(strategy = "\"[^\u{0}]{0,64}\".prop_map(LongString)"))]
+    pub body: LongString<MAX_LEN>,
+}
+
+impl QuickText {
+    pub fn new(body: &str) -> anyhow::Result<Self> {
+        Ok(QuickText {
+            body: LongString::new(body)?,
+        })
+    }
+}
+
+impl CPData for QuickText {
+    fn cp_section() -> u16 {
+        0x32
+    }
+}
+
+impl CPLayout for QuickText {
+    const LAYOUT: &'static [Field] = &[Field::utf16(0, MAX_LEN * 2, "body")];
+}
+
+impl DisplayTabular for QuickText {
+    fn get_heading() -> Vec<String> {
+        ["Message"].iter().map(|s| s.to_string()).collect()
+    }
+
+    fn get_row(&self, _codeplug: &Codeplug) -> Vec<String> {
+        vec![self.body.to_string()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_serde;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn quick_text_serde(qt in any::<super::QuickText>()) {
+            check_serde(&qt)?;
+        }
+    }
+}
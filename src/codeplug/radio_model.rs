@@ -0,0 +1,199 @@
+use super::{channel_common::power_level::PowerLevel, DeviceType};
+
+/// USB vendor ID shared by all Hytera radios in CPS mode.
+const HYTERA_VID: u16 = 0x238b;
+
+const VHF: (u32, u32) = (136_000_000, 174_000_000);
+const UHF: (u32, u32) = (350_000_000, 470_000_000);
+
+/// Static description of a radio model and what its codeplug can hold.
+#[derive(Debug)]
+pub struct RadioModel {
+    /// Name used when printing the model.
+    pub name: &'static str,
+    /// Lower-case prefixes of the model string stored at 0x3c of the
+    /// codeplug image which identify this model.
+    model_prefixes: &'static [&'static str],
+    /// (vendor ID, product ID, endpoint) tuples used in CPS mode.
+    pub usb_ids: &'static [(u16, u16, u8)],
+    /// Inclusive frequency ranges in Hz the radio can operate on.
+    pub bands: &'static [(u32, u32)],
+    pub max_channels: usize,
+    pub max_zones: usize,
+    pub max_contacts: usize,
+    /// Transmit power of each supported power level, in watts.
+    pub power_levels: &'static [(PowerLevel, f32)],
+    /// Maximum number of UTF-16 characters in a channel, zone or contact
+    /// name.
+    pub name_len: usize,
+    pub device_type: DeviceType,
+}
+
+/// Bands, channel and zone capacity and power output are taken from Hytera's
+/// specification sheet for each model. A model is sold in several single band
+/// variants and the image doesn't say which this is, so `bands` covers all of
+/// them. Where power output differs between bands the lower figure is given.
+/// The specification sheets don't give a contact limit, so contacts are only
+/// limited by the section capacity.
+pub static MODELS: &[RadioModel] = &[
+    RadioModel {
+        name: "PD785G",
+        model_prefixes: &["pd785"],
+        usb_ids: &[(HYTERA_VID, 0x0a11, 0x04)],
+        bands: &[VHF, UHF],
+        max_channels: 1024,
+        max_zones: 64,
+        max_contacts: usize::MAX,
+        power_levels: &[(PowerLevel::High, 4.0), (PowerLevel::Low, 1.0)],
+        name_len: 16,
+        device_type: DeviceType::Portable,
+    },
+    RadioModel {
+        name: "X1p",
+        model_prefixes: &["x1p"],
+        usb_ids: &[(HYTERA_VID, 0x0a12, 0x02)],
+        bands: &[VHF, UHF],
+        max_channels: 1024,
+        max_zones: 64,
+        max_contacts: usize::MAX,
+        power_levels: &[(PowerLevel::High, 4.0), (PowerLevel::Low, 1.0)],
+        name_len: 16,
+        device_type: DeviceType::Portable,
+    },
+    RadioModel {
+        name: "MD785",
+        model_prefixes: &["md785"],
+        usb_ids: &[(HYTERA_VID, 0x0a21, 0x04)],
+        bands: &[VHF, UHF],
+        max_channels: 1024,
+        max_zones: 64,
+        max_contacts: usize::MAX,
+        power_levels: &[(PowerLevel::High, 25.0), (PowerLevel::Low, 5.0)],
+        name_len: 16,
+        device_type: DeviceType::Mobile,
+    },
+];
+
+/// Fallback used for model strings which aren't in `MODELS`. Limits are left
+/// wide open so that only the section capacities constrain the codeplug.
+pub static GENERIC: RadioModel = RadioModel {
+    name: "Unknown",
+    model_prefixes: &[],
+    usb_ids: &[],
+    bands: &[(0, u32::MAX)],
+    max_channels: usize::MAX,
+    max_zones: usize::MAX,
+    max_contacts: usize::MAX,
+    power_levels: &[],
+    name_len: 16,
+    device_type: DeviceType::Portable,
+};
+
+impl RadioModel {
+    /// Find the model matching the model string read from a codeplug image.
+    pub fn lookup(model: &str) -> Option<&'static RadioModel> {
+        let model = model.trim_matches(char::from(0)).trim().to_lowercase();
+
+        MODELS
+            .iter()
+            .find(|m| m.model_prefixes.iter().any(|p| model.starts_with(p)))
+    }
+
+    /// USB IDs of every known model, for probing the bus in CPS mode.
+    pub fn all_usb_ids() -> Vec<(u16, u16, u8)> {
+        MODELS
+            .iter()
+            .flat_map(|m| m.usb_ids.iter().copied())
+            .collect()
+    }
+
+    pub fn in_band(&self, freq: u32) -> bool {
+        self.bands.iter().any(|b| (b.0..=b.1).contains(&freq))
+    }
+
+    /// Whether the model can transmit at `level`.
+    pub fn supports_power(&self, level: PowerLevel) -> bool {
+        self.power_levels.iter().any(|(l, _)| *l == level)
+    }
+
+    /// The strongest power level which transmits at no more than `watts`.
+    pub fn power_at_most(&self, watts: f32) -> Option<PowerLevel> {
+        self.power_levels
+            .iter()
+            .filter(|(_, w)| *w <= watts)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(l, _)| *l)
+    }
+
+    pub fn is_generic(&self) -> bool {
+        std::ptr::eq(self, &GENERIC)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RadioModel, GENERIC};
+    use crate::{
+        codeplug::{
+            analog_channel::{tone::Tone, AnalogChannel},
+            channel_common::power_level::PowerLevel,
+            DeviceType,
+        },
+        tests::empty_codeplug,
+    };
+
+    static LOW_POWER_ONLY: RadioModel = RadioModel {
+        name: "Low Power",
+        model_prefixes: &[],
+        usb_ids: &[],
+        bands: &[(0, u32::MAX)],
+        max_channels: usize::MAX,
+        max_zones: usize::MAX,
+        max_contacts: usize::MAX,
+        power_levels: &[(PowerLevel::Low, 1.0)],
+        name_len: 16,
+        device_type: DeviceType::Portable,
+    };
+
+    #[test]
+    fn lookup_known_models() {
+        assert_eq!(RadioModel::lookup("PD785G").unwrap().name, "PD785G");
+        assert_eq!(RadioModel::lookup("x1p\0\0\0").unwrap().name, "X1p");
+        assert_eq!(
+            RadioModel::lookup("md785").unwrap().device_type,
+            DeviceType::Mobile
+        );
+        assert!(RadioModel::lookup("bd505").is_none());
+    }
+
+    #[test]
+    fn power_at_most() {
+        let pd785 = RadioModel::lookup("PD785G").unwrap();
+        let md785 = RadioModel::lookup("MD785").unwrap();
+
+        assert_eq!(pd785.power_at_most(5.0), Some(PowerLevel::High));
+        assert_eq!(md785.power_at_most(5.0), Some(PowerLevel::Low));
+        assert_eq!(md785.power_at_most(1.0), None);
+        assert_eq!(GENERIC.power_at_most(5.0), None);
+    }
+
+    #[test]
+    fn unsupported_power_level() {
+        let mut cp = empty_codeplug(4, 4);
+        cp.profile = &LOW_POWER_ONLY;
+        cp.ana_chans.insert(AnalogChannel::new(
+            "Simplex".to_string(),
+            446_000_000,
+            446_000_000,
+            false,
+            PowerLevel::High,
+            Tone::None,
+            Tone::None,
+        ));
+
+        assert!(cp.verify().is_err());
+
+        cp.ana_chans.data[0].common.power_level = PowerLevel::Low;
+        cp.verify().unwrap();
+    }
+}
@@ -0,0 +1,238 @@
+use super::{
+    layout::{field_names, is_secret},
+    section::{Section, Sections, SECTION_TABLE_ADDR},
+    section_registry,
+};
+use itertools::Itertools;
+
+/// Where in a codeplug image a byte lives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    /// The image header before the section table.
+    Header,
+    SectionHeader {
+        section_type: u16,
+    },
+    Element {
+        section_type: u16,
+        /// Logical element index if the data chunk is mapped, along with
+        /// whether it is in use.
+        element: Option<(u16, bool)>,
+        chunk: usize,
+        offset: usize,
+        field: Option<String>,
+        /// The byte is key material and mustn't be shown.
+        secret: bool,
+    },
+    Mappings {
+        section_type: u16,
+    },
+    /// Not covered by the header or any section.
+    Unattributed,
+}
+
+/// A run of contiguous changed bytes sharing the same location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffRun {
+    pub addr: u64,
+    pub location: Location,
+    pub before: Vec<u8>,
+    pub after: Vec<u8>,
+}
+
+impl DiffRun {
+    fn extends(&self, addr: u64, location: &Location) -> bool {
+        if self.addr + self.before.len() as u64 != addr {
+            return false;
+        }
+
+        match (&self.location, location) {
+            (
+                Location::Element {
+                    section_type: t1,
+                    chunk: c1,
+                    field: f1,
+                    secret: s1,
+                    ..
+                },
+                Location::Element {
+                    section_type: t2,
+                    chunk: c2,
+                    field: f2,
+                    secret: s2,
+                    ..
+                },
+            ) => t1 == t2 && c1 == c2 && f1 == f2 && s1 == s2,
+            (l1, l2) => l1 == l2,
+        }
+    }
+}
+
+fn locate(sections: &[&Section], addr: u64, changed: u8) -> Location {
+    if addr < SECTION_TABLE_ADDR {
+        return Location::Header;
+    }
+
+    let sec = match sections
+        .iter()
+        .find(|s| (s.addr..s.end_addr()).contains(&addr))
+    {
+        Some(s) => s,
+        None => return Location::Unattributed,
+    };
+
+    let section_type = sec.header.section_type;
+
+    if addr < sec.data_addr() {
+        return Location::SectionHeader { section_type };
+    }
+
+    if addr >= sec.mappings_addr() {
+        return Location::Mappings { section_type };
+    }
+
+    let data_offset = (addr - sec.data_addr()) as usize;
+    let elm_sz = sec.get_element_sz();
+    let chunk = data_offset / elm_sz;
+    let offset = data_offset % elm_sz;
+
+    let element = sec
+        .rev_map(chunk as u16)
+        .map(|e| (e as u16, e < sec.header.elements_in_use as usize));
+
+    let decoder = section_registry::lookup(section_type).and_then(|i| i.decoder.as_ref());
+    let secret = matches!(decoder, Some(d) if is_secret(d.layout, offset));
+    let field = decoder.map(|d| {
+        format!(
+            "{}.{}",
+            d.name,
+            field_names(d.layout, offset, changed).unwrap_or_else(|| "<unknown>".to_string())
+        )
+    });
+
+    Location::Element {
+        section_type,
+        element,
+        chunk,
+        offset,
+        field,
+        secret,
+    }
+}
+
+/// Attribute every changed byte between `before` and `after` to a location
+/// within the `before` image, merging contiguous changes into runs.
+pub fn diff_images(sections: &Sections, before: &[u8], after: &[u8]) -> Vec<DiffRun> {
+    let sections = sections.values().sorted_by_key(|s| s.addr).collect::<Vec<_>>();
+    let mut runs: Vec<DiffRun> = vec![];
+
+    for (addr, (b, a)) in before.iter().zip(after.iter()).enumerate() {
+        if b == a {
+            continue;
+        }
+
+        let addr = addr as u64;
+        let location = locate(&sections, addr, a ^ b);
+
+        match runs.last_mut() {
+            Some(run) if run.extends(addr, &location) => {
+                run.before.push(*b);
+                run.after.push(*a);
+            }
+            _ => runs.push(DiffRun {
+                addr,
+                location,
+                before: vec![*b],
+                after: vec![*a],
+            }),
+        }
+    }
+
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_images, Location};
+    use crate::codeplug::section::{Section, SectionHeader, SectionMappings};
+    use std::io::{Cursor, Write};
+
+    /// An image with a section of two `elm_sz` byte elements, whose second
+    /// data chunk holds element 0.
+    fn image(section_type: u16, elm_sz: u32) -> Vec<u8> {
+        let mut img = vec![0u8; 0x38e];
+        let header = SectionHeader {
+            section_type,
+            capacity: 2,
+            unk1: 0,
+            elements_in_use: 1,
+            unk2: 0,
+            byte_size: 2 * elm_sz,
+        };
+        let end = 0x38e + 4 + 0x16 + 2 * elm_sz + 2 * 6;
+
+        img.extend_from_slice(&end.to_le_bytes());
+
+        let mut cursor = Cursor::new(&mut img);
+        cursor.set_position(0x392);
+        header.write(&mut cursor).unwrap();
+        cursor.write_all(&vec![0; 2 * elm_sz as usize]).unwrap();
+        SectionMappings {
+            idx: 1,
+            offset: elm_sz,
+        }
+        .write(&mut cursor)
+        .unwrap();
+        SectionMappings { idx: 0, offset: 0 }.write(&mut cursor).unwrap();
+
+        img
+    }
+
+    #[test]
+    fn attribute_changes() {
+        let before = image(0x2a, 0x30);
+        let mut after = before.clone();
+
+        after[0x10] = 1;
+        // Contact ID of the second data chunk, which is mapped to element 0.
+        after[0x392 + 0x16 + 0x30 + 40] = 0x23;
+        after[0x392 + 0x16 + 0x30 + 41] = 0x01;
+
+        let sections = Section::load_sections(&mut Cursor::new(&before)).unwrap();
+        let runs = diff_images(&sections, &before, &after);
+
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].location, Location::Header);
+        assert_eq!(
+            runs[1].location,
+            Location::Element {
+                section_type: 0x2a,
+                element: Some((0, true)),
+                chunk: 1,
+                offset: 40,
+                field: Some("Contact.id".to_string()),
+                secret: false,
+            }
+        );
+        assert_eq!(runs[1].after, vec![0x23, 0x01]);
+    }
+
+    #[test]
+    fn secret_changes() {
+        let before = image(0x31, 66);
+        let mut after = before.clone();
+
+        // Name and key of enhanced privacy key 0.
+        after[0x392 + 0x16 + 66] = 0x41;
+        after[0x392 + 0x16 + 66 + 40] = 0x5a;
+
+        let sections = Section::load_sections(&mut Cursor::new(&before)).unwrap();
+        let runs = diff_images(&sections, &before, &after);
+        let secret: Vec<_> = runs
+            .iter()
+            .map(|r| matches!(r.location, Location::Element { secret: true, .. }))
+            .collect();
+
+        assert_eq!(secret, [false, true]);
+    }
+}
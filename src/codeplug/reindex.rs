@@ -0,0 +1,238 @@
+use std::convert::TryFrom;
+
+use anyhow::bail;
+use itertools::Itertools;
+
+use super::{
+    channel_pointer::{digi_chan_pointer::DigiChannelPointer, pointer::ChannelPointer},
+    digital_channel::rrs_revert_ch::RrsRevertCh,
+    zone::b_idx,
+    Codeplug,
+};
+
+/// Where `ptr` points once `deleted` has been removed, or `None` if it
+/// pointed at the deleted channel.
+fn remap(ptr: ChannelPointer, deleted: ChannelPointer) -> Option<ChannelPointer> {
+    match (ptr, deleted) {
+        _ if ptr == deleted => None,
+        (ChannelPointer::Digital(i), ChannelPointer::Digital(d)) if i > d => {
+            Some(ChannelPointer::Digital(i - 1))
+        }
+        (ChannelPointer::Analog(i), ChannelPointer::Analog(d)) if i > d => {
+            Some(ChannelPointer::Analog(i - 1))
+        }
+        _ => Some(ptr),
+    }
+}
+
+fn remap_digi(ptr: DigiChannelPointer, deleted: ChannelPointer) -> Option<DigiChannelPointer> {
+    remap(ChannelPointer::from(&ptr), deleted)
+        .map(|p| DigiChannelPointer::try_from(&p).expect("remap changed the channel type"))
+}
+
+/// RRS revert channel indices count digital channels from one, zero being
+/// the channel itself.
+fn remap_rrs(ch: RrsRevertCh, deleted: ChannelPointer) -> RrsRevertCh {
+    match ch {
+        RrsRevertCh::Idx(i) => {
+            remap(ChannelPointer::Digital(i - 1), deleted).map_or(RrsRevertCh::None, |p| match p {
+                ChannelPointer::Digital(i) => RrsRevertCh::Idx(i + 1),
+                _ => unreachable!("remap changed the channel type"),
+            })
+        }
+        _ => ch,
+    }
+}
+
+impl Codeplug {
+    /// Find a channel by name.
+    pub fn find_channel(&self, name: &str) -> Result<ChannelPointer, anyhow::Error> {
+        let digital = self
+            .digi_chans
+            .data
+            .iter()
+            .positions(|c| c.common.name == name)
+            .map(|i| ChannelPointer::Digital(i as u16));
+        let analog = self
+            .ana_chans
+            .data
+            .iter()
+            .positions(|c| c.common.name == name)
+            .map(|i| ChannelPointer::Analog(i as u16));
+
+        match digital.chain(analog).collect::<Vec<_>>()[..] {
+            [p] => Ok(p),
+            [] => bail!("No channel named '{}'", name),
+            _ => bail!("Several channels are named '{}'", name),
+        }
+    }
+
+    /// Delete a channel, removing it from every zone, scan list and roam list
+    /// and moving references to later channels down to their new index.
+    /// Scan lists lose the channel as a priority channel.
+    /// Emergency systems which revert to the channel revert to the selected
+    /// channel instead and location and RRS revert channels are unset.
+    pub fn delete_channel(&mut self, chan: ChannelPointer) -> anyhow::Result<()> {
+        match chan {
+            ChannelPointer::Digital(i) if (i as usize) < self.digi_chans.data.len() => {
+                self.digi_chans.remove(i as usize);
+            }
+            ChannelPointer::Analog(i) if (i as usize) < self.ana_chans.data.len() => {
+                self.ana_chans.remove(i as usize);
+            }
+            _ => bail!("Can't delete {:?}", chan),
+        }
+
+        for (zone, list) in self
+            .zones
+            .data
+            .data
+            .iter_mut()
+            .zip(self.zones.channels.data.iter_mut())
+        {
+            let default_b = zone.default_channel_b(&list.channels);
+
+            list.channels = list
+                .channels
+                .iter()
+                .filter_map(|p| remap(*p, chan))
+                .collect();
+            if let Some(p) = zone.default_channel.get() {
+                zone.default_channel.set(remap(p, chan));
+            }
+            zone.default_b_idx = default_b.and_then(|p| remap(p, chan)).map_or(0, b_idx);
+
+            zone.set_channels(&list.channels);
+        }
+
+        for (scan, list) in self
+            .scan_list
+            .data
+            .data
+            .iter_mut()
+            .zip(self.scan_list.channels.data.iter_mut())
+        {
+            let priority = scan
+                .priority_channels(list)
+                .map(|p| p.and_then(|p| remap(p, chan)));
+
+            scan.designated_tx_channel =
+                remap(scan.designated_tx_channel, chan).unwrap_or(ChannelPointer::Selected);
+            list.channels = list
+                .channels
+                .iter()
+                .filter_map(|p| remap(*p, chan))
+                .collect();
+            scan.set_priority_channels(list, priority)?;
+        }
+
+        for list in self.roam_list.channels.data.iter_mut() {
+            list.channels = list
+                .channels
+                .iter()
+                .filter_map(|p| remap_digi(*p, chan))
+                .collect();
+        }
+
+        if let Some(emergency_systems) = &mut self.emergency_systems {
+            for es in emergency_systems.data.iter_mut() {
+                es.revert_channel =
+                    remap(es.revert_channel, chan).unwrap_or(ChannelPointer::Selected);
+            }
+        }
+
+        for dc in self.digi_chans.data.iter_mut() {
+            dc.loc_rev_channel = dc.loc_rev_channel.and_then(|p| remap_digi(p, chan));
+            dc.rrs_revert_ch = remap_rrs(dc.rrs_revert_ch, chan);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{remap, remap_rrs, ChannelPointer, RrsRevertCh};
+    use crate::{
+        codeplug::{
+            channel_common::power_level::PowerLevel,
+            digital_channel::{timeslot::Timeslot, DigitalChannel},
+            scan::Scan,
+            scan_list::ScanList,
+        },
+        tests::empty_codeplug,
+    };
+
+    #[test]
+    fn remap_pointers() {
+        let deleted = ChannelPointer::Digital(3);
+
+        assert_eq!(remap(ChannelPointer::Digital(3), deleted), None);
+        assert_eq!(
+            remap(ChannelPointer::Digital(5), deleted),
+            Some(ChannelPointer::Digital(4))
+        );
+        assert_eq!(
+            remap(ChannelPointer::Digital(2), deleted),
+            Some(ChannelPointer::Digital(2))
+        );
+        assert_eq!(
+            remap(ChannelPointer::Analog(5), deleted),
+            Some(ChannelPointer::Analog(5))
+        );
+        assert_eq!(
+            remap(ChannelPointer::Selected, deleted),
+            Some(ChannelPointer::Selected)
+        );
+    }
+
+    #[test]
+    fn remap_rrs_revert_channels() {
+        let deleted = ChannelPointer::Digital(3);
+
+        assert_eq!(remap_rrs(RrsRevertCh::Idx(4), deleted), RrsRevertCh::None);
+        assert_eq!(remap_rrs(RrsRevertCh::Idx(6), deleted), RrsRevertCh::Idx(5));
+        assert_eq!(remap_rrs(RrsRevertCh::Idx(3), deleted), RrsRevertCh::Idx(3));
+        assert_eq!(
+            remap_rrs(RrsRevertCh::Idx(6), ChannelPointer::Analog(3)),
+            RrsRevertCh::Idx(6)
+        );
+        assert_eq!(remap_rrs(RrsRevertCh::ChSelf, deleted), RrsRevertCh::ChSelf);
+        assert_eq!(remap_rrs(RrsRevertCh::None, deleted), RrsRevertCh::None);
+    }
+
+    #[test]
+    fn delete_priority_channel() {
+        let mut cp = empty_codeplug(8, 4);
+
+        for i in 0..3 {
+            cp.digi_chans.insert(DigitalChannel::new(
+                format!("D{}", i),
+                430_000_000,
+                439_000_000,
+                false,
+                PowerLevel::Low,
+                1,
+                0,
+                Timeslot::Slot1,
+            ));
+        }
+
+        let channels: Vec<_> = (0..3).map(ChannelPointer::Digital).collect();
+        cp.scan_list
+            .insert(Scan::new("Home".to_string()), ScanList::new(&channels));
+        cp.scan_list.data.data[0]
+            .set_priority_channels(
+                &cp.scan_list.channels.data[0],
+                [Some(channels[2]), Some(channels[0])],
+            )
+            .unwrap();
+
+        cp.delete_channel(channels[0]).unwrap();
+
+        let (scan, list) = (&cp.scan_list.data.data[0], &cp.scan_list.channels.data[0]);
+        assert_eq!(list.channels[1..], channels[..2]);
+        assert_eq!(scan.priority_channels(list), [Some(channels[1]), None]);
+        assert_eq!((scan.priority_channel_1, scan.priority_channel_2), (3, 0));
+    }
+}
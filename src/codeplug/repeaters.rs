@@ -0,0 +1,1153 @@
+//! DMR repeaters and the talkgroups carried on them, for `import-repeaters`.
+//!
+//! Repeaters are read from a CSV with frequencies in MHz, as the radio sees
+//! them, so TX is the repeater's input:
+//!
+//! ```text
+//! CALLSIGN,LOCATION,TX,RX,CC,NETWORK
+//! GB7LE,Leeds,430.6625,439.6625,2,M62 Corridor
+//! ```
+//!
+//! A position may be given in `LAT` and `LON` columns or as a Maidenhead
+//! `LOCATOR`, for building zones by distance.
+//!
+//! A talkgroup profile lists the talkgroups to make a channel for on every
+//! repeater. `ROAM` is optional, and puts the talkgroup's channels in a roam
+//! list shared by the repeaters on the same network, one for each talkgroup
+//! and timeslot:
+//!
+//! ```text
+//! ID,NAME,TIMESLOT,ROAM
+//! 235,UK Call,1,
+//! 862,M62 Roam,2,yes
+//! ```
+
+use std::{collections::BTreeMap, fmt, io::Read};
+
+use anyhow::{bail, Context, Result};
+use itertools::Itertools;
+
+use super::{
+    channel_common::power_level::PowerLevel,
+    channel_pointer::{digi_chan_pointer::DigiChannelPointer, pointer::ChannelPointer},
+    contact::{
+        call_type::CallType,
+        pointer::ContactPointer,
+        talkgroups::{Talkgroup, DEFAULT_NAME_FORMAT},
+        users_csv::column,
+        Contact,
+    },
+    cp_data::truncate_name,
+    digital_channel::{slrl_pointer::SLRLPointer, timeslot::Timeslot, DigitalChannel},
+    position::Position,
+    roam::{
+        list::{self, RoamList},
+        Roam,
+    },
+    scan::Scan,
+    scan_list::{self, ScanList},
+    zone_list, Codeplug, DeviceType,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repeater {
+    pub callsign: String,
+    pub location: String,
+    pub tx_freq: u32,
+    pub rx_freq: u32,
+    pub colour_code: u8,
+    /// Repeaters on the same network share roam lists.
+    pub network: String,
+    pub position: Option<Position>,
+}
+
+impl Repeater {
+    /// The callsign's suffix, e.g. `LE` for `GB7LE`, which keeps channel names
+    /// short.
+    pub fn suffix(&self) -> &str {
+        match self.callsign.rfind(|c: char| c.is_ascii_digit()) {
+            Some(i) if i + 1 < self.callsign.len() => &self.callsign[i + 1..],
+            _ => &self.callsign,
+        }
+    }
+
+    /// Name of the repeater's zone, scan list and RX group list.
+    pub fn zone_name(&self) -> String {
+        truncate_name(&format!("{} {}", self.callsign, self.location))
+    }
+
+    pub fn parse_csv(reader: impl Read) -> Result<Vec<Self>> {
+        let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+        let headers = csv.headers().context("Could not read CSV header")?.clone();
+
+        let (callsign, tx, rx, cc) = match (
+            column(&headers, &["CALLSIGN"]),
+            column(&headers, &["TX", "TX_FREQ"]),
+            column(&headers, &["RX", "RX_FREQ"]),
+            column(&headers, &["CC", "COLOUR_CODE", "COLOR_CODE"]),
+        ) {
+            (Some(callsign), Some(tx), Some(rx), Some(cc)) => (callsign, tx, rx, cc),
+            _ => bail!("CSV needs CALLSIGN, TX, RX and CC columns"),
+        };
+        let location = column(&headers, &["LOCATION", "CITY"]);
+        let network = column(&headers, &["NETWORK"]);
+        let lat = column(&headers, &["LAT", "LATITUDE"]);
+        let lon = column(&headers, &["LON", "LONG", "LONGITUDE"]);
+        let locator = column(&headers, &["LOCATOR", "GRID"]);
+
+        let mut repeaters = vec![];
+
+        for (n, record) in csv.records().enumerate() {
+            let line = || format!("Line {}", n + 2);
+            let record = record.with_context(line)?;
+            let field = |i: Option<usize>| {
+                i.and_then(|i| record.get(i))
+                    .unwrap_or("")
+                    .trim()
+                    .to_string()
+            };
+
+            let colour_code = field(Some(cc))
+                .parse()
+                .ok()
+                .filter(|cc| *cc < 16)
+                .with_context(|| format!("{}: Invalid colour code", line()))?;
+
+            let position = match (field(lat), field(lon), field(locator)) {
+                (lat, lon, _) if !lat.is_empty() || !lon.is_empty() => {
+                    Some(format!("{},{}", lat, lon).parse().with_context(line)?)
+                }
+                (_, _, locator) if !locator.is_empty() => {
+                    Some(Position::from_locator(&locator).with_context(line)?)
+                }
+                _ => None,
+            };
+
+            repeaters.push(Repeater {
+                callsign: field(Some(callsign)),
+                location: field(location),
+                tx_freq: parse_mhz(&field(Some(tx))).with_context(line)?,
+                rx_freq: parse_mhz(&field(Some(rx))).with_context(line)?,
+                colour_code,
+                network: field(network),
+                position,
+            });
+        }
+
+        Ok(repeaters)
+    }
+}
+
+/// Parse a frequency in MHz into Hz, without going through floating point.
+fn parse_mhz(s: &str) -> Result<u32> {
+    let (mhz, frac) = s.split_once('.').unwrap_or((s, ""));
+
+    if frac.len() > 6 || !frac.chars().all(|c| c.is_ascii_digit()) {
+        bail!("Invalid frequency: {}", s);
+    }
+
+    let hz = mhz
+        .parse::<u32>()
+        .ok()
+        .and_then(|m| m.checked_mul(1_000_000))
+        .and_then(|m| m.checked_add(format!("{:0<6}", frac).parse().ok()?));
+
+    match hz {
+        Some(hz) if hz > 0 => Ok(hz),
+        _ => bail!("Invalid frequency: {}", s),
+    }
+}
+
+/// A talkgroup to add a channel for on each repeater.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileTalkgroup {
+    pub talkgroup: Talkgroup,
+    pub timeslot: Timeslot,
+    /// Put the channel in its network's roam list.
+    pub roam: bool,
+}
+
+impl ProfileTalkgroup {
+    pub fn new(name: &str, id: u32, timeslot: Timeslot, roam: bool) -> Self {
+        ProfileTalkgroup {
+            talkgroup: Talkgroup {
+                id,
+                name: name.to_string(),
+            },
+            timeslot,
+            roam,
+        }
+    }
+
+    pub fn parse_csv(reader: impl Read) -> Result<Vec<Self>> {
+        let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+        let headers = csv.headers().context("Could not read CSV header")?.clone();
+
+        let (id, name, timeslot) = match (
+            column(&headers, &["ID", "TALKGROUP", "TG"]),
+            column(&headers, &["NAME"]),
+            column(&headers, &["TIMESLOT", "TS", "SLOT"]),
+        ) {
+            (Some(id), Some(name), Some(timeslot)) => (id, name, timeslot),
+            _ => bail!("CSV needs ID, NAME and TIMESLOT columns"),
+        };
+        let roam = column(&headers, &["ROAM"]);
+
+        let mut profile = vec![];
+
+        for (n, record) in csv.records().enumerate() {
+            let line = || format!("Line {}", n + 2);
+            let record = record.with_context(line)?;
+            let field = |i: Option<usize>| i.and_then(|i| record.get(i)).unwrap_or("").trim();
+
+            let id = field(Some(id))
+                .parse()
+                .with_context(|| format!("{}: Invalid talkgroup ID", line()))?;
+            let roam = matches!(
+                field(roam).to_ascii_lowercase().as_str(),
+                "1" | "y" | "yes" | "true"
+            );
+
+            profile.push(ProfileTalkgroup::new(
+                field(Some(name)),
+                id,
+                field(Some(timeslot)).parse().with_context(line)?,
+                roam,
+            ));
+        }
+
+        Ok(profile)
+    }
+}
+
+/// What `add_dmr_repeater` created.
+#[derive(Debug)]
+pub struct AddedRepeater {
+    pub zone_idx: usize,
+    pub channels: Vec<ChannelPointer>,
+    /// The channels of talkgroups marked to roam.
+    pub roam_channels: Vec<DigiChannelPointer>,
+}
+
+/// Zones of repeaters to build from their positions, besides the zone for
+/// each repeater.
+#[derive(Debug, Default)]
+pub struct LocationZones {
+    /// Where distances are measured from.
+    pub qth: Option<Position>,
+    /// Add a zone of the repeaters within this many kilometres of `qth`.
+    pub radius_km: Option<f64>,
+    /// Add a zone for each Maidenhead grid square with repeaters in it.
+    pub grid_squares: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct RepeaterReport {
+    pub repeaters: usize,
+    pub channels: usize,
+    pub contacts: usize,
+    pub roam_lists: usize,
+    pub location_zones: usize,
+    /// Repeaters left out of location zones for not having a position.
+    pub no_position: usize,
+    /// The furthest repeaters left out of full location zones, with the zone
+    /// they were left out of.
+    pub zone_overflow: Vec<(String, String)>,
+    /// Channels left out of full roam lists, which scan instead.
+    pub roam_overflow: usize,
+}
+
+impl fmt::Display for RepeaterReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Added {} repeaters with {} channels, {} new contacts, {} roam lists and {} location zones",
+            self.repeaters, self.channels, self.contacts, self.roam_lists, self.location_zones
+        )?;
+
+        if self.no_position > 0 {
+            write!(
+                f,
+                "\n{} repeaters have no position and aren't in location zones",
+                self.no_position
+            )?;
+        }
+
+        for (zone, callsign) in self.zone_overflow.iter() {
+            write!(f, "\n{} didn't fit in location zone '{}'", callsign, zone)?;
+        }
+
+        if self.roam_overflow > 0 {
+            write!(
+                f,
+                "\n{} channels didn't fit in roam lists",
+                self.roam_overflow
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What `generate_roam_list` created.
+#[derive(Debug)]
+pub struct GeneratedRoamList {
+    pub name: String,
+    pub channels: usize,
+    /// Matching channels left out because the roam list was full.
+    pub overflow: usize,
+    /// Channels which scanned a scan list before roaming on the new list.
+    pub lost_scan_lists: Vec<String>,
+}
+
+impl fmt::Display for GeneratedRoamList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Added roam list '{}' with {} channels",
+            self.name, self.channels
+        )?;
+
+        if self.overflow > 0 {
+            write!(
+                f,
+                "\n{} channels didn't fit in the roam list",
+                self.overflow
+            )?;
+        }
+
+        for chan in self.lost_scan_lists.iter() {
+            write!(f, "\n'{}' roams instead of scanning its scan list", chan)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The network, talkgroup and timeslot a roam list is for.
+type RoamKey<'a> = (&'a str, u32, Timeslot);
+
+/// The position, callsign and channels of a repeater.
+type Placed<'a> = (Position, &'a str, Vec<ChannelPointer>);
+
+fn slot_name(ts: Timeslot) -> &'static str {
+    match ts {
+        Timeslot::Slot1 => "S1",
+        Timeslot::Slot2 => "S2",
+        Timeslot::PseudoTrunk => "PS",
+    }
+}
+
+/// Most power, in watts, the generators use for repeater channels. Repeaters
+/// are usually close by, so mobiles don't need their full power.
+const REPEATER_WATTS: f32 = 5.0;
+
+impl Codeplug {
+    /// Power level for repeater channels, from the model's power levels where
+    /// it is known.
+    fn repeater_power_level(&self) -> PowerLevel {
+        match (
+            self.radio_model().power_at_most(REPEATER_WATTS),
+            self.radio_type(),
+        ) {
+            (Some(level), _) => level,
+            (None, DeviceType::Portable) => PowerLevel::High,
+            (None, DeviceType::Mobile) => PowerLevel::Low,
+        }
+    }
+
+    /// Index of the group call contact for `tg`, adding one if there isn't
+    /// one already.
+    pub fn create_or_add_contact(&mut self, tg: &Talkgroup) -> Result<usize> {
+        if let Some((i, _)) = self.contacts.data.iter().find_position(|x| x.id == tg.id) {
+            return Ok(i);
+        }
+
+        if self.contacts.free_space() == 0 {
+            bail!("No space for a contact for talkgroup {}", tg.id);
+        }
+
+        Ok(self.contacts.insert(Contact::new(
+            tg.contact_name(DEFAULT_NAME_FORMAT),
+            CallType::Group,
+            tg.id,
+        )))
+    }
+
+    /// Make the zone's channels scan `scan_list_idx` when selected.
+    pub fn set_zone_scanlist(&mut self, zone_idx: usize, scan_list_idx: usize) -> Result<()> {
+        for cptr in self.zones.channels.data[zone_idx].channels.clone() {
+            self.set_channel_scan_list(cptr, scan_list_idx)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add a digital channel on `repeater` for each talkgroup in `profile`,
+    /// with a zone, scan list and, if the codeplug has them, an RX group list
+    /// for the repeater.
+    pub fn add_dmr_repeater(
+        &mut self,
+        repeater: &Repeater,
+        profile: &[ProfileTalkgroup],
+    ) -> Result<AddedRepeater> {
+        let name = repeater.zone_name();
+
+        if profile.len() >= scan_list::MAX_CHANNELS {
+            bail!(
+                "Talkgroup profile has {} talkgroups, but a scan list holds {}",
+                profile.len(),
+                scan_list::MAX_CHANNELS - 1
+            );
+        }
+
+        let missing_contacts = profile
+            .iter()
+            .map(|p| p.talkgroup.id)
+            .unique()
+            .filter(|id| !self.contacts.data.iter().any(|c| c.id == *id))
+            .count();
+
+        if self.digi_chans.free_space() < profile.len()
+            || self.contacts.free_space() < missing_contacts
+            || self.zones.data.free_space() == 0
+            || self.scan_list.data.free_space() == 0
+            || self
+                .rx_groups
+                .as_ref()
+                .is_some_and(|g| g.data.free_space() == 0)
+        {
+            bail!(
+                "No space for the channels, contacts, zone and lists of '{}'",
+                name
+            );
+        }
+
+        let digi_power_level = self.repeater_power_level();
+
+        let mut rx_group_contacts = vec![];
+        let mut channels = vec![];
+        let mut roam_channels = vec![];
+
+        for ptg in profile {
+            let tg = &ptg.talkgroup;
+
+            // Name the slot when the talkgroup is on both.
+            let chan_name = if profile.iter().filter(|p| p.talkgroup.id == tg.id).count() > 1 {
+                format!(
+                    "{} {} {} {}",
+                    repeater.suffix(),
+                    tg.id,
+                    slot_name(ptg.timeslot),
+                    tg.name
+                )
+            } else {
+                format!("{} {} {}", repeater.suffix(), tg.id, tg.name)
+            };
+
+            let contact_idx = self.create_or_add_contact(tg)?;
+
+            if !rx_group_contacts.contains(&ContactPointer(contact_idx as u16)) {
+                rx_group_contacts.push(ContactPointer(contact_idx as u16));
+            }
+
+            let idx = self.digi_chans.insert(DigitalChannel::new(
+                truncate_name(&chan_name),
+                repeater.tx_freq,
+                repeater.rx_freq,
+                false,
+                digi_power_level,
+                repeater.colour_code,
+                contact_idx as u16,
+                ptg.timeslot,
+            ));
+
+            channels.push(ChannelPointer::Digital(idx as u16));
+
+            if ptg.roam {
+                roam_channels.push(DigiChannelPointer::Digital(idx as u16));
+            }
+        }
+
+        if self.rx_groups.is_some() {
+            let rx_group_idx = self.add_rx_group(name.clone(), &rx_group_contacts)?;
+
+            for cptr in channels.iter() {
+                if let ChannelPointer::Digital(idx) = cptr {
+                    self.digi_chans.data[*idx as usize].rx_group_list_idx = rx_group_idx as u16 + 1;
+                }
+            }
+        }
+
+        let scan = Scan::new(name.clone());
+        let scan_list = ScanList::new(&channels);
+
+        let scan_idx = self.scan_list.insert(scan, scan_list);
+
+        let zone_idx = self.add_zone(name, channels.clone())?;
+
+        self.set_zone_scanlist(zone_idx, scan_idx)?;
+
+        Ok(AddedRepeater {
+            zone_idx,
+            channels,
+            roam_channels,
+        })
+    }
+
+    /// Add a roam list of `channels`, and have them start roaming on it when
+    /// selected. Fails if there's already a roam list called `name`.
+    pub fn add_roam_list(&mut self, name: &str, channels: &[DigiChannelPointer]) -> Result<usize> {
+        let name = truncate_name(name);
+
+        if self.roam_list.data.data.iter().any(|r| r.name == name) {
+            bail!("There is already a roam list named '{}'", name);
+        }
+
+        if channels.len() >= list::MAX_CHANNELS {
+            bail!(
+                "Roam list '{}' has {} channels, but can hold {}",
+                name,
+                channels.len(),
+                list::MAX_CHANNELS - 1
+            );
+        }
+
+        if self.roam_list.data.free_space() == 0 {
+            bail!("No space for roam list '{}'", name);
+        }
+
+        let roam_idx = self
+            .roam_list
+            .insert(Roam::new(name), RoamList::new(channels));
+
+        for chan in channels {
+            if let DigiChannelPointer::Digital(di) = chan {
+                let dc = &mut self.digi_chans.data[*di as usize];
+                dc.ip_multi_site_connect = true;
+                dc.slrl_pointer = SLRLPointer::RoamList(roam_idx as u8);
+                dc.auto_start_roam = true;
+            }
+        }
+
+        Ok(roam_idx)
+    }
+
+    /// Add a roam list of the repeater channels which call group `talkgroup`
+    /// on `timeslot`, for as many as fit. It's named after the talkgroup's
+    /// contact unless `name` is given. Channels which scanned a scan list
+    /// roam instead, and are reported.
+    pub fn generate_roam_list(
+        &mut self,
+        talkgroup: u32,
+        timeslot: Timeslot,
+        name: Option<&str>,
+    ) -> Result<GeneratedRoamList> {
+        let mut contact_name = None;
+        let mut channels = vec![];
+
+        for (i, dc) in self.digi_chans.data.iter().enumerate() {
+            let contact = match dc.resolve_tx_contact(&self.contacts) {
+                Some(c) if c.call_type == CallType::Group && c.id == talkgroup => c,
+                _ => continue,
+            };
+
+            if dc.timeslot == timeslot && dc.common.tx_freq != dc.common.rx_freq {
+                contact_name.get_or_insert_with(|| contact.name.clone());
+                channels.push(DigiChannelPointer::Digital(i as u16));
+            }
+        }
+
+        let name = match (name, contact_name) {
+            (_, None) => bail!(
+                "No repeater channels call talkgroup {} on {:?}",
+                talkgroup,
+                timeslot
+            ),
+            (Some(name), _) => truncate_name(name),
+            (None, Some(contact_name)) => contact_name,
+        };
+
+        let overflow = channels.len().saturating_sub(list::MAX_CHANNELS - 1);
+        channels.truncate(list::MAX_CHANNELS - 1);
+
+        let lost_scan_lists = channels
+            .iter()
+            .filter_map(|c| match c {
+                DigiChannelPointer::Digital(i) => Some(&self.digi_chans.data[*i as usize]),
+                DigiChannelPointer::Selected => None,
+            })
+            .filter(|dc| matches!(dc.slrl_pointer, SLRLPointer::ScanList(_)))
+            .map(|dc| dc.common.name.clone())
+            .collect();
+
+        self.add_roam_list(&name, &channels)?;
+
+        Ok(GeneratedRoamList {
+            name,
+            channels: channels.len(),
+            overflow,
+            lost_scan_lists,
+        })
+    }
+
+    /// Add a zone of the channels of `repeaters`, nearest first, leaving out
+    /// the furthest repeaters if they don't all fit. Each repeater is given
+    /// with its distance, callsign and channels.
+    fn add_location_zone(
+        &mut self,
+        name: &str,
+        mut repeaters: Vec<(f64, &str, &[ChannelPointer])>,
+        report: &mut RepeaterReport,
+    ) -> Result<()> {
+        repeaters.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let name = truncate_name(name);
+        let mut channels: Vec<ChannelPointer> = vec![];
+        let mut full = false;
+
+        for (_, callsign, chans) in repeaters {
+            full |= channels.len() + chans.len() > zone_list::MAX_CHANNELS;
+
+            if full {
+                report
+                    .zone_overflow
+                    .push((name.clone(), callsign.to_string()));
+            } else {
+                channels.extend_from_slice(chans);
+            }
+        }
+
+        if channels.is_empty() {
+            return Ok(());
+        }
+
+        self.add_zone(name, channels)?;
+        report.location_zones += 1;
+
+        Ok(())
+    }
+
+    /// Add each of `repeaters` with `add_dmr_repeater`, then a roam list per
+    /// network, talkgroup and timeslot for the channels of talkgroups marked
+    /// to roam, and the zones asked for by `zones`. Roam lists are named after
+    /// the network, with the talkgroup added when the network has several.
+    pub fn import_repeaters(
+        &mut self,
+        repeaters: &[Repeater],
+        profile: &[ProfileTalkgroup],
+        zones: &LocationZones,
+    ) -> Result<RepeaterReport> {
+        let contacts = self.contacts.data.len();
+        let mut report = RepeaterReport::default();
+        let mut roam_lists: Vec<(RoamKey, Vec<DigiChannelPointer>)> = vec![];
+        let mut placed: Vec<Placed> = vec![];
+
+        if let Some(radius) = zones.radius_km {
+            if radius.is_nan() || radius < 0.0 {
+                bail!("Radius must be a positive number of kilometres");
+            }
+        }
+
+        for repeater in repeaters {
+            let added = self
+                .add_dmr_repeater(repeater, profile)
+                .with_context(|| format!("Could not add {}", repeater.callsign))?;
+
+            report.repeaters += 1;
+            report.channels += added.channels.len();
+
+            match repeater.position {
+                Some(pos) => placed.push((pos, &repeater.callsign, added.channels)),
+                None => report.no_position += 1,
+            }
+
+            if repeater.network.is_empty() {
+                continue;
+            }
+
+            let roaming = profile.iter().filter(|p| p.roam);
+
+            for (ptg, chan) in roaming.zip(added.roam_channels) {
+                let key = (&repeater.network[..], ptg.talkgroup.id, ptg.timeslot);
+
+                match roam_lists.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, chans)) => chans.push(chan),
+                    None => roam_lists.push((key, vec![chan])),
+                }
+            }
+        }
+
+        for ((network, tg, ts), chans) in roam_lists.iter() {
+            let on_network = roam_lists.iter().filter(|((n, _, _), _)| n == network);
+            let with_tg = on_network.clone().filter(|((_, t, _), _)| t == tg);
+
+            let name = match (on_network.count(), with_tg.count()) {
+                (1, _) => network.to_string(),
+                (_, 1) => format!("{} {}", network, tg),
+                _ => format!("{} {} {}", network, tg, slot_name(*ts)),
+            };
+
+            let kept = &chans[..chans.len().min(list::MAX_CHANNELS - 1)];
+            report.roam_overflow += chans.len() - kept.len();
+
+            self.add_roam_list(&name, kept)?;
+            report.roam_lists += 1;
+        }
+
+        if let (Some(qth), Some(radius)) = (zones.qth, zones.radius_km) {
+            let near = placed
+                .iter()
+                .map(|(pos, callsign, chans)| (qth.distance_km(pos), *callsign, &chans[..]))
+                .filter(|(d, _, _)| *d <= radius)
+                .collect();
+
+            self.add_location_zone(&format!("Within {}km", radius), near, &mut report)?;
+        }
+
+        if zones.grid_squares {
+            let mut squares: BTreeMap<String, Vec<&Placed>> = BTreeMap::new();
+
+            for repeater in placed.iter() {
+                squares
+                    .entry(repeater.0.grid_square())
+                    .or_default()
+                    .push(repeater);
+            }
+
+            for (square, repeaters) in squares {
+                // Without a QTH, sort from the middle of the square.
+                let from = match zones.qth {
+                    Some(qth) => qth,
+                    None => Position::from_locator(&square)?,
+                };
+                let repeaters = repeaters
+                    .into_iter()
+                    .map(|(pos, callsign, chans)| (from.distance_km(pos), *callsign, &chans[..]))
+                    .collect();
+
+                self.add_location_zone(&square, repeaters, &mut report)?;
+            }
+        }
+
+        report.contacts = self.contacts.data.len() - contacts;
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_mhz, LocationZones, ProfileTalkgroup, Repeater};
+    use crate::{
+        codeplug::{
+            channel_common::power_level::PowerLevel,
+            channel_pointer::{digi_chan_pointer::DigiChannelPointer, pointer::ChannelPointer},
+            contact::{call_type::CallType, pointer::ContactPointer, Contact},
+            digital_channel::{slrl_pointer::SLRLPointer, timeslot::Timeslot, DigitalChannel},
+            position::Position,
+            Codeplug,
+        },
+        tests::empty_codeplug,
+    };
+
+    fn add_channel(cp: &mut Codeplug, contact_idx: u16, ts: Timeslot, tx: u32) -> usize {
+        let name = format!("C{}", cp.digi_chans.data.len());
+
+        cp.digi_chans.insert(DigitalChannel::new(
+            name,
+            tx,
+            439_662_500,
+            false,
+            PowerLevel::Low,
+            1,
+            contact_idx,
+            ts,
+        ))
+    }
+
+    #[test]
+    fn parse_repeaters() {
+        let csv = "CALLSIGN,LOCATION,TX,RX,CC,NETWORK\n\
+            GB7LE,Leeds,430.6625,439.6625,2,M62 Corridor\n\
+            GB7MP,Heysham,430.75,439.75,3,\n";
+        let repeaters = Repeater::parse_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(repeaters.len(), 2);
+        assert_eq!(repeaters[0].tx_freq, 430_662_500);
+        assert_eq!(repeaters[1].rx_freq, 439_750_000);
+        assert_eq!(repeaters[0].network, "M62 Corridor");
+        assert_eq!(repeaters[0].suffix(), "LE");
+        assert_eq!(repeaters[0].zone_name(), "GB7LE Leeds");
+
+        let bad_cc = "CALLSIGN,TX,RX,CC\nGB7LE,430.6625,439.6625,16\n";
+        assert!(Repeater::parse_csv(bad_cc.as_bytes()).is_err());
+        assert!(Repeater::parse_csv("CALLSIGN,TX\n".as_bytes()).is_err());
+
+        let located = "CALLSIGN,TX,RX,CC,LAT,LON,LOCATOR\n\
+            GB7LE,430.6625,439.6625,2,53.7997,-1.5492,\n\
+            GB7TD,430.1625,439.1625,1,,,IO93gp\n\
+            GB7MP,430.75,439.75,3,,,\n";
+        let repeaters = Repeater::parse_csv(located.as_bytes()).unwrap();
+        let squares: Vec<_> = repeaters
+            .iter()
+            .map(|r| r.position.map(|p| p.grid_square()))
+            .collect();
+
+        assert_eq!(
+            squares,
+            [Some("IO93".to_string()), Some("IO93".to_string()), None]
+        );
+
+        let half = "CALLSIGN,TX,RX,CC,LAT,LON\nGB7LE,430.6625,439.6625,2,53.7997,\n";
+        assert!(Repeater::parse_csv(half.as_bytes()).is_err());
+
+        assert_eq!(parse_mhz("145").unwrap(), 145_000_000);
+        assert!(parse_mhz("430.66255").is_ok());
+        assert!(parse_mhz("430.6625001").is_err());
+        assert!(parse_mhz("4300.6625").is_err());
+        assert!(parse_mhz("-1.5").is_err());
+    }
+
+    #[test]
+    fn parse_profile() {
+        let csv = "ID,NAME,TIMESLOT,ROAM\n235,UK Call,1,\n9,Local,TS2,\n862,M62 Roam,2,yes\n";
+        let profile = ProfileTalkgroup::parse_csv(csv.as_bytes()).unwrap();
+
+        assert_eq!(
+            profile,
+            [
+                ProfileTalkgroup::new("UK Call", 235, Timeslot::Slot1, false),
+                ProfileTalkgroup::new("Local", 9, Timeslot::Slot2, false),
+                ProfileTalkgroup::new("M62 Roam", 862, Timeslot::Slot2, true),
+            ]
+        );
+        assert!(ProfileTalkgroup::parse_csv("ID,NAME,TIMESLOT\n9,Local,3\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn generate_roam_list() {
+        let mut cp = empty_codeplug(64, 4);
+        cp.contacts
+            .insert(Contact::new("M62".to_string(), CallType::Group, 862));
+        cp.contacts
+            .insert(Contact::new("Local".to_string(), CallType::Group, 9));
+
+        // Wrong timeslot, simplex and wrong talkgroup.
+        add_channel(&mut cp, 0, Timeslot::Slot1, 430_662_500);
+        add_channel(&mut cp, 0, Timeslot::Slot2, 439_662_500);
+        add_channel(&mut cp, 1, Timeslot::Slot2, 430_662_500);
+
+        for _ in 0..40 {
+            add_channel(&mut cp, 0, Timeslot::Slot2, 430_662_500);
+        }
+
+        cp.digi_chans.data[3].slrl_pointer = SLRLPointer::ScanList(0);
+
+        let generated = cp.generate_roam_list(862, Timeslot::Slot2, None).unwrap();
+
+        assert_eq!(generated.name, "M62");
+        assert_eq!(generated.channels, 31);
+        assert_eq!(generated.overflow, 9);
+        assert_eq!(generated.lost_scan_lists, ["C3"]);
+
+        let expected: Vec<_> = std::iter::once(DigiChannelPointer::Selected)
+            .chain((3..34).map(DigiChannelPointer::Digital))
+            .collect();
+        assert_eq!(cp.roam_list.channels.data[0].channels, expected);
+
+        for (i, dc) in cp.digi_chans.data.iter().enumerate() {
+            let roaming = (3..34).contains(&i);
+
+            assert_eq!(dc.slrl_pointer == SLRLPointer::RoamList(0), roaming);
+            assert_eq!(dc.auto_start_roam, roaming);
+        }
+
+        assert!(cp.generate_roam_list(862, Timeslot::Slot2, None).is_err());
+        assert!(cp
+            .generate_roam_list(862, Timeslot::Slot2, Some("M62 2"))
+            .is_ok());
+        assert!(cp.generate_roam_list(9, Timeslot::Slot1, None).is_err());
+        assert_eq!(cp.roam_list.data.data.len(), 2);
+    }
+
+    fn repeater(callsign: &str, network: &str) -> Repeater {
+        Repeater {
+            callsign: callsign.to_string(),
+            location: "Leeds".to_string(),
+            tx_freq: 430_662_500,
+            rx_freq: 439_662_500,
+            colour_code: 2,
+            network: network.to_string(),
+            position: None,
+        }
+    }
+
+    #[test]
+    fn add_dmr_repeater_without_contact_space() {
+        let mut cp = empty_codeplug(3, 4);
+        cp.contacts
+            .insert(Contact::new("WW".to_string(), CallType::Group, 1));
+        let profile = [
+            ProfileTalkgroup::new("WW", 1, Timeslot::Slot1, false),
+            ProfileTalkgroup::new("UK Call", 235, Timeslot::Slot1, false),
+            ProfileTalkgroup::new("Local", 9, Timeslot::Slot2, false),
+        ];
+
+        assert!(cp
+            .add_dmr_repeater(&repeater("GB7LE", ""), &profile)
+            .is_err());
+        assert!(cp.digi_chans.data.is_empty());
+        assert_eq!(cp.contacts.data.len(), 1);
+
+        assert!(cp
+            .add_dmr_repeater(&repeater("GB7LE", ""), &profile[..2])
+            .is_ok());
+        assert_eq!(cp.contacts.data.len(), 2);
+    }
+
+    #[test]
+    fn add_dmr_repeater() {
+        let mut cp = empty_codeplug(64, 8);
+        let profile = [
+            ProfileTalkgroup::new("UK Call", 235, Timeslot::Slot1, false),
+            ProfileTalkgroup::new("Local", 9, Timeslot::Slot1, false),
+            ProfileTalkgroup::new("Local", 9, Timeslot::Slot2, false),
+            ProfileTalkgroup::new("M62 Roam", 862, Timeslot::Slot2, true),
+        ];
+
+        let added = cp
+            .add_dmr_repeater(&repeater("GB7LE", "M62"), &profile)
+            .unwrap();
+        let channels: Vec<_> = (0..4).map(ChannelPointer::Digital).collect();
+
+        assert_eq!(added.channels, channels);
+        assert_eq!(added.roam_channels, [DigiChannelPointer::Digital(3)]);
+
+        let names: Vec<_> = cp.digi_chans.data.iter().map(|c| &c.common.name).collect();
+        assert_eq!(
+            names,
+            [
+                "LE 235 UK Call",
+                "LE 9 S1 Local",
+                "LE 9 S2 Local",
+                "LE 862 M62 Roam"
+            ]
+        );
+
+        let ids: Vec<_> = cp.contacts.data.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [235, 9, 862]);
+        assert!(cp
+            .contacts
+            .data
+            .iter()
+            .all(|c| c.call_type == CallType::Group));
+
+        for (dc, (ptg, contact)) in cp
+            .digi_chans
+            .data
+            .iter()
+            .zip(profile.iter().zip([1, 2, 2, 3]))
+        {
+            assert_eq!(dc.common.tx_freq, 430_662_500);
+            assert_eq!(dc.common.rx_freq, 439_662_500);
+            assert_eq!(dc.common.power_level, PowerLevel::High);
+            assert_eq!(dc.colour_code, 2);
+            assert_eq!(dc.timeslot, ptg.timeslot);
+            assert_eq!(dc.tx_contact_idx, contact);
+            assert_eq!(dc.rx_group_list_idx, 1);
+            assert_eq!(dc.slrl_pointer, SLRLPointer::ScanList(0));
+            assert!(dc.auto_start_scan);
+        }
+
+        let rx_groups = cp.rx_groups.as_ref().unwrap();
+        assert_eq!(rx_groups.data.data[0].name, "GB7LE Leeds");
+        assert_eq!(
+            rx_groups.channels.data[0].contacts,
+            [ContactPointer(0), ContactPointer(1), ContactPointer(2)]
+        );
+
+        assert_eq!(added.zone_idx, 0);
+        assert_eq!(cp.zones.data.data[0].name, "GB7LE Leeds");
+        assert_eq!(cp.zones.channels.data[0].channels, channels);
+
+        assert_eq!(cp.scan_list.data.data[0].name, "GB7LE Leeds");
+        assert_eq!(
+            cp.scan_list.channels.data[0].channels[0],
+            ChannelPointer::Selected
+        );
+        assert_eq!(cp.scan_list.channels.data[0].channels[1..], channels[..]);
+    }
+
+    #[test]
+    fn import_repeaters_roam_lists() {
+        let mut cp = empty_codeplug(64, 8);
+        let profile = [
+            ProfileTalkgroup::new("UK Call", 235, Timeslot::Slot1, false),
+            ProfileTalkgroup::new("Local", 9, Timeslot::Slot1, true),
+            ProfileTalkgroup::new("Local", 9, Timeslot::Slot2, true),
+            ProfileTalkgroup::new("M62 Roam", 862, Timeslot::Slot2, true),
+        ];
+        let repeaters = [
+            repeater("GB7LE", "M62"),
+            repeater("GB7MP", ""),
+            repeater("GB7TD", "M62"),
+        ];
+
+        let report = cp
+            .import_repeaters(&repeaters, &profile, &LocationZones::default())
+            .unwrap();
+
+        assert_eq!(report.repeaters, 3);
+        assert_eq!(report.channels, 12);
+        assert_eq!(report.contacts, 3);
+        assert_eq!(report.roam_lists, 3);
+        assert_eq!(report.roam_overflow, 0);
+
+        let names: Vec<_> = cp.roam_list.data.data.iter().map(|r| &r.name).collect();
+        assert_eq!(names, ["M62 9 S1", "M62 9 S2", "M62 862"]);
+
+        let roam_lists: Vec<Vec<_>> = cp
+            .roam_list
+            .channels
+            .data
+            .iter()
+            .map(|l| l.channels[1..].to_vec())
+            .collect();
+        let digital = DigiChannelPointer::Digital;
+        assert_eq!(
+            roam_lists,
+            [
+                [digital(1), digital(9)],
+                [digital(2), digital(10)],
+                [digital(3), digital(11)]
+            ]
+        );
+
+        for (i, dc) in cp.digi_chans.data.iter().enumerate() {
+            let slrl = match i {
+                1 | 9 => SLRLPointer::RoamList(0),
+                2 | 10 => SLRLPointer::RoamList(1),
+                3 | 11 => SLRLPointer::RoamList(2),
+                _ => SLRLPointer::ScanList((i / 4) as u8),
+            };
+
+            assert_eq!(dc.slrl_pointer, slrl, "channel {}", i);
+        }
+    }
+
+    #[test]
+    fn import_repeaters_roam_overflow() {
+        let mut cp = empty_codeplug(64, 40);
+        let profile = [ProfileTalkgroup::new(
+            "M62 Roam",
+            862,
+            Timeslot::Slot2,
+            true,
+        )];
+        let repeaters: Vec<_> = (0..35)
+            .map(|i| repeater(&format!("GB7{}", i), "M62"))
+            .collect();
+
+        let report = cp
+            .import_repeaters(&repeaters, &profile, &LocationZones::default())
+            .unwrap();
+
+        assert_eq!(report.roam_lists, 1);
+        assert_eq!(report.roam_overflow, 4);
+        assert_eq!(cp.roam_list.data.data[0].name, "M62");
+        assert_eq!(cp.roam_list.channels.data[0].channels.len(), 32);
+        assert_eq!(
+            cp.digi_chans.data[34].slrl_pointer,
+            SLRLPointer::ScanList(34)
+        );
+    }
+
+    /// `repeater` placed `km` kilometres north of `qth`.
+    fn placed(callsign: &str, qth: Position, km: f64) -> Repeater {
+        let mut repeater = repeater(callsign, "");
+        repeater.position = Some(Position::new(qth.lat + km / 111.2, qth.lon).unwrap());
+        repeater
+    }
+
+    #[test]
+    fn import_repeaters_location_zones() {
+        let mut cp = empty_codeplug(64, 8);
+        let qth = Position::new(53.8, -1.55).unwrap();
+        let profile = [ProfileTalkgroup::new("Local", 9, Timeslot::Slot2, false)];
+        let mut unplaced = repeater("GB7XX", "");
+        unplaced.position = None;
+        let repeaters = [
+            placed("GB7FAR", qth, 40.0),
+            placed("GB7MID", qth, 20.0),
+            unplaced,
+            placed("GB7NEAR", qth, 5.0),
+        ];
+        let zones = LocationZones {
+            qth: Some(qth),
+            radius_km: Some(30.0),
+            grid_squares: true,
+        };
+
+        let report = cp.import_repeaters(&repeaters, &profile, &zones).unwrap();
+
+        assert_eq!(report.location_zones, 3);
+        assert_eq!(report.no_position, 1);
+        assert!(report.zone_overflow.is_empty());
+        assert_eq!(cp.zones.data.data[4].name, "Within 30km");
+        assert_eq!(
+            cp.zones.channels.data[4].channels,
+            [ChannelPointer::Digital(3), ChannelPointer::Digital(1)]
+        );
+        assert_eq!(cp.zones.data.data[5].name, "IO93");
+        assert_eq!(
+            cp.zones.channels.data[5].channels,
+            [ChannelPointer::Digital(3), ChannelPointer::Digital(1)]
+        );
+        assert_eq!(cp.zones.data.data[6].name, "IO94");
+
+        for radius in [f64::NAN, -1.0] {
+            let zones = LocationZones {
+                qth: Some(qth),
+                radius_km: Some(radius),
+                grid_squares: false,
+            };
+
+            assert!(cp.import_repeaters(&repeaters, &profile, &zones).is_err());
+        }
+
+        assert_eq!(cp.digi_chans.data.len(), 4);
+    }
+
+    #[test]
+    fn import_repeaters_location_zone_overflow() {
+        let mut cp = empty_codeplug(560, 24);
+        let qth = Position::new(53.8, -1.55).unwrap();
+        let profile: Vec<_> = (1..=30)
+            .map(|id| ProfileTalkgroup::new("TG", id, Timeslot::Slot1, false))
+            .collect();
+        // Furthest first, so the zone has to be sorted.
+        let repeaters: Vec<_> = (0..18)
+            .map(|i| placed(&format!("GB7{}", i), qth, 18.0 - i as f64))
+            .collect();
+        let zones = LocationZones {
+            qth: Some(qth),
+            radius_km: Some(100.0),
+            grid_squares: false,
+        };
+
+        let report = cp.import_repeaters(&repeaters, &profile, &zones).unwrap();
+
+        assert_eq!(
+            report.zone_overflow,
+            [("Within 100km".to_string(), "GB70".to_string())]
+        );
+
+        let zone = &cp.zones.channels.data[18].channels;
+        let expected: Vec<_> = (1..18)
+            .rev()
+            .flat_map(|r| (r * 30..r * 30 + 30).map(ChannelPointer::Digital))
+            .collect();
+        assert_eq!(zone, &expected);
+    }
+}
@@ -8,6 +8,7 @@ use crate::codeplug::{
         section::ChannelPointerSection,
     },
     cp_data::{CPData, RawCPData},
+    layout::{channel_list_layout, CPLayout, Field},
     Codeplug,
 };
 
@@ -17,6 +18,9 @@ pub struct RoamList {
 
 const DATA_SZ: u32 = 0x40;
 
+/// Most channels a roam list holds, including the leading `<Selected>`.
+pub const MAX_CHANNELS: usize = DATA_SZ as usize / 2;
+
 impl RawCPData for RoamList {
     fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
         let cps = ChannelPointerSection::load(reader)
@@ -46,11 +50,19 @@ impl RoamList {
     }
 }
 
+impl CPLayout for RoamList {
+    const LAYOUT: &'static [Field] = &channel_list_layout(DATA_SZ as usize, 2);
+}
+
 impl CPData for RoamList {
     fn cp_section() -> u16 {
         0x79
     }
 
+    fn fixed_size() -> bool {
+        false
+    }
+
     fn verify(&self, codeplug: &Codeplug) -> anyhow::Result<()> {
         if self.channels[0] != DigiChannelPointer::Selected {
             bail!("First channel of roam list is not <Selected>");
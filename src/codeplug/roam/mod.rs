@@ -1,79 +1,34 @@
 use self::list::RoamList;
 use super::{
-    cp_data::{CPData, RawCPData},
+    cp_data::CPData,
     disp_tabular::DisplayTabular,
+    layout::{CPLayout, Field},
 };
 use crate::codeplug::channel_pointer::digi_chan_pointer::DigiChannelPointer;
-use byteorder::{ReadBytesExt, WriteBytesExt};
+use hyrps_derive::RawCPData;
 
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 
 pub mod list;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, RawCPData)]
 #[cfg_attr(test, derive(Arbitrary))]
+#[cp(magic(u8 = 0))]
 pub struct Roam {
     #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
     pub name: String,
     pub rssi_threshold: u8,
-    pub rssi_offset: u8,
-    pub interval_time: u8,
+    #[cp(magic([u8; 3] = [0; 3]), bitfield(u8), bits(0x02))]
     pub active_site_roam: bool,
+    #[cp(bits(0x04))]
     pub return_to_selected_ch: bool,
+    #[cp(bits(0x08))]
     pub follow_all_master_site_config: bool,
+    #[cp(bits(0x10))]
     pub stay: bool,
-}
-
-const PADDING: [u8; 3] = [0u8; 3];
-
-impl RawCPData for Roam {
-    fn load(reader: &mut impl std::io::Read) -> anyhow::Result<Self> {
-        let name = String::load(reader)?;
-        let rssi_threshold = reader.read_u8()?;
-
-        let mut buf = [0u8; 3];
-        reader.read_exact(&mut buf)?;
-        assert_eq!(buf, PADDING);
-
-        let flags = reader.read_u8()?;
-        let rssi_offset = reader.read_u8()?;
-        let interval_time = reader.read_u8()?;
-
-        assert_eq!(reader.read_u8()?, 0);
-
-        Ok(Roam {
-            name,
-            rssi_threshold,
-            rssi_offset,
-            interval_time,
-            active_site_roam: flags & 0x2 != 0,
-            return_to_selected_ch: flags & 0x4 != 0,
-            follow_all_master_site_config: flags & 0x8 != 0,
-            stay: flags & 0x10 != 0,
-        })
-    }
-
-    fn store(&self, writer: &mut impl std::io::Write) -> anyhow::Result<()> {
-        self.name.store(writer)?;
-        writer.write_u8(self.rssi_threshold)?;
-
-        writer.write_all(&PADDING)?;
-
-        let flags = (self.active_site_roam as u8) << 1
-            | (self.return_to_selected_ch as u8) << 2
-            | (self.follow_all_master_site_config as u8) << 3
-            | (self.stay as u8) << 4;
-
-        writer.write_u8(flags)?;
-
-        writer.write_u8(self.rssi_offset)?;
-        writer.write_u8(self.interval_time)?;
-
-        writer.write_u8(0)?;
-
-        Ok(())
-    }
+    pub rssi_offset: u8,
+    pub interval_time: u8,
 }
 
 impl Roam {
@@ -97,6 +52,22 @@ impl CPData for Roam {
     }
 }
 
+impl CPLayout for Roam {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "name"),
+        Field::new(32, 1, "rssi_threshold"),
+        Field::unknown(33, 3, "padding"),
+        Field::bits(36, 0x02, "active_site_roam"),
+        Field::bits(36, 0x04, "return_to_selected_ch"),
+        Field::bits(36, 0x08, "follow_all_master_site_config"),
+        Field::bits(36, 0x10, "stay"),
+        Field::unknown_bits(36, 0xe1, "unk_flags"),
+        Field::new(37, 1, "rssi_offset"),
+        Field::new(38, 1, "interval_time"),
+        Field::unknown(39, 1, "unk1"),
+    ];
+}
+
 impl DisplayTabular for (&Roam, &RoamList) {
     fn get_heading() -> Vec<String> {
         [
@@ -0,0 +1,61 @@
+use std::io::Read;
+
+use anyhow::Context;
+
+use crate::codeplug::{
+    channel_pointer::{raw_pointer::RawPointer, section::ChannelPointerSection},
+    contact::pointer::ContactPointer,
+    cp_data::{CPData, RawCPData},
+    layout::{channel_list_layout, CPLayout, Field},
+    Codeplug,
+};
+
+/// Contacts a channel using an RX group list listens to.
+pub struct RxGroupList {
+    pub contacts: Vec<ContactPointer>,
+}
+
+const DATA_SZ: u32 = 0x40;
+
+impl RawCPData for RxGroupList {
+    fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
+        let cps = ChannelPointerSection::load(reader)
+            .context("Could not load channel pointer section")?;
+
+        let contacts = cps.deduce_channels().context("Could not deduce contacts")?;
+
+        Ok(RxGroupList { contacts })
+    }
+
+    fn store(&self, writer: &mut impl std::io::Write) -> anyhow::Result<()> {
+        let cps = ChannelPointerSection::from_channels(&self.contacts, DATA_SZ)?;
+
+        cps.store(writer)
+    }
+}
+
+impl RxGroupList {
+    pub fn new(contacts: &[ContactPointer]) -> Self {
+        Self {
+            contacts: contacts.to_vec(),
+        }
+    }
+}
+
+impl CPLayout for RxGroupList {
+    const LAYOUT: &'static [Field] = &channel_list_layout(DATA_SZ as usize, 2);
+}
+
+impl CPData for RxGroupList {
+    fn cp_section() -> u16 {
+        0x2b
+    }
+
+    fn fixed_size() -> bool {
+        false
+    }
+
+    fn verify(&self, codeplug: &Codeplug) -> anyhow::Result<()> {
+        self.contacts.iter().try_for_each(|x| x.verify(codeplug))
+    }
+}
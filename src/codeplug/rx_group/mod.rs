@@ -0,0 +1,77 @@
+use self::list::RxGroupList;
+use super::{
+    cp_data::CPData,
+    disp_tabular::DisplayTabular,
+    layout::{CPLayout, Field},
+};
+use hyrps_derive::RawCPData;
+
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+pub mod list;
+
+#[derive(Debug, PartialEq, Eq, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct RxGroup {
+    #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
+    pub name: String,
+    pub no_contacts: u16,
+    unk1: [u8; 6],
+}
+
+impl RxGroup {
+    pub fn new(name: String, contacts: &RxGroupList) -> Self {
+        RxGroup {
+            name,
+            no_contacts: contacts.contacts.len() as u16,
+            unk1: [0; 6],
+        }
+    }
+}
+
+impl CPData for RxGroup {
+    fn cp_section() -> u16 {
+        0x2c
+    }
+}
+
+impl CPLayout for RxGroup {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "name"),
+        Field::new(32, 2, "no_contacts"),
+        Field::unknown(34, 6, "unk1"),
+    ];
+}
+
+impl DisplayTabular for (&RxGroup, &RxGroupList) {
+    fn get_heading() -> Vec<String> {
+        ["Name", "Contact(s)"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn get_row(&self, codeplug: &super::Codeplug) -> Vec<String> {
+        let contact_names: String = itertools::intersperse(
+            self.1.contacts.iter().map(|c| c.get_contact_name(codeplug)),
+            "\n".to_string(),
+        )
+        .collect();
+
+        vec![self.0.name.clone(), contact_names]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_serde;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn rx_group_serde(rx_group in any::<super::RxGroup>()) {
+            check_serde(&rx_group)?;
+        }
+    }
+}
@@ -17,7 +17,7 @@ impl TryFrom<u16> for CTCSSScanMode {
     type Error = Error;
 
     fn try_from(value: u16) -> Result<Self, Self::Error> {
-        match (value >> 2) & 3 {
+        match value & 3 {
             0 => Ok(CTCSSScanMode::Disabled),
             1 => Ok(CTCSSScanMode::NonPriorityChannel),
             2 => Ok(CTCSSScanMode::PriorityChannel),
@@ -26,3 +26,9 @@ impl TryFrom<u16> for CTCSSScanMode {
         }
     }
 }
+
+impl From<CTCSSScanMode> for u16 {
+    fn from(v: CTCSSScanMode) -> Self {
+        v as u16
+    }
+}
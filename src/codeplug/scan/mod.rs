@@ -1,63 +1,57 @@
 use super::{
     channel_pointer::pointer::ChannelPointer,
-    cp_data::{CPData, RawCPData},
+    cp_data::CPData,
     disp_tabular::DisplayTabular,
+    layout::{CPLayout, Field},
     scan_list::ScanList,
+    Codeplug,
 };
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::{convert::TryFrom, io::Read};
+use anyhow::bail;
+use hyrps_derive::RawCPData;
+use itertools::Itertools;
 
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 
 mod ctcss_scan_mode;
-mod scan_type;
+pub mod scan_type;
 mod tx_mode;
 
-#[derive(PartialEq, Eq, Debug)]
+/// Number of priority channels a scan list can have.
+pub const NUM_PRIORITY: usize = 2;
+
+#[derive(PartialEq, Eq, Debug, RawCPData)]
 #[cfg_attr(test, derive(Arbitrary))]
 pub struct Scan {
     #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
     pub name: String,
+    #[cp(bitfield(u16), bits(0x0003))]
     pub scan_type: scan_type::ScanType,
+    #[cp(bits(0x000c))]
     pub ctcss_mode: ctcss_scan_mode::CTCSSScanMode,
+    #[cp(bits(0x00c0))]
     pub tx_mode: tx_mode::ScanTxMode,
     pub designated_tx_channel: ChannelPointer,
-}
-
-impl RawCPData for Scan {
-    fn load(reader: &mut impl Read) -> anyhow::Result<Self> {
-        let name = String::load(reader)?;
-        let flags = reader.read_u16::<LittleEndian>()?;
-
-        let scan_type = scan_type::ScanType::try_from(flags)?;
-        let ctcss_mode = ctcss_scan_mode::CTCSSScanMode::try_from(flags)?;
-        let tx_mode = tx_mode::ScanTxMode::try_from(flags)?;
-
-        let designated_tx_channel = ChannelPointer::load(reader)?;
-
-        Ok(Scan {
-            name,
-            scan_type,
-            ctcss_mode,
-            tx_mode,
-            designated_tx_channel,
-        })
-    }
-
-    fn store(&self, writer: &mut impl std::io::Write) -> anyhow::Result<()> {
-        self.name.store(writer)?;
-
-        writer.write_u16::<LittleEndian>(
-            (self.scan_type as u16) | (self.ctcss_mode as u16) << 2 | (self.tx_mode as u16) << 6,
-        )?;
-
-        self.designated_tx_channel.store(writer)?;
-
-        writer.write_all(&[0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x05, 0x08, 0x6, 0x14, 0x06])?;
-
-        Ok(())
-    }
+    /// One based index into the scan list's channels, zero if unset.
+    pub priority_channel_1: u16,
+    /// How long scanning stays on an active channel, in units of 100ms.
+    pub hang_time: u8,
+    /// Not decoded yet, carried through as read.
+    pub unk1: u8,
+    /// One based index into the scan list's channels, zero if unset.
+    pub priority_channel_2: u16,
+    /// How often the priority channels are checked, in units of 100ms.
+    pub priority_sample_time: u8,
+    /// Weakest signal, in S-units, a vote scan will choose a channel on.
+    pub vote_rssi_threshold: u8,
+    /// How much stronger, in dB, a channel must be for a vote scan to move
+    /// to it.
+    pub vote_rssi_hysteresis: u8,
+    /// How long each channel is measured during a vote scan, in units of
+    /// 100ms.
+    pub vote_sample_time: u8,
+    /// How long a vote scan stays on its chosen channel, in seconds.
+    pub vote_hold_time: u8,
 }
 
 impl Scan {
@@ -68,14 +62,104 @@ impl Scan {
             ctcss_mode: ctcss_scan_mode::CTCSSScanMode::Disabled,
             tx_mode: tx_mode::ScanTxMode::Selected,
             designated_tx_channel: ChannelPointer::Digital(0),
+            priority_channel_1: 0,
+            hang_time: 5,
+            unk1: 0,
+            priority_channel_2: 0,
+            priority_sample_time: 5,
+            vote_rssi_threshold: 8,
+            vote_rssi_hysteresis: 6,
+            vote_sample_time: 20,
+            vote_hold_time: 6,
         }
     }
+
+    /// The priority channels, resolved against the scan list's channels.
+    pub fn priority_channels(&self, list: &ScanList) -> [Option<ChannelPointer>; NUM_PRIORITY] {
+        [self.priority_channel_1, self.priority_channel_2]
+            .map(|i| list.channels.get((i as usize).checked_sub(1)?).copied())
+    }
+
+    /// Set the priority channels, which must be on the scan list.
+    pub fn set_priority_channels(
+        &mut self,
+        list: &ScanList,
+        channels: [Option<ChannelPointer>; NUM_PRIORITY],
+    ) -> anyhow::Result<()> {
+        let [p1, p2] = channels.map(|c| match c {
+            Some(c) => match list.channels.iter().position(|l| *l == c) {
+                Some(i) => Ok(i as u16 + 1),
+                None => bail!(
+                    "Priority channel {:?} isn't on scan list '{}'",
+                    c,
+                    self.name
+                ),
+            },
+            None => Ok(0),
+        });
+
+        let (p1, p2) = (p1?, p2?);
+        self.priority_channel_1 = p1;
+        self.priority_channel_2 = p2;
+
+        Ok(())
+    }
+
+    /// Check the priority channels are on `list`.
+    pub fn verify_priority(&self, list: &ScanList) -> anyhow::Result<()> {
+        for i in [self.priority_channel_1, self.priority_channel_2] {
+            if i as usize > list.channels.len() {
+                bail!(
+                    "Scan list '{}' has priority channel {} but only {} channels",
+                    self.name,
+                    i,
+                    list.channels.len()
+                );
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl CPData for Scan {
     fn cp_section() -> u16 {
         0x6d
     }
+
+    fn verify(&self, _codeplug: &Codeplug) -> anyhow::Result<()> {
+        if self.scan_type == scan_type::ScanType::Vote
+            && (self.vote_rssi_threshold == 0 || self.vote_sample_time == 0)
+        {
+            bail!(
+                "Vote scan list '{}' needs an RSSI threshold and sample time",
+                self.name
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl CPLayout for Scan {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "name"),
+        Field::bits(32, 0x03, "scan_type"),
+        Field::bits(32, 0x0c, "ctcss_mode"),
+        Field::unknown_bits(32, 0x30, "unk_bits"),
+        Field::bits(32, 0xc0, "tx_mode"),
+        Field::unknown(33, 1, "unk_flags"),
+        Field::new(34, 4, "designated_tx_channel"),
+        Field::new(38, 2, "priority_channel_1"),
+        Field::new(40, 1, "hang_time"),
+        Field::unknown(41, 1, "unk1"),
+        Field::new(42, 2, "priority_channel_2"),
+        Field::new(44, 1, "priority_sample_time"),
+        Field::new(45, 1, "vote_rssi_threshold"),
+        Field::new(46, 1, "vote_rssi_hysteresis"),
+        Field::new(47, 1, "vote_sample_time"),
+        Field::new(48, 1, "vote_hold_time"),
+    ];
 }
 
 impl DisplayTabular for (&Scan, &ScanList) {
@@ -85,6 +169,9 @@ impl DisplayTabular for (&Scan, &ScanList) {
             "Scan Type",
             "CTCSS Scan Mode",
             "TX Mode",
+            "Priority",
+            "Hang (ms)",
+            "Vote",
             "Channel(S)",
         ]
         .iter()
@@ -92,22 +179,70 @@ impl DisplayTabular for (&Scan, &ScanList) {
         .collect()
     }
 
-    fn get_row(&self, codeplug: &super::Codeplug) -> Vec<String> {
+    fn get_row(&self, codeplug: &Codeplug) -> Vec<String> {
         assert_eq!(self.1.channels[0], ChannelPointer::Selected);
 
-        let chan_names: String = itertools::intersperse(self
-            .1
-            .channels
-            .iter()
-            .map(|m| m.get_chan_name(codeplug)), "\n".to_string())
-            .collect();
+        let chan_names: String = itertools::intersperse(
+            self.1.channels.iter().map(|m| m.get_chan_name(codeplug)),
+            "\n".to_string(),
+        )
+        .collect();
 
         vec![
             self.0.name.clone(),
             format!("{:?}", self.0.scan_type),
             format!("{:?}", self.0.ctcss_mode),
             format!("{:?}", self.0.tx_mode),
+            self.0
+                .priority_channels(self.1)
+                .iter()
+                .flatten()
+                .map(|c| c.get_chan_name(codeplug))
+                .join("\n"),
+            (self.0.hang_time as u32 * 100).to_string(),
+            match self.0.scan_type {
+                scan_type::ScanType::Vote => format!(
+                    "S{} +{}dB\n{}ms / {}s",
+                    self.0.vote_rssi_threshold,
+                    self.0.vote_rssi_hysteresis,
+                    self.0.vote_sample_time as u32 * 100,
+                    self.0.vote_hold_time
+                ),
+                _ => "-".to_string(),
+            },
             chan_names,
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ChannelPointer, Scan, ScanList};
+
+    #[test]
+    fn priority_channels() {
+        let (d0, d1, a0) = (
+            ChannelPointer::Digital(0),
+            ChannelPointer::Digital(1),
+            ChannelPointer::Analog(0),
+        );
+        let list = ScanList::new(&[d0, d1, a0]);
+        let mut scan = Scan::new("Home".to_string());
+
+        assert_eq!(scan.priority_channels(&list), [None, None]);
+
+        scan.set_priority_channels(&list, [Some(a0), Some(d0)])
+            .unwrap();
+        assert_eq!((scan.priority_channel_1, scan.priority_channel_2), (4, 2));
+        assert_eq!(scan.priority_channels(&list), [Some(a0), Some(d0)]);
+
+        let missing = Some(ChannelPointer::Digital(5));
+        assert!(scan.set_priority_channels(&list, [None, missing]).is_err());
+        assert_eq!(scan.priority_channels(&list), [Some(a0), Some(d0)]);
+
+        assert!(scan.verify_priority(&list).is_ok());
+        scan.priority_channel_2 = 5;
+        assert!(scan.verify_priority(&list).is_err());
+        assert_eq!(scan.priority_channels(&list), [Some(a0), None]);
+    }
+}
@@ -1,5 +1,6 @@
 use std::convert::TryFrom;
 use std::io::{Error, ErrorKind};
+use std::str::FromStr;
 
 #[cfg(test)]
 use proptest_derive::Arbitrary;
@@ -16,7 +17,7 @@ impl TryFrom<u16> for ScanType {
     type Error = Error;
 
     fn try_from(value: u16) -> Result<Self, Self::Error> {
-        match value & 3 {
+        match value {
             0 => Ok(ScanType::Normal),
             1 => Ok(ScanType::Vote),
             2 => Ok(ScanType::DigitalChannel),
@@ -24,3 +25,25 @@ impl TryFrom<u16> for ScanType {
         }
     }
 }
+
+impl From<ScanType> for u16 {
+    fn from(v: ScanType) -> Self {
+        v as u16
+    }
+}
+
+impl FromStr for ScanType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "normal" => ScanType::Normal,
+            "vote" => ScanType::Vote,
+            "digital" => ScanType::DigitalChannel,
+            _ => anyhow::bail!(
+                "Unknown scan type '{}', expected normal, vote or digital",
+                s
+            ),
+        })
+    }
+}
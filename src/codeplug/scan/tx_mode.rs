@@ -16,7 +16,7 @@ impl TryFrom<u16> for ScanTxMode {
     type Error = Error;
 
     fn try_from(value: u16) -> Result<Self, Self::Error> {
-        match (value >> 6) & 3 {
+        match value {
             0 => Ok(ScanTxMode::Selected),
             1 => Ok(ScanTxMode::LastActive),
             2 => Ok(ScanTxMode::Designated),
@@ -24,3 +24,9 @@ impl TryFrom<u16> for ScanTxMode {
         }
     }
 }
+
+impl From<ScanTxMode> for u16 {
+    fn from(v: ScanTxMode) -> Self {
+        v as u16
+    }
+}
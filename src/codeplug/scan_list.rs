@@ -6,6 +6,7 @@ use super::{
         {pointer::ChannelPointer, raw_pointer::RawPointer},
     },
     cp_data::{CPData, RawCPData},
+    layout::{channel_list_layout, CPLayout, Field},
 };
 
 use anyhow::bail;
@@ -16,6 +17,9 @@ pub struct ScanList {
 
 const DATA_SZ: u32 = 0x80;
 
+/// Most channels a scan list holds, including the leading `<Selected>`.
+pub const MAX_CHANNELS: usize = DATA_SZ as usize / 4;
+
 impl RawCPData for ScanList {
     fn load(reader: &mut impl Read) -> anyhow::Result<ScanList> {
         let cps = ChannelPointerSection::load(reader)?;
@@ -46,11 +50,19 @@ impl ScanList {
     }
 }
 
+impl CPLayout for ScanList {
+    const LAYOUT: &'static [Field] = &channel_list_layout(DATA_SZ as usize, 4);
+}
+
 impl CPData for ScanList {
     fn cp_section() -> u16 {
         0x4d
     }
 
+    fn fixed_size() -> bool {
+        false
+    }
+
     fn verify(&self, codeplug: &super::Codeplug) -> anyhow::Result<()> {
         if self.channels[0] != ChannelPointer::Selected {
             bail!("First channel of scan list is not <Selected>");
@@ -8,6 +8,10 @@ use std::{
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 
+/// Address of the pointer to the end of the section table. Everything before
+/// this is the image header.
+pub const SECTION_TABLE_ADDR: u64 = 0x38e;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(test, derive(Arbitrary))]
 pub struct SectionHeader {
@@ -58,6 +62,10 @@ impl SectionHeader {
         })
     }
 
+    pub const fn sz() -> u64 {
+        0x16
+    }
+
     pub fn write(&self, writer: &mut impl Write) -> Result<(), Error> {
         writer.write_u16::<LittleEndian>(self.section_type)?;
         let cap_field = self.capacity | (self.unk1 as u16) << 12;
@@ -73,6 +81,10 @@ impl SectionHeader {
 }
 
 impl SectionMappings {
+    pub const fn sz() -> u64 {
+        0x6
+    }
+
     pub fn load(reader: &mut impl Read) -> Result<Self, Error> {
         let idx = reader.read_u16::<LittleEndian>()?;
         let offset = reader.read_u32::<LittleEndian>()?;
@@ -110,6 +122,79 @@ impl Section {
         })
     }
 
+    /// Write the section back out exactly as it was loaded, including the
+    /// original mapping table.
+    pub fn write(&self, writer: &mut (impl Write + Seek)) -> Result<(), Error> {
+        writer.seek(SeekFrom::Start(self.addr))?;
+
+        self.header.write(writer)?;
+        writer.write_all(&self.data)?;
+
+        self.mappings.iter().try_for_each(|m| m.write(writer))
+    }
+
+    /// Replace the section payload with `elements`, in order, and reset the
+    /// mapping table to an identity mapping. As with `CodeplugSection`, a
+    /// section holds at most one element fewer than its capacity.
+    pub fn set_elements(&mut self, elements: &[Vec<u8>]) -> Result<(), Error> {
+        let elm_sz = self.get_element_sz();
+
+        if elements.len() >= self.header.capacity as usize {
+            return Err(Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "{} elements don't fit in a section of capacity {}, which holds at most {}",
+                    elements.len(),
+                    self.header.capacity,
+                    self.header.capacity.saturating_sub(1)
+                ),
+            ));
+        }
+
+        let mut data = Vec::with_capacity(self.header.byte_size as usize);
+
+        for (idx, elm) in elements.iter().enumerate() {
+            if elm.len() != elm_sz {
+                return Err(Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "Element {} is 0x{:X} bytes, expected 0x{:X}",
+                        idx,
+                        elm.len(),
+                        elm_sz
+                    ),
+                ));
+            }
+
+            data.extend_from_slice(elm);
+        }
+
+        data.resize(self.header.byte_size as usize, 0);
+
+        self.data = data;
+        self.header.elements_in_use = elements.len() as u16;
+        self.mappings = (0..self.header.capacity)
+            .map(|i| self.get_mapping(i))
+            .collect();
+
+        Ok(())
+    }
+
+    /// Offset of the section payload within the image.
+    pub fn data_addr(&self) -> u64 {
+        self.addr + SectionHeader::sz()
+    }
+
+    /// Offset of the mapping table within the image.
+    pub fn mappings_addr(&self) -> u64 {
+        self.data_addr() + self.header.byte_size as u64
+    }
+
+    /// Offset of the first byte after the section within the image.
+    pub fn end_addr(&self) -> u64 {
+        self.mappings_addr() + self.header.capacity as u64 * SectionMappings::sz()
+    }
+
     pub fn get_mapping(&self, idx: u16) -> SectionMappings {
         SectionMappings {
             idx,
@@ -137,9 +222,7 @@ impl Section {
     }
 
     pub fn load_sections(data: &mut (impl Read + Seek)) -> Result<Sections, Error> {
-        let addr = 0x38e;
-
-        data.seek(SeekFrom::Start(addr))?;
+        data.seek(SeekFrom::Start(SECTION_TABLE_ADDR))?;
 
         let end_addr = u64::from(data.read_u32::<LittleEndian>()?);
         let mut sections = HashMap::new();
@@ -171,7 +254,7 @@ impl Display for Section {
 mod tests {
     use super::SectionHeader;
     use proptest::prelude::*;
-    use std::io::Seek;
+    use std::io::{Seek, Write};
 
     proptest! {
         #[test]
@@ -204,4 +287,66 @@ mod tests {
             prop_assert_eq!(sm, sm2);
         }
     }
+
+    #[test]
+    fn section_raw_roundtrip() {
+        let header = SectionHeader {
+            section_type: 0x42,
+            capacity: 2,
+            unk1: 0,
+            elements_in_use: 1,
+            unk2: 0xdaaf12,
+            byte_size: 8,
+        };
+        let mut cursor = std::io::Cursor::new(Vec::new());
+
+        header.write(&mut cursor).unwrap();
+        cursor.write_all(&[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+        super::SectionMappings { idx: 1, offset: 4 }.write(&mut cursor).unwrap();
+        super::SectionMappings { idx: 0, offset: 0 }.write(&mut cursor).unwrap();
+
+        let orig = cursor.get_ref().clone();
+
+        cursor.rewind().unwrap();
+        let sec = super::Section::load(&mut cursor).unwrap();
+
+        let mut out = std::io::Cursor::new(Vec::new());
+        sec.write(&mut out).unwrap();
+
+        assert_eq!(out.into_inner(), orig);
+    }
+
+    #[test]
+    fn set_elements_capacity() {
+        let header = SectionHeader {
+            section_type: 0x42,
+            capacity: 3,
+            unk1: 0,
+            elements_in_use: 0,
+            unk2: 0,
+            byte_size: 12,
+        };
+        let mut cursor = std::io::Cursor::new(Vec::new());
+
+        header.write(&mut cursor).unwrap();
+        cursor.write_all(&[0; 12]).unwrap();
+        for i in 0..3 {
+            super::SectionMappings {
+                idx: i,
+                offset: i as u32 * 4,
+            }
+            .write(&mut cursor)
+            .unwrap();
+        }
+
+        cursor.rewind().unwrap();
+        let mut sec = super::Section::load(&mut cursor).unwrap();
+
+        assert!(sec
+            .set_elements(&[vec![1; 4], vec![2; 4], vec![3; 4]])
+            .is_err());
+        sec.set_elements(&[vec![1; 4], vec![2; 4]]).unwrap();
+        assert_eq!(sec.header.elements_in_use, 2);
+        assert_eq!(sec.data, [1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0]);
+    }
 }
@@ -0,0 +1,247 @@
+use super::{
+    analog_channel::AnalogChannel,
+    buttons::Buttons,
+    contact::{user_contact::UserContact, Contact},
+    digital_channel::DigitalChannel,
+    emergency_system::EmergencySystem,
+    layout::{CPLayout, Field},
+    phone_system::PhoneSystem,
+    privacy::{BasicKey, EnhancedKey},
+    quick_text::QuickText,
+    roam::{list::RoamList, Roam},
+    rx_group::{list::RxGroupList, RxGroup},
+    scan::Scan,
+    scan_list::ScanList,
+    section::Section,
+    settings::Settings,
+    signalling::{dtmf_system::DtmfSystem, two_tone::TwoToneSystem},
+    zone::Zone,
+    zone_list::ZoneList,
+};
+
+/// The type which decodes the elements of a section.
+#[derive(Debug)]
+pub struct Decoder {
+    pub name: &'static str,
+    pub layout: &'static [Field],
+}
+
+/// Describes a codeplug section type which has been identified.
+#[derive(Debug)]
+pub struct SectionInfo {
+    pub section_type: u16,
+    pub name: &'static str,
+    /// Set if hyrps is able to parse the section. Sections without a decoder
+    /// are kept as raw data.
+    pub decoder: Option<Decoder>,
+}
+
+/// Known section types. The IDs and element layouts of 0x02 and 0x2b to 0x35
+/// haven't been confirmed against a CPS capture yet. `Codeplug::read_codeplug`
+/// keeps any of them which fail to decode as raw data.
+pub static SECTIONS: &[SectionInfo] = &[
+    SectionInfo {
+        section_type: 0x02,
+        name: "General Settings",
+        decoder: Some(Decoder {
+            name: "Settings",
+            layout: Settings::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x23,
+        name: "Zone Channel Lists",
+        decoder: Some(Decoder {
+            name: "ZoneList",
+            layout: ZoneList::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x24,
+        name: "Zones",
+        decoder: Some(Decoder {
+            name: "Zone",
+            layout: Zone::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x26,
+        name: "Digital Channels",
+        decoder: Some(Decoder {
+            name: "DigitalChannel",
+            layout: DigitalChannel::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x27,
+        name: "Analog Channels",
+        decoder: Some(Decoder {
+            name: "AnalogChannel",
+            layout: AnalogChannel::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x2a,
+        name: "Contacts",
+        decoder: Some(Decoder {
+            name: "Contact",
+            layout: Contact::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x2b,
+        name: "RX Group Contact Lists",
+        decoder: Some(Decoder {
+            name: "RxGroupList",
+            layout: RxGroupList::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x2c,
+        name: "RX Group Lists",
+        decoder: Some(Decoder {
+            name: "RxGroup",
+            layout: RxGroup::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x2d,
+        name: "Emergency Systems",
+        decoder: Some(Decoder {
+            name: "EmergencySystem",
+            layout: EmergencySystem::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x2e,
+        name: "Phone Systems",
+        decoder: Some(Decoder {
+            name: "PhoneSystem",
+            layout: PhoneSystem::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x2f,
+        name: "Digital Contact List",
+        decoder: Some(Decoder {
+            name: "UserContact",
+            layout: UserContact::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x30,
+        name: "Basic Privacy Keys",
+        decoder: Some(Decoder {
+            name: "BasicKey",
+            layout: BasicKey::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x31,
+        name: "Enhanced Privacy Keys",
+        decoder: Some(Decoder {
+            name: "EnhancedKey",
+            layout: EnhancedKey::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x32,
+        name: "Quick Text Messages",
+        decoder: Some(Decoder {
+            name: "QuickText",
+            layout: QuickText::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x33,
+        name: "Buttons",
+        decoder: Some(Decoder {
+            name: "Buttons",
+            layout: Buttons::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x34,
+        name: "Two-Tone Systems",
+        decoder: Some(Decoder {
+            name: "TwoToneSystem",
+            layout: TwoToneSystem::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x35,
+        name: "DTMF Systems",
+        decoder: Some(Decoder {
+            name: "DtmfSystem",
+            layout: DtmfSystem::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x4d,
+        name: "Scan Channel Lists",
+        decoder: Some(Decoder {
+            name: "ScanList",
+            layout: ScanList::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x6d,
+        name: "Scan Lists",
+        decoder: Some(Decoder {
+            name: "Scan",
+            layout: Scan::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x79,
+        name: "Roam Channel Lists",
+        decoder: Some(Decoder {
+            name: "RoamList",
+            layout: RoamList::LAYOUT,
+        }),
+    },
+    SectionInfo {
+        section_type: 0x7a,
+        name: "Roam Lists",
+        decoder: Some(Decoder {
+            name: "Roam",
+            layout: Roam::LAYOUT,
+        }),
+    },
+];
+
+pub fn lookup(section_type: u16) -> Option<&'static SectionInfo> {
+    SECTIONS.iter().find(|s| s.section_type == section_type)
+}
+
+pub fn section_name(section_type: u16) -> &'static str {
+    lookup(section_type).map_or("<Unknown>", |s| s.name)
+}
+
+/// Returns true if the section is parsed into the `Codeplug` model rather than
+/// being passed through as raw data.
+pub fn is_decoded(section_type: u16) -> bool {
+    lookup(section_type).is_some_and(|s| s.decoder.is_some())
+}
+
+/// Format the number of elements in use against the section capacity.
+pub fn utilization(sec: &Section) -> String {
+    let cap = sec.header.capacity;
+    let used = sec.header.elements_in_use;
+
+    if cap == 0 {
+        return format!("{used}/{cap}");
+    }
+
+    format!("{used}/{cap} ({}%)", used as u32 * 100 / cap as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    #[test]
+    fn section_types_unique() {
+        assert!(super::SECTIONS.iter().map(|s| s.section_type).all_unique());
+    }
+}
@@ -0,0 +1,116 @@
+use super::{
+    cp_data::CPData,
+    disp_tabular::DisplayTabular,
+    layout::{CPLayout, Field},
+    Codeplug,
+};
+use anyhow::bail;
+use hyrps_derive::RawCPData;
+
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+/// Largest radio ID the radio will accept.
+pub const MAX_RADIO_ID: u32 = 16_776_415;
+
+/// General radio settings. The section holds a single element.
+#[derive(Debug, PartialEq, Eq, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct Settings {
+    /// Name shown to other radios which support talker alias.
+    #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
+    pub radio_alias: String,
+    pub radio_id: u32,
+    /// Text shown while the radio is powering on.
+    #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
+    pub intro_line_1: String,
+    #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
+    pub intro_line_2: String,
+    /// Not decoded yet, carried through as read.
+    #[cfg_attr(test, proptest(value = "[0; 80]"))]
+    pub trailer: [u8; 80],
+}
+
+impl CPData for Settings {
+    fn cp_section() -> u16 {
+        0x02
+    }
+
+    /// A radio ID of 0 is left unset, as in factory images.
+    fn verify(&self, _codeplug: &Codeplug) -> anyhow::Result<()> {
+        if self.radio_id > MAX_RADIO_ID {
+            bail!(
+                "Radio ID {} is outside of the range 1-{}",
+                self.radio_id,
+                MAX_RADIO_ID
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl CPLayout for Settings {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "radio_alias"),
+        Field::new(32, 4, "radio_id"),
+        Field::utf16(36, 32, "intro_line_1"),
+        Field::utf16(68, 32, "intro_line_2"),
+        Field::unknown(100, 80, "trailer"),
+    ];
+}
+
+impl DisplayTabular for Settings {
+    fn get_heading() -> Vec<String> {
+        ["Radio ID", "Radio Alias", "Intro Line 1", "Intro Line 2"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn get_row(&self, _codeplug: &Codeplug) -> Vec<String> {
+        vec![
+            self.radio_id.to_string(),
+            self.radio_alias.clone(),
+            self.intro_line_1.clone(),
+            self.intro_line_2.clone(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Settings, MAX_RADIO_ID};
+    use crate::{
+        codeplug::cp_data::CPData,
+        tests::{check_serde, empty_codeplug},
+    };
+    use proptest::prelude::*;
+
+    #[test]
+    fn verify_radio_id() {
+        let cp = empty_codeplug(4, 4);
+        let mut settings = Settings {
+            radio_alias: String::new(),
+            radio_id: 0,
+            intro_line_1: String::new(),
+            intro_line_2: String::new(),
+            trailer: [0; 80],
+        };
+
+        assert!(settings.verify(&cp).is_ok());
+
+        settings.radio_id = MAX_RADIO_ID;
+        assert!(settings.verify(&cp).is_ok());
+
+        settings.radio_id = MAX_RADIO_ID + 1;
+        assert!(settings.verify(&cp).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn settings_serde(settings in any::<super::Settings>()) {
+            check_serde(&settings)?;
+        }
+    }
+}
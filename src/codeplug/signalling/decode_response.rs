@@ -0,0 +1,14 @@
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+/// What the radio does when it decodes its own selective call.
+#[derive(Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive, Clone, Copy)]
+#[repr(u8)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub enum DecodeResponse {
+    None = 0,
+    Alert = 1,
+    Transpond = 2,
+    AlertAndTranspond = 3,
+}
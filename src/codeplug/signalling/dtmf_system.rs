@@ -0,0 +1,92 @@
+use super::decode_response::DecodeResponse;
+use crate::codeplug::{
+    cp_data::CPData,
+    disp_tabular::DisplayTabular,
+    dtmf,
+    layout::{CPLayout, Field},
+    Codeplug,
+};
+use anyhow::Context;
+use hyrps_derive::RawCPData;
+
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+pub const ID_LEN: usize = 8;
+
+/// DTMF selective calling. Timings are in units of 10ms.
+#[derive(Debug, PartialEq, Eq, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct DtmfSystem {
+    #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
+    pub name: String,
+    /// DTMF digits the radio answers to.
+    pub own_id: [u8; ID_LEN],
+    pub digit_duration: u8,
+    pub digit_interval: u8,
+    pub first_digit_delay: u8,
+    #[cp(repr(u8))]
+    pub decode_response: DecodeResponse,
+    /// Not decoded yet, carried through as read.
+    pub unk1: [u8; 4],
+}
+
+impl CPData for DtmfSystem {
+    fn cp_section() -> u16 {
+        0x35
+    }
+
+    fn verify(&self, _codeplug: &Codeplug) -> anyhow::Result<()> {
+        dtmf::verify(&self.own_id).with_context(|| format!("DTMF system '{}' ID", self.name))
+    }
+}
+
+impl CPLayout for DtmfSystem {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "name"),
+        Field::bytes(32, ID_LEN, "own_id"),
+        Field::new(40, 1, "digit_duration"),
+        Field::new(41, 1, "digit_interval"),
+        Field::new(42, 1, "first_digit_delay"),
+        Field::new(43, 1, "decode_response"),
+        Field::unknown(44, 4, "unk1"),
+    ];
+}
+
+impl DisplayTabular for DtmfSystem {
+    fn get_heading() -> Vec<String> {
+        [
+            "Name",
+            "ID",
+            "Digit Duration (ms)",
+            "Digit Interval (ms)",
+            "Decode Response",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    fn get_row(&self, _codeplug: &Codeplug) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            dtmf::to_string(&self.own_id),
+            (self.digit_duration as u32 * 10).to_string(),
+            (self.digit_interval as u32 * 10).to_string(),
+            format!("{:?}", self.decode_response),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_serde;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn dtmf_system_serde(ds in any::<super::DtmfSystem>()) {
+            check_serde(&ds)?;
+        }
+    }
+}
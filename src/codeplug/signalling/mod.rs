@@ -0,0 +1,122 @@
+use super::{analog_channel::signalling_type::SignallingType, Codeplug};
+use anyhow::{bail, Context};
+use std::convert::TryFrom;
+
+pub mod decode_response;
+pub mod dtmf_system;
+pub mod two_tone;
+
+/// The signalling system an analog channel uses for selective calling.
+/// Indices are zero based.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Signalling {
+    None,
+    TwoTone(usize),
+    Dtmf(usize),
+}
+
+impl Signalling {
+    /// Decode the type and one based index stored in a channel.
+    pub fn from_raw(kind: SignallingType, idx: u8) -> Self {
+        match (kind, idx) {
+            (SignallingType::None, _) | (_, 0) => Signalling::None,
+            (SignallingType::TwoTone, i) => Signalling::TwoTone(i as usize - 1),
+            (SignallingType::Dtmf, i) => Signalling::Dtmf(i as usize - 1),
+        }
+    }
+
+    /// Encode as the type and one based index stored in a channel.
+    pub fn to_raw(self) -> anyhow::Result<(SignallingType, u8)> {
+        let (kind, i) = match self {
+            Signalling::None => return Ok((SignallingType::None, 0)),
+            Signalling::TwoTone(i) => (SignallingType::TwoTone, i),
+            Signalling::Dtmf(i) => (SignallingType::Dtmf, i),
+        };
+
+        let idx = u8::try_from(i)
+            .ok()
+            .and_then(|i| i.checked_add(1))
+            .with_context(|| format!("Signalling system index {} is too large for a channel", i))?;
+
+        Ok((kind, idx))
+    }
+
+    /// Name of the referenced system, if it exists.
+    fn resolve_name<'a>(&self, codeplug: &'a Codeplug) -> Option<&'a str> {
+        match self {
+            Signalling::None => None,
+            Signalling::TwoTone(i) => codeplug
+                .two_tone_systems
+                .as_ref()?
+                .data
+                .get(*i)
+                .map(|s| s.name.as_str()),
+            Signalling::Dtmf(i) => codeplug
+                .dtmf_systems
+                .as_ref()?
+                .data
+                .get(*i)
+                .map(|s| s.name.as_str()),
+        }
+    }
+
+    /// Describe the signalling for tabular output.
+    pub fn describe(&self, codeplug: &Codeplug) -> String {
+        let kind = match self {
+            Signalling::None => return "<None>".to_string(),
+            Signalling::TwoTone(_) => "2-Tone",
+            Signalling::Dtmf(_) => "DTMF",
+        };
+
+        format!(
+            "{} {}",
+            kind,
+            self.resolve_name(codeplug).unwrap_or("<Invalid>")
+        )
+    }
+
+    /// Check the referenced system exists. References can't be checked when
+    /// the section isn't present.
+    pub fn verify(&self, codeplug: &Codeplug) -> anyhow::Result<()> {
+        let present = match self {
+            Signalling::None => return Ok(()),
+            Signalling::TwoTone(_) => codeplug.two_tone_systems.is_some(),
+            Signalling::Dtmf(_) => codeplug.dtmf_systems.is_some(),
+        };
+
+        if present && self.resolve_name(codeplug).is_none() {
+            bail!("Missing signalling system {:?}", self);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Signalling, SignallingType};
+
+    #[test]
+    fn raw_signalling() {
+        for s in [
+            Signalling::None,
+            Signalling::TwoTone(0),
+            Signalling::Dtmf(4),
+        ] {
+            let (kind, idx) = s.to_raw().unwrap();
+            assert_eq!(Signalling::from_raw(kind, idx), s);
+        }
+
+        assert_eq!(
+            Signalling::Dtmf(254).to_raw().unwrap(),
+            (SignallingType::Dtmf, 255)
+        );
+        assert!(Signalling::Dtmf(255).to_raw().is_err());
+        assert!(Signalling::TwoTone(usize::MAX).to_raw().is_err());
+
+        assert_eq!(
+            Signalling::from_raw(SignallingType::TwoTone, 0),
+            Signalling::None
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use super::decode_response::DecodeResponse;
+use crate::codeplug::{
+    cp_data::CPData,
+    disp_tabular::DisplayTabular,
+    layout::{CPLayout, Field},
+    Codeplug,
+};
+use hyrps_derive::RawCPData;
+
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+
+/// Sequential two-tone encode and decode. Tones are in tenths of a Hz and
+/// durations in milliseconds.
+#[derive(Debug, PartialEq, Eq, RawCPData)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub struct TwoToneSystem {
+    #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
+    pub name: String,
+    pub encode_tone_1: u16,
+    pub encode_tone_2: u16,
+    pub decode_tone_1: u16,
+    pub decode_tone_2: u16,
+    pub tone_1_duration: u16,
+    pub tone_2_duration: u16,
+    #[cp(repr(u8))]
+    pub decode_response: DecodeResponse,
+    /// Not decoded yet, carried through as read.
+    pub unk1: [u8; 3],
+}
+
+fn fmt_tones(t1: u16, t2: u16) -> String {
+    format!("{:.1} / {:.1}", t1 as f64 / 10.0, t2 as f64 / 10.0)
+}
+
+impl CPData for TwoToneSystem {
+    fn cp_section() -> u16 {
+        0x34
+    }
+}
+
+impl CPLayout for TwoToneSystem {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "name"),
+        Field::new(32, 2, "encode_tone_1"),
+        Field::new(34, 2, "encode_tone_2"),
+        Field::new(36, 2, "decode_tone_1"),
+        Field::new(38, 2, "decode_tone_2"),
+        Field::new(40, 2, "tone_1_duration"),
+        Field::new(42, 2, "tone_2_duration"),
+        Field::new(44, 1, "decode_response"),
+        Field::unknown(45, 3, "unk1"),
+    ];
+}
+
+impl DisplayTabular for TwoToneSystem {
+    fn get_heading() -> Vec<String> {
+        [
+            "Name",
+            "Encode (Hz)",
+            "Decode (Hz)",
+            "Durations (ms)",
+            "Decode Response",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    fn get_row(&self, _codeplug: &Codeplug) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            fmt_tones(self.encode_tone_1, self.encode_tone_2),
+            fmt_tones(self.decode_tone_1, self.decode_tone_2),
+            format!("{} / {}", self.tone_1_duration, self.tone_2_duration),
+            format!("{:?}", self.decode_response),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_serde;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn two_tone_system_serde(tt in any::<super::TwoToneSystem>()) {
+            check_serde(&tt)?;
+        }
+    }
+}
@@ -1,71 +1,144 @@
-use super::channel_pointer::pointer::ChannelPointer;
-use super::cp_data::{CPData, RawCPData};
+use super::channel_pointer::pointer::{ChannelPointer, OptionalPointer};
+use super::cp_data::CPData;
 use super::disp_tabular::DisplayTabular;
+use super::layout::{CPLayout, Field};
 use super::zone_list::ZoneList;
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
-use std::convert::TryInto;
-use std::io::{Cursor, Read};
+use anyhow::bail;
+use hyrps_derive::RawCPData;
 
 #[cfg(test)]
 use proptest_derive::Arbitrary;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, RawCPData)]
 #[cfg_attr(test, derive(Arbitrary))]
 pub struct Zone {
     #[cfg_attr(test, proptest(regex = "[^\u{0}]{0,8}"))]
     pub name: String,
     pub no_channels: u16,
-    pointer_data: [u8; 6],
+    /// Channel selected on the A side when switching to the zone, unset if
+    /// the zone is empty.
+    pub default_channel: OptionalPointer,
+    /// One based index of the channel selected on the B side, zero if unset.
+    /// Unlike the A side it's stored without the channel's type.
+    pub default_b_idx: u16,
+}
+
+/// How a channel is numbered in `Zone::default_b_idx`.
+pub fn b_idx(chan: ChannelPointer) -> u16 {
+    match chan {
+        ChannelPointer::Digital(i) | ChannelPointer::Analog(i) => i + 1,
+        ChannelPointer::Selected => 0,
+    }
 }
 
 impl Zone {
-    pub fn new(name: String, channels: &Vec<ChannelPointer>) -> Self {
-        let mut buf = vec![];
-        let mut cursor = Cursor::new(&mut buf);
+    pub fn new(name: String, channels: &[ChannelPointer]) -> Self {
+        let mut zone = Zone {
+            name,
+            no_channels: 0,
+            default_channel: OptionalPointer::unset(),
+            default_b_idx: 0,
+        };
+
+        zone.set_channels(channels);
 
-        channels[0].store(&mut cursor).unwrap();
-        channels[1].store(&mut cursor).unwrap();
+        zone
+    }
 
-        buf.resize(6, 0);
+    /// Update the channel count after the zone's channel list has changed.
+    /// The default channels are kept if they're still in the zone, otherwise
+    /// the first channel becomes the A default and the second the B default.
+    pub fn set_channels(&mut self, channels: &[ChannelPointer]) {
+        self.no_channels = channels.len() as u16;
 
-        Zone {
-            name,
-            no_channels: channels.len() as u16,
-            pointer_data: buf.try_into().unwrap(),
+        if !self
+            .default_channel
+            .get()
+            .is_some_and(|c| channels.contains(&c))
+        {
+            self.default_channel.set(channels.first().copied());
+        }
+
+        if self.default_channel_b(channels).is_none() {
+            self.default_b_idx = channels.get(1).map_or(0, |c| b_idx(*c));
         }
     }
-}
 
-impl RawCPData for Zone {
-    fn load(reader: &mut impl Read) -> anyhow::Result<Zone> {
-        let name = String::load(reader)?;
-        let no_channels = reader.read_u16::<LittleEndian>()?;
-        let mut pointer_data: [u8; 6] = [0; 6];
+    /// The channel of the zone selected on the B side, if any.
+    pub fn default_channel_b(&self, channels: &[ChannelPointer]) -> Option<ChannelPointer> {
+        match self.default_b_idx {
+            0 => None,
+            idx => channels.iter().copied().find(|c| b_idx(*c) == idx),
+        }
+    }
 
-        reader.read_exact(&mut pointer_data)?;
+    /// Set the default channel, which must be in the zone.
+    pub fn set_default_channel(
+        &mut self,
+        list: &ZoneList,
+        chan: ChannelPointer,
+    ) -> anyhow::Result<()> {
+        if !list.channels.contains(&chan) {
+            bail!("{:?} isn't in zone '{}'", chan, self.name);
+        }
 
-        Ok(Zone {
-            name,
-            no_channels,
-            pointer_data,
-        })
-    }
+        self.default_channel.set(Some(chan));
 
-    fn store(&self, writer: &mut impl std::io::Write) -> anyhow::Result<()> {
-        self.name.store(writer)?;
+        Ok(())
+    }
 
-        writer.write_u16::<LittleEndian>(self.no_channels)?;
+    /// Set the B side default channel, which must be in the zone and not
+    /// share its index with another channel of the zone.
+    pub fn set_default_channel_b(
+        &mut self,
+        list: &ZoneList,
+        chan: ChannelPointer,
+    ) -> anyhow::Result<()> {
+        if !list.channels.contains(&chan) {
+            bail!("{:?} isn't in zone '{}'", chan, self.name);
+        }
 
-        self.pointer_data
+        if let Some(other) = list
+            .channels
             .iter()
-            .try_for_each(|x| writer.write_u8(*x))?;
+            .find(|c| **c != chan && b_idx(**c) == b_idx(chan))
+        {
+            bail!(
+                "{:?} can't be told apart from {:?} as the B default of zone '{}'",
+                chan,
+                other,
+                self.name
+            );
+        }
+
+        self.default_b_idx = b_idx(chan);
 
         Ok(())
     }
+
+    /// Check the zone agrees with its channel list.
+    pub fn verify_channels(&self, list: &ZoneList) -> anyhow::Result<()> {
+        if self.no_channels as usize != list.channels.len() {
+            bail!(
+                "Zone '{}' should have {} channels but its list has {}",
+                self.name,
+                self.no_channels,
+                list.channels.len()
+            );
+        }
+
+        match self.default_channel.get() {
+            Some(c) if !list.channels.contains(&c) => {
+                bail!("Default channel of zone '{}' isn't in the zone", self.name)
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::ChannelPointer;
     use crate::tests::check_serde;
     use proptest::prelude::*;
 
@@ -75,6 +148,69 @@ mod tests {
             check_serde(&zone)?;
         }
     }
+
+    #[test]
+    fn small_zones() {
+        let empty = super::Zone::new("Empty".to_string(), &[]);
+        assert_eq!(empty.default_channel.get(), None);
+
+        let mut zone = super::Zone::new("One".to_string(), &[ChannelPointer::Analog(2)]);
+        assert_eq!(zone.default_channel.get(), Some(ChannelPointer::Analog(2)));
+        assert_eq!(zone.default_b_idx, 0);
+
+        let channels = [ChannelPointer::Digital(0), ChannelPointer::Analog(2)];
+        zone.set_channels(&channels);
+        assert_eq!(zone.default_channel.get(), Some(ChannelPointer::Analog(2)));
+        assert_eq!(
+            zone.default_channel_b(&channels),
+            Some(ChannelPointer::Analog(2))
+        );
+
+        zone.set_channels(&[]);
+        assert_eq!(zone.no_channels, 0);
+        assert_eq!(zone.default_channel.get(), None);
+        assert_eq!(zone.default_b_idx, 0);
+    }
+
+    #[test]
+    fn default_channels() {
+        let channels = [
+            ChannelPointer::Digital(4),
+            ChannelPointer::Analog(1),
+            ChannelPointer::Digital(0),
+        ];
+        let list = super::ZoneList {
+            channels: channels.to_vec(),
+        };
+        let mut zone = super::Zone::new("Three".to_string(), &channels);
+
+        // The baseline value: the second channel's index.
+        assert_eq!(zone.default_b_idx, 2);
+
+        zone.set_default_channel_b(&list, ChannelPointer::Digital(0))
+            .unwrap();
+        assert_eq!(zone.default_b_idx, 1);
+        assert_eq!(
+            zone.default_channel_b(&channels),
+            Some(ChannelPointer::Digital(0))
+        );
+        assert!(zone
+            .set_default_channel_b(&list, ChannelPointer::Analog(0))
+            .is_err());
+
+        let ambiguous = super::ZoneList {
+            channels: vec![ChannelPointer::Digital(0), ChannelPointer::Analog(0)],
+        };
+        assert!(zone
+            .set_default_channel_b(&ambiguous, ChannelPointer::Analog(0))
+            .is_err());
+
+        zone.set_channels(&channels[..2]);
+        assert_eq!(
+            zone.default_channel_b(&channels[..2]),
+            Some(ChannelPointer::Analog(1))
+        );
+    }
 }
 
 impl CPData for Zone {
@@ -83,9 +219,20 @@ impl CPData for Zone {
     }
 }
 
+impl CPLayout for Zone {
+    const LAYOUT: &'static [Field] = &[
+        Field::utf16(0, 32, "name"),
+        Field::new(32, 2, "no_channels"),
+        Field::new(34, 2, "default_channel.idx"),
+        Field::new(36, 1, "default_channel.target"),
+        Field::unknown(37, 1, "default_channel.flags"),
+        Field::new(38, 2, "default_b_idx"),
+    ];
+}
+
 impl DisplayTabular for (&Zone, &ZoneList) {
     fn get_heading() -> Vec<String> {
-        ["Name", "Channel(S)"]
+        ["Name", "Default A", "Default B", "Channel(S)"]
             .iter()
             .map(|s| s.to_string())
             .collect()
@@ -99,6 +246,17 @@ impl DisplayTabular for (&Zone, &ZoneList) {
             .map(|m| m.get_chan_name(codeplug)), "\n".to_string())
             .collect();
 
-        vec![self.0.name.clone(), chan_names]
+        vec![
+            self.0.name.clone(),
+            match self.0.default_channel.get() {
+                Some(c) => c.get_chan_name(codeplug),
+                None if self.0.default_channel.is_unknown() => "<Unknown>".to_string(),
+                None => "<None>".to_string(),
+            },
+            self.0
+                .default_channel_b(&self.1.channels)
+                .map_or("<None>".to_string(), |c| c.get_chan_name(codeplug)),
+            chan_names,
+        ]
     }
 }
@@ -5,12 +5,16 @@ use crate::codeplug::channel_pointer::section::ChannelPointerSection;
 use super::{
     channel_pointer::{pointer::ChannelPointer, raw_pointer::RawPointer},
     cp_data::{CPData, RawCPData},
+    layout::{channel_list_layout, CPLayout, Field},
 };
 
 use std::io::Read;
 
 const DATA_SZ: u32 = 0x800;
 
+/// Most channels a zone holds.
+pub const MAX_CHANNELS: usize = DATA_SZ as usize / 4;
+
 pub struct ZoneList {
     pub channels: Vec<ChannelPointer>,
 }
@@ -37,11 +41,19 @@ impl RawCPData for ZoneList {
     }
 }
 
+impl CPLayout for ZoneList {
+    const LAYOUT: &'static [Field] = &channel_list_layout(DATA_SZ as usize, 4);
+}
+
 impl CPData for ZoneList {
     fn cp_section() -> u16 {
         0x23
     }
 
+    fn fixed_size() -> bool {
+        false
+    }
+
     fn verify(&self, codeplug: &super::Codeplug) -> anyhow::Result<()> {
         self.channels.iter().try_for_each(|cp| cp.verify(codeplug))
     }
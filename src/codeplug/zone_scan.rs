@@ -0,0 +1,338 @@
+use std::{convert::TryFrom, fmt};
+
+use anyhow::{bail, Context, Result};
+
+use super::{
+    channel_pointer::pointer::ChannelPointer,
+    cp_data::{truncate_name, NAME_LEN},
+    digital_channel::slrl_pointer::SLRLPointer,
+    scan::Scan,
+    scan_list::{self, ScanList},
+    Codeplug,
+};
+
+/// What `generate_zone_scan_lists` did.
+#[derive(Debug, Default)]
+pub struct ZoneScanReport {
+    pub scan_lists: usize,
+    /// Zones too big for one scan list, with how many lists they were split
+    /// across.
+    pub split_zones: Vec<(String, usize)>,
+    /// Zones left without scan lists because the scan list section was full.
+    pub no_space: Vec<String>,
+    /// Digital channels left roaming rather than scanning.
+    pub roaming: usize,
+    /// Channels in several zones, which scan the list of the first one.
+    pub shared: usize,
+    /// Zones whose channels already scan a list named after the zone, which
+    /// were left alone.
+    pub existing: Vec<String>,
+}
+
+impl fmt::Display for ZoneScanReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Added {} scan lists", self.scan_lists)?;
+
+        for (zone, lists) in self.split_zones.iter() {
+            write!(f, "\nZone '{}' was split across {} scan lists", zone, lists)?;
+        }
+
+        for zone in self.no_space.iter() {
+            write!(f, "\nNo space for a scan list for zone '{}'", zone)?;
+        }
+
+        for zone in self.existing.iter() {
+            write!(f, "\nZone '{}' already has a scan list", zone)?;
+        }
+
+        if self.roaming > 0 {
+            write!(
+                f,
+                "\n{} channels are left on their roam lists",
+                self.roaming
+            )?;
+        }
+
+        if self.shared > 0 {
+            write!(
+                f,
+                "\n{} channels are in several zones and scan the first one's list",
+                self.shared
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Name of part `n` of `parts` scan lists for `zone`.
+fn part_name(zone: &str, n: usize, parts: usize) -> String {
+    if parts == 1 {
+        return zone.to_string();
+    }
+
+    let suffix = format!(" {}", n + 1);
+    let zone: String = zone.chars().take(NAME_LEN - suffix.len()).collect();
+
+    truncate_name(&format!("{}{}", zone.trim_end(), suffix))
+}
+
+impl Codeplug {
+    /// Name of the scan list `cptr` scans when selected, if any.
+    fn channel_scan_list(&self, cptr: ChannelPointer) -> Option<&str> {
+        let idx = match cptr {
+            ChannelPointer::Digital(i) => match self.digi_chans.data[i as usize].slrl_pointer {
+                SLRLPointer::ScanList(idx) => idx as usize,
+                _ => return None,
+            },
+            ChannelPointer::Analog(i) => {
+                (self.ana_chans.data[i as usize].scan_list_idx as usize).checked_sub(1)?
+            }
+            ChannelPointer::Selected => return None,
+        };
+
+        self.scan_list.data.data.get(idx).map(|s| s.name.as_str())
+    }
+
+    /// Make `cptr` scan `scan_list_idx` when selected.
+    pub fn set_channel_scan_list(
+        &mut self,
+        cptr: ChannelPointer,
+        scan_list_idx: usize,
+    ) -> Result<()> {
+        let idx = u8::try_from(scan_list_idx + 1)
+            .ok()
+            .context("Scan list index is too large for a channel to refer to")?;
+
+        match cptr {
+            ChannelPointer::Digital(i) => {
+                let dc = &mut self.digi_chans.data[i as usize];
+                dc.slrl_pointer = SLRLPointer::ScanList(idx - 1);
+                dc.auto_start_scan = true;
+            }
+            ChannelPointer::Analog(i) => {
+                let ac = &mut self.ana_chans.data[i as usize];
+                ac.scan_list_idx = idx;
+                ac.auto_start_scan = true;
+            }
+            ChannelPointer::Selected => bail!("Can't set scanlist of selected channel"),
+        }
+
+        Ok(())
+    }
+
+    /// Add a scan list for each zone and have the zone's channels scan it.
+    /// Zones which don't fit in one scan list are split across several.
+    /// Channels in several zones scan the first zone's list, and digital
+    /// channels on a roam list are left roaming. Zones whose channels
+    /// already scan a list named after the zone are skipped, so running this
+    /// again doesn't add more lists.
+    pub fn generate_zone_scan_lists(&mut self) -> Result<ZoneScanReport> {
+        let mut report = ZoneScanReport::default();
+        let mut seen = vec![];
+
+        let zones: Vec<(String, Vec<ChannelPointer>)> = self
+            .zones
+            .data
+            .data
+            .iter()
+            .zip(self.zones.channels.data.iter())
+            .map(|(zone, list)| {
+                let channels = list
+                    .channels
+                    .iter()
+                    .filter(|c| **c != ChannelPointer::Selected)
+                    .copied()
+                    .collect();
+
+                (zone.name.clone(), channels)
+            })
+            .collect();
+
+        for (name, channels) in zones {
+            let parts: Vec<&[ChannelPointer]> =
+                channels.chunks(scan_list::MAX_CHANNELS - 1).collect();
+
+            if parts.is_empty() {
+                continue;
+            }
+
+            let part_names: Vec<String> = (0..parts.len())
+                .map(|n| part_name(&name, n, parts.len()))
+                .collect();
+
+            let existing = channels.iter().any(|c| {
+                let scan_list = self.channel_scan_list(*c);
+                part_names.iter().any(|n| scan_list == Some(n.as_str()))
+            });
+
+            if existing {
+                seen.extend(channels);
+                report.existing.push(name);
+                continue;
+            }
+
+            if self.scan_list.data.free_space() < parts.len() {
+                report.no_space.push(name);
+                continue;
+            }
+
+            if parts.len() > 1 {
+                report.split_zones.push((name.clone(), parts.len()));
+            }
+
+            for (part, part_name) in parts.iter().zip(part_names) {
+                let scan = Scan::new(part_name);
+                let scan_idx = self.scan_list.insert(scan, ScanList::new(part));
+                report.scan_lists += 1;
+
+                for cptr in part.iter() {
+                    let roaming = match cptr {
+                        ChannelPointer::Digital(i) => matches!(
+                            self.digi_chans.data[*i as usize].slrl_pointer,
+                            SLRLPointer::RoamList(_)
+                        ),
+                        _ => false,
+                    };
+
+                    if seen.contains(cptr) {
+                        report.shared += 1;
+                        continue;
+                    }
+
+                    seen.push(*cptr);
+
+                    if roaming {
+                        report.roaming += 1;
+                    } else {
+                        self.set_channel_scan_list(*cptr, scan_idx)?;
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::part_name;
+    use crate::{
+        codeplug::{
+            analog_channel::{tone::Tone, AnalogChannel},
+            channel_common::power_level::PowerLevel,
+            channel_pointer::pointer::ChannelPointer,
+            digital_channel::{slrl_pointer::SLRLPointer, timeslot::Timeslot, DigitalChannel},
+            Codeplug,
+        },
+        tests::empty_codeplug,
+    };
+
+    /// A codeplug with `n` digital channels and one analog channel.
+    fn codeplug(n: u16, lists: u16) -> Codeplug {
+        let mut cp = empty_codeplug(40, lists);
+
+        for i in 0..n {
+            cp.digi_chans.insert(DigitalChannel::new(
+                format!("D{}", i),
+                430_000_000,
+                439_000_000,
+                false,
+                PowerLevel::Low,
+                1,
+                0,
+                Timeslot::Slot1,
+            ));
+        }
+
+        cp.ana_chans.insert(AnalogChannel::new(
+            "A0".to_string(),
+            145_500_000,
+            145_500_000,
+            false,
+            PowerLevel::Low,
+            Tone::None,
+            Tone::None,
+        ));
+
+        cp
+    }
+
+    fn digital(range: std::ops::Range<u16>) -> Vec<ChannelPointer> {
+        range.map(ChannelPointer::Digital).collect()
+    }
+
+    #[test]
+    fn generate_zone_scan_lists() {
+        let mut cp = codeplug(33, 8);
+        cp.digi_chans.data[1].slrl_pointer = SLRLPointer::RoamList(0);
+        cp.add_zone("Big".to_string(), digital(0..33)).unwrap();
+        cp.add_zone(
+            "Small".to_string(),
+            vec![ChannelPointer::Digital(0), ChannelPointer::Analog(0)],
+        )
+        .unwrap();
+
+        let report = cp.generate_zone_scan_lists().unwrap();
+        let names: Vec<&str> = cp
+            .scan_list
+            .data
+            .data
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["Big 1", "Big 2", "Small"]);
+        assert_eq!(report.scan_lists, 3);
+        assert_eq!(report.split_zones, [("Big".to_string(), 2)]);
+        assert_eq!(report.roaming, 1);
+        assert_eq!(report.shared, 1);
+        assert!(report.no_space.is_empty());
+
+        let lists = &cp.scan_list.channels.data;
+        assert_eq!(lists[0].channels.len(), 32);
+        assert_eq!(lists[0].channels[1..], digital(0..31)[..]);
+        assert_eq!(lists[1].channels[1..], digital(31..33)[..]);
+
+        let dc = &cp.digi_chans.data;
+        assert_eq!(dc[0].slrl_pointer, SLRLPointer::ScanList(0));
+        assert!(dc[0].auto_start_scan);
+        assert_eq!(dc[1].slrl_pointer, SLRLPointer::RoamList(0));
+        assert_eq!(dc[30].slrl_pointer, SLRLPointer::ScanList(0));
+        assert_eq!(dc[31].slrl_pointer, SLRLPointer::ScanList(1));
+        assert_eq!(cp.ana_chans.data[0].scan_list_idx, 3);
+        assert!(cp.ana_chans.data[0].auto_start_scan);
+
+        let report = cp.generate_zone_scan_lists().unwrap();
+
+        assert_eq!(report.scan_lists, 0);
+        assert_eq!(report.existing, ["Big", "Small"]);
+        assert_eq!(cp.scan_list.data.data.len(), 3);
+    }
+
+    #[test]
+    fn generate_zone_scan_lists_without_space() {
+        let mut cp = codeplug(33, 3);
+        cp.add_zone("Big".to_string(), digital(0..33)).unwrap();
+        cp.add_zone("Small".to_string(), digital(0..2)).unwrap();
+        cp.scan_list.data.sec.header.capacity = 2;
+
+        let report = cp.generate_zone_scan_lists().unwrap();
+
+        assert_eq!(report.no_space, ["Big"]);
+        assert_eq!(report.scan_lists, 1);
+        assert_eq!(cp.scan_list.data.data[0].name, "Small");
+        assert_eq!(cp.digi_chans.data[0].slrl_pointer, SLRLPointer::ScanList(0));
+        assert_eq!(cp.digi_chans.data[2].slrl_pointer, SLRLPointer::None);
+    }
+
+    #[test]
+    fn part_names() {
+        assert_eq!(part_name("Home", 0, 1), "Home");
+        assert_eq!(part_name("Home", 1, 3), "Home 2");
+        assert_eq!(part_name("GB7LE Leeds Long", 0, 2), "GB7LE Leeds Lo 1");
+        assert_eq!(part_name("GB7LE Leeds Long", 9, 12), "GB7LE Leeds L 10");
+        assert_eq!(part_name("Within 50km", 0, 2), "Within 50km 1");
+    }
+}
@@ -1,8 +1,27 @@
-use crate::codeplug::section::Section;
-use anyhow::{Context, Result};
+use crate::codeplug::{
+    analog_channel::{tone::Tone, AnalogChannel},
+    contact::{
+        talkgroups::{IdRange, TalkgroupFilter, Talkgroups, DEFAULT_NAME_FORMAT},
+        users_csv::{UserFilter, UsersCsv},
+    },
+    privacy::{keys_file::KeysFile, HIDDEN},
+    re_diff::{self, Location},
+    position::Position,
+    repeaters::{LocationZones, ProfileTalkgroup, Repeater},
+    section::Section,
+    channel_pointer::pointer::ChannelPointer,
+    digital_channel::{timeslot::Timeslot, DigitalChannel},
+    disp_tabular::DisplayTabular,
+    emergency_system::{alarm_type::AlarmType, EmergencySystem},
+    layout,
+    scan::scan_type::ScanType,
+    section_registry,
+    signalling::Signalling,
+};
+use anyhow::{bail, Context, Result};
 use clap::{Parser, Subcommand};
 use codeplug::Codeplug;
-use comfy_table::{presets::UTF8_BORDERS_ONLY, Table};
+use comfy_table::{presets::UTF8_BORDERS_ONLY, Cell, Color, Table};
 use cp_device::{get_sink, get_source};
 use indicatif::{ProgressBar, ProgressStyle};
 use itertools::Itertools;
@@ -11,8 +30,9 @@ use radio::{
     Radio, firmware_mode::FirmwareMode, CPS_MEM_MAX_SZ,
 };
 use std::{
+    convert::TryFrom,
     fs::File,
-    io::{Read, Write},
+    io::{Cursor, Read, Seek, Write},
 };
 
 mod codeplug;
@@ -75,6 +95,21 @@ enum Commands {
         codeplug_image: Option<std::path::PathBuf>,
     },
 
+    /// Export the analog or digital channels as CSV, with the same columns
+    /// as `print-codeplug`.
+    ExportChannels {
+        /// CSV file to write.
+        output_csv: std::path::PathBuf,
+
+        /// Export the digital channels rather than the analog channels.
+        #[clap(long)]
+        digital: bool,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+    },
+
     /// Diesect the codeplug, writing out earch section's data elements to a
     /// file.
     Disect {
@@ -86,6 +121,45 @@ enum Commands {
         codeplug_image: Option<std::path::PathBuf>,
     },
 
+    /// Rebuild a codeplug image from a directory written by `disect`, replacing
+    /// the elements of every section found in the directory.
+    Assemble {
+        /// Directory containing the (possibly edited) section elements.
+        input_directory: std::path::PathBuf,
+
+        /// Codeplug image used for all sections not present in the directory.
+        base_image: std::path::PathBuf,
+
+        /// Output file where the assembled codeplug image will be written.
+        output_image: std::path::PathBuf,
+    },
+
+    /// Compare two codeplug images, attributing every changed byte to a
+    /// section, element and, where known, a field.
+    ReDiff {
+        /// Codeplug image before the change.
+        before: std::path::PathBuf,
+
+        /// Codeplug image after the change.
+        after: std::path::PathBuf,
+    },
+
+    /// Print the raw bytes of a section element annotated with the fields they
+    /// decode to. Bytes whose meaning is unknown are highlighted.
+    Hexdump {
+        /// Section type, e.g. 0x26.
+        #[clap(long, parse(try_from_str = parse_u16))]
+        section: u16,
+
+        /// Element index within the section.
+        #[clap(long)]
+        element: u16,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+    },
+
     /// Verify a given codeplug, checking that various values aren't out of
     /// range and that no broken links exist.
     Verify {
@@ -95,10 +169,434 @@ enum Commands {
     },
 
     WriteCustomCodeplug {
+        /// Also replace the key assignments with the defaults for the type of
+        /// radio.
+        #[clap(long)]
+        default_buttons: bool,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Delete a channel, removing it from all zones, scan lists and roam lists
+    /// and updating references to the remaining channels.
+    DeleteChannel {
+        /// Name of the channel to delete.
+        name: String,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Add DMR repeaters from a CSV, with a channel for each talkgroup in a
+    /// profile and a zone and scan list per repeater. Talkgroups marked to
+    /// roam get a roam list per network.
+    ImportRepeaters {
+        /// Repeater CSV, with CALLSIGN, LOCATION, TX, RX, CC and NETWORK
+        /// columns. Frequencies are in MHz.
+        repeaters_csv: std::path::PathBuf,
+
+        /// Talkgroup profile CSV, with ID, NAME, TIMESLOT and optional ROAM
+        /// columns.
+        #[clap(long)]
+        talkgroups: std::path::PathBuf,
+
+        /// Where distances are measured from, as LAT,LON or a Maidenhead
+        /// locator.
+        #[clap(long)]
+        qth: Option<Position>,
+
+        /// Add a zone of the repeaters within this many kilometres of the QTH,
+        /// nearest first.
+        #[clap(long, requires = "qth")]
+        radius: Option<f64>,
+
+        /// Add a zone for each Maidenhead grid square with repeaters in it,
+        /// nearest first.
+        #[clap(long)]
+        grid_zones: bool,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Add a roam list of the repeater channels which call a talkgroup on a
+    /// timeslot, and have them roam on it. A roam list holds 31 channels.
+    GenerateRoamList {
+        /// Talkgroup ID.
+        #[clap(long)]
+        talkgroup: u32,
+
+        /// Timeslot, 1 or 2.
+        #[clap(long)]
+        timeslot: Timeslot,
+
+        /// Name of the roam list. Defaults to the talkgroup's contact name.
+        #[clap(long)]
+        name: Option<String>,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Add a scan list for every zone and have the zone's channels scan it.
+    /// Zones with more than 31 channels are split across several scan lists.
+    GenerateScanLists {
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Set the channel a zone starts on when it is selected.
+    SetZoneDefault {
+        /// Name of the zone.
+        zone: String,
+
+        /// Name of the channel, which must be in the zone.
+        channel: String,
+
+        /// Set the channel the B side starts on rather than the A side.
+        #[clap(long)]
+        b: bool,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Change the scan and vote parameters of a scan list. Parameters which
+    /// aren't given are left unchanged.
+    SetScanList {
+        /// Name of the scan list.
+        name: String,
+
+        #[clap(flatten)]
+        params: ScanListParams,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Set the CTCSS or DCS tones of an analog channel. Tones are given as
+    /// 88.5 for CTCSS, D023N or D023I for normal or inverted DCS, or none.
+    /// Setting an RX tone makes the squelch open on it.
+    SetAnalogTones {
+        /// Name of the analog channel.
+        channel: String,
+
+        /// Tone the squelch opens on.
+        #[clap(long)]
+        rx_tone: Option<Tone>,
+
+        /// Tone sent when transmitting.
+        #[clap(long)]
+        tx_tone: Option<Tone>,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Make an analog channel a selective call channel, which only opens its
+    /// squelch when the chosen two-tone or DTMF system decodes. Giving neither
+    /// system turns selective calling off.
+    SelectiveCall {
+        /// Name of the analog channel.
+        channel: String,
+
+        /// Two-tone system to use, numbered from 1.
+        #[clap(long, conflicts_with = "dtmf")]
+        two_tone: Option<usize>,
+
+        /// DTMF system to use, numbered from 1.
+        #[clap(long)]
+        dtmf: Option<usize>,
+
+        /// Also set the DTMF digits this radio answers to on the DTMF system.
+        #[clap(long, requires = "dtmf")]
+        dtmf_id: Option<String>,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Add an emergency system which alarms to a talkgroup, and have the
+    /// channels of a zone use it.
+    AddEmergencySystem {
+        /// Name of the emergency system.
+        name: String,
+
+        #[clap(flatten)]
+        params: EmergencySystemParams,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Replace the privacy keys with those read from a keys file. Key types
+    /// missing from the file are left unchanged.
+    ImportKeys {
+        /// Keys file. Each line is either `basic <hex key>` or
+        /// `enhanced <name> <key id> <arc4|aes128|aes256> <hex key>`.
+        keys_file: std::path::PathBuf,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Bulk import contacts.
+    Contacts {
+        #[clap(subcommand)]
+        action: ContactsAction,
+    },
+
+    /// List, add or remove quick text messages.
+    QuickText {
+        #[clap(subcommand)]
+        action: QuickTextAction,
+    },
+
+    /// Change the general radio settings. Settings which aren't given are left
+    /// unchanged.
+    SetSettings {
+        /// DMR radio ID.
+        #[clap(long)]
+        radio_id: Option<u32>,
+
+        /// Radio alias sent to other radios.
+        #[clap(long)]
+        alias: Option<String>,
+
+        /// First line of the power on text.
+        #[clap(long)]
+        intro_line_1: Option<String>,
+
+        /// Second line of the power on text.
+        #[clap(long)]
+        intro_line_2: Option<String>,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Debug, clap::Args)]
+struct EmergencySystemParams {
+    /// Alarm type: disable, regular, silent or silent-voice.
+    #[clap(long, default_value = "regular")]
+    alarm_type: AlarmType,
+
+    /// ID of the contact the alarm is sent to, which must already exist.
+    #[clap(long)]
+    talkgroup: u32,
+
+    /// Channel switched to when the alarm is raised. Defaults to staying on
+    /// the current channel.
+    #[clap(long)]
+    revert_channel: Option<String>,
+
+    /// Zone whose channels use the emergency system.
+    #[clap(long)]
+    zone: Option<String>,
+}
+
+#[derive(Debug, clap::Args)]
+struct ScanListParams {
+    /// Scan type, one of normal, vote or digital.
+    #[clap(long)]
+    scan_type: Option<ScanType>,
+
+    /// First priority channel. Must be on the scan list, `selected` for the
+    /// selected channel or `none` to unset it.
+    #[clap(long)]
+    priority_1: Option<String>,
+
+    /// Second priority channel, as for `--priority-1`.
+    #[clap(long)]
+    priority_2: Option<String>,
+
+    /// How long to stay on an active channel, in milliseconds.
+    #[clap(long)]
+    hang_time: Option<u32>,
+
+    /// How often to check the priority channels, in milliseconds.
+    #[clap(long)]
+    priority_sample_time: Option<u32>,
+
+    /// Weakest signal a vote scan will choose, in S-units.
+    #[clap(long)]
+    vote_threshold: Option<u8>,
+
+    /// How much stronger a channel must be for a vote scan to move to it, in
+    /// dB.
+    #[clap(long)]
+    vote_hysteresis: Option<u8>,
+
+    /// How long a vote scan measures each channel, in milliseconds.
+    #[clap(long)]
+    vote_sample_time: Option<u32>,
+
+    /// How long a vote scan stays on its chosen channel, in seconds.
+    #[clap(long)]
+    vote_hold_time: Option<u8>,
+}
+
+#[derive(Debug, Subcommand)]
+enum ContactsAction {
+    /// Add private call contacts from a radioid.net style user CSV. Users
+    /// already in the contact list are skipped.
+    ImportUsers {
+        /// User CSV, with at least RADIO_ID and CALLSIGN columns.
+        users_csv: std::path::PathBuf,
+
+        /// Only import users from this country. May be repeated.
+        #[clap(long)]
+        country: Vec<String>,
+
+        /// Only import users from this state or region. May be repeated.
+        #[clap(long)]
+        region: Vec<String>,
+
+        /// Only import users whose callsign starts with this prefix. May be
+        /// repeated.
+        #[clap(long)]
+        prefix: Vec<String>,
+
+        /// Import into the digital contact list, which holds more contacts,
+        /// instead of the contacts.
+        #[clap(long)]
+        digital_contact_list: bool,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Add group call contacts from a saved BrandMeister or TGIF style
+    /// talkgroup list, in JSON or CSV. Talkgroups already in the contact list
+    /// are skipped.
+    ImportTalkgroups {
+        /// Talkgroup list file.
+        talkgroups: std::path::PathBuf,
+
+        /// Import talkgroups with IDs in this range, e.g. 80-83 or 235. May be
+        /// repeated.
+        #[clap(long)]
+        range: Vec<IdRange>,
+
+        /// Import talkgroups whose name matches this pattern, where * and ?
+        /// are wildcards. May be repeated.
+        #[clap(long)]
+        pattern: Vec<String>,
+
+        /// Contact name template, {id} and {name} are replaced by the
+        /// talkgroup's.
+        #[clap(long, default_value = DEFAULT_NAME_FORMAT)]
+        name_format: String,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum QuickTextAction {
+    /// Print the quick text messages, numbered.
+    List {
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+    },
+
+    /// Add a quick text message.
+    Add {
+        /// Message body.
+        text: String,
+
+        /// Path to codeplug image. If not specified the codeplug is read
+        /// directly from the radio.
+        codeplug_image: Option<std::path::PathBuf>,
+
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
+        output_file: Option<std::path::PathBuf>,
+    },
+
+    /// Remove a quick text message.
+    Remove {
+        /// Number of the message, as shown by `list`.
+        number: usize,
+
         /// Path to codeplug image. If not specified the codeplug is read
         /// directly from the radio.
         codeplug_image: Option<std::path::PathBuf>,
 
+        /// Output codeplug image. If not specified the codeplug is written
+        /// directly to the radio.
         output_file: Option<std::path::PathBuf>,
     },
 }
@@ -197,8 +695,11 @@ pub fn print_sections(path: &Option<std::path::PathBuf>, verbose: bool) -> Resul
     table.set_header([
         "Address",
         "Type",
+        "Name",
+        "Decoded",
         "Capacity",
         "Elements in Use",
+        "Utilization",
         "Byte Size",
         "Unk1",
         "Unk2",
@@ -211,8 +712,11 @@ pub fn print_sections(path: &Option<std::path::PathBuf>, verbose: bool) -> Resul
         table.add_row([
             format!("0x{:X}", section.addr),
             format!("0x{:X}", section.header.section_type),
+            section_registry::section_name(section.header.section_type).to_string(),
+            section_registry::is_decoded(section.header.section_type).to_string(),
             format!("0x{:X}", section.header.capacity),
             format!("0x{:X}", section.header.elements_in_use),
+            section_registry::utilization(section),
             format!("0x{:X}", section.header.byte_size),
             format!("0x{:X}", section.header.unk1),
             format!("0x{:X}", section.header.unk2),
@@ -224,15 +728,47 @@ pub fn print_sections(path: &Option<std::path::PathBuf>, verbose: bool) -> Resul
     Ok(())
 }
 
+/// Read a codeplug, warning when verbose if its model isn't known and so
+/// won't be checked against the model's limits.
+fn read_codeplug(src: &mut (impl Read + Seek), verbose: bool) -> Result<Codeplug> {
+    let cp = Codeplug::read_codeplug(src)?;
+
+    if verbose && cp.radio_model().is_generic() {
+        println!(
+            "WARNING: Unknown radio model '{}', skipping model limit checks",
+            cp.model()
+        );
+    }
+
+    Ok(cp)
+}
+
 pub fn print_codeplug(path: &Option<std::path::PathBuf>, verbose: bool) -> Result<()> {
     let mut src = get_source(path, verbose)?;
-    let codeplug = Codeplug::read_codeplug(&mut src).context("Failed to read codeplug")?;
+    let codeplug = read_codeplug(&mut src, verbose).context("Failed to read codeplug")?;
 
     println!("{}", codeplug);
 
     Ok(())
 }
 
+fn export_channels(
+    output_csv: &std::path::Path,
+    digital: bool,
+    codeplug_image: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let mut src = get_source(codeplug_image, verbose)?;
+    let cp = read_codeplug(&mut src, verbose).context("Failed to read codeplug")?;
+    let out = File::create(output_csv).context("Could not create CSV file")?;
+
+    if digital {
+        DigitalChannel::write_csv(&cp.digi_chans.data, &cp, out)
+    } else {
+        AnalogChannel::write_csv(&cp.ana_chans.data, &cp, out)
+    }
+}
+
 fn disect_codeplug(
     codeplug_image: &Option<std::path::PathBuf>,
     output_directory: &std::path::Path,
@@ -255,52 +791,1001 @@ fn disect_codeplug(
     Ok(())
 }
 
-fn verify_codeplug(codeplug_image: &Option<std::path::PathBuf>, verbose: bool) -> Result<()> {
-    let mut src = get_source(codeplug_image, verbose)?;
-    let cp = Codeplug::read_codeplug(&mut src)?;
+fn parse_hex_dir_name(path: &std::path::Path) -> Option<u16> {
+    let name = path.file_name()?.to_str()?;
 
-    cp.verify()?;
+    u16::from_str_radix(name.strip_prefix("0x")?, 16).ok()
+}
 
-    println!("Verification finished.");
+fn read_section_elements(sec_dir: &std::path::Path) -> Result<Vec<Vec<u8>>> {
+    let mut files = std::fs::read_dir(sec_dir)?
+        .map(|e| {
+            let path = e?.path();
+            let idx = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.parse::<usize>().ok())
+                .with_context(|| format!("Unexpected element file {}", path.display()))?;
 
-    Ok(())
+            Ok((idx, path))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    files.sort();
+
+    files
+        .iter()
+        .enumerate()
+        .map(|(n, (idx, path))| {
+            if n != *idx {
+                bail!("Missing element {:04} in {}", n, sec_dir.display());
+            }
+
+            std::fs::read(path).with_context(|| format!("Could not read {}", path.display()))
+        })
+        .collect()
 }
 
-fn write_custom_codeplug(
-    codeplug_image: &Option<std::path::PathBuf>,
-    output_file: &Option<std::path::PathBuf>,
-    verbose: bool,
+fn assemble_codeplug(
+    input_directory: &std::path::Path,
+    base_image: &std::path::Path,
+    output_image: &std::path::Path,
 ) -> Result<()> {
-    let mut src = get_source(codeplug_image, verbose)?;
-    let mut cp = Codeplug::read_codeplug(&mut src)?;
+    let buf = std::fs::read(base_image).context("Could not read base image")?;
+    let mut cursor = Cursor::new(buf);
+    let mut sections = Section::load_sections(&mut cursor).context("Failed to parse sections")?;
 
-    drop(src);
+    for entry in std::fs::read_dir(input_directory).context("Could not read input directory")? {
+        let sec_dir = entry?.path();
 
-    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+        if !sec_dir.is_dir() {
+            continue;
+        }
 
-    cp.mutate_cp();
+        let sec_kind = parse_hex_dir_name(&sec_dir)
+            .with_context(|| format!("Unexpected directory {}", sec_dir.display()))?;
 
-    cp.write_codeplug(&mut dst)
+        let sec = sections
+            .get_mut(&sec_kind)
+            .with_context(|| format!("Section 0x{sec_kind:04X} not found in base image"))?;
+
+        let elements = read_section_elements(&sec_dir)?;
+
+        sec.set_elements(&elements)
+            .with_context(|| format!("Could not assemble section 0x{sec_kind:04X}"))?;
+
+        sec.write(&mut cursor)
+            .with_context(|| format!("Could not write section 0x{sec_kind:04X}"))?;
+    }
+
+    std::fs::write(output_image, cursor.into_inner()).context("Could not write output image")
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+fn hex_bytes(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{b:02X}")).join(" ")
+}
 
-    match args.command {
-        Commands::DumpCPMemory { path } => dump_codeplug_image(&path, args.verbose),
-        Commands::WriteCPMemory { path } => write_codeplug_image(&path, args.verbose),
-        Commands::FwDumpCPMemory { path } => fw_dump_codeplug_image(&path, args.verbose),
-        Commands::FwWriteCPMemory { path } => fw_write_codeplug_image(&path, args.verbose),
-        Commands::PrintSections { codeplug_image } => print_sections(&codeplug_image, args.verbose),
-        Commands::PrintCodeplug { codeplug_image } => print_codeplug(&codeplug_image, args.verbose),
-        Commands::Disect {
-            output_directory,
-            codeplug_image,
-        } => disect_codeplug(&codeplug_image, &output_directory, args.verbose),
-        Commands::Verify { codeplug_image } => verify_codeplug(&codeplug_image, args.verbose),
-        Commands::WriteCustomCodeplug {
+fn re_diff_codeplug(before: &std::path::Path, after: &std::path::Path) -> Result<()> {
+    let before = std::fs::read(before).context("Could not read before image")?;
+    let after = std::fs::read(after).context("Could not read after image")?;
+
+    let sections = Section::load_sections(&mut Cursor::new(&before))
+        .context("Failed to parse sections of before image")?;
+    match Section::load_sections(&mut Cursor::new(&after)) {
+        Ok(after_sections) => {
+            for (kind, sec) in sections.iter() {
+                match after_sections.get(kind) {
+                    Some(s) if s.addr == sec.addr && s.end_addr() == sec.end_addr() => (),
+                    _ => println!(
+                        "WARNING: Section 0x{kind:X} moved or was resized, attribution uses the before image"
+                    ),
+                }
+            }
+        }
+        Err(e) => println!("WARNING: Could not parse sections of after image: {e}"),
+    }
+
+    if before.len() != after.len() {
+        println!(
+            "WARNING: Image sizes differ (0x{:X} vs 0x{:X}), only comparing common bytes",
+            before.len(),
+            after.len()
+        );
+    }
+
+    let runs = re_diff::diff_images(&sections, &before, &after);
+
+    let (header_runs, section_runs): (Vec<_>, Vec<_>) = runs
+        .iter()
+        .partition(|r| r.location == Location::Header);
+
+    let mut header_table = Table::new();
+    header_table.load_preset(UTF8_BORDERS_ONLY);
+    header_table.set_header(["Address", "Before", "After"]);
+
+    for run in header_runs.iter() {
+        header_table.add_row([
+            format!("0x{:X}", run.addr),
+            hex_bytes(&run.before),
+            hex_bytes(&run.after),
+        ]);
+    }
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_BORDERS_ONLY);
+    table.set_header([
+        "Address", "Section", "Region", "Element", "Offset", "Field", "Before", "After",
+    ]);
+
+    for run in section_runs.iter() {
+        let sec_name = |t: u16| format!("0x{:X} {}", t, section_registry::section_name(t));
+
+        let (section, region, element, offset, field) = match &run.location {
+            Location::Header => unreachable!(),
+            Location::SectionHeader { section_type } => (
+                sec_name(*section_type),
+                "Header".to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ),
+            Location::Mappings { section_type } => (
+                sec_name(*section_type),
+                "Mappings".to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ),
+            Location::Element {
+                section_type,
+                element,
+                chunk,
+                offset,
+                field,
+                ..
+            } => (
+                sec_name(*section_type),
+                "Data".to_string(),
+                match element {
+                    Some((e, true)) => format!("{e}"),
+                    Some((e, false)) => format!("{e} (unused)"),
+                    None => format!("<unmapped chunk {chunk}>"),
+                },
+                format!("0x{offset:X}"),
+                field.clone().unwrap_or_default(),
+            ),
+            Location::Unattributed => (
+                "<None>".to_string(),
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+            ),
+        };
+
+        let (before, after) = match run.location {
+            Location::Element { secret: true, .. } => (HIDDEN.to_string(), HIDDEN.to_string()),
+            _ => (hex_bytes(&run.before), hex_bytes(&run.after)),
+        };
+
+        table.add_row([
+            format!("0x{:X}", run.addr),
+            section,
+            region,
+            element,
+            offset,
+            field,
+            before,
+            after,
+        ]);
+    }
+
+    println!("Header Changes");
+    println!("==============");
+    println!("{header_table}");
+
+    println!("Section Changes");
+    println!("===============");
+    println!("{table}");
+
+    Ok(())
+}
+
+fn parse_u16(s: &str) -> Result<u16, std::num::ParseIntError> {
+    match s.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+}
+
+fn hexdump_element(
+    codeplug_image: &Option<std::path::PathBuf>,
+    section: u16,
+    element: u16,
+    verbose: bool,
+) -> Result<()> {
+    let mut src = get_source(codeplug_image, verbose)?;
+    let sections = Section::load_sections(&mut src).context("Failed to parse sections")?;
+
+    let sec = sections
+        .get(&section)
+        .with_context(|| format!("Section 0x{section:X} not found"))?;
+
+    if element >= sec.header.capacity {
+        bail!(
+            "Element {} is out of range, section capacity is {}",
+            element,
+            sec.header.capacity
+        );
+    }
+
+    if element >= sec.header.elements_in_use {
+        println!("WARNING: Element {element} is not in use");
+    }
+
+    let data = sec.get_data_chunk(element)?;
+    let decoder = section_registry::lookup(section).and_then(|i| i.decoder.as_ref());
+    let layout = decoder.map_or(&[][..], |d| d.layout);
+
+    println!(
+        "Section 0x{:X} ({}) element {}, decoded as {}",
+        section,
+        section_registry::section_name(section),
+        element,
+        decoder.map_or("<None>", |d| d.name)
+    );
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_BORDERS_ONLY);
+    table.set_header(["Offset", "Bytes", "Field", "Value"]);
+
+    for a in layout::annotate(layout, data) {
+        let bytes = if a.secret {
+            HIDDEN.to_string()
+        } else {
+            data[a.offset..a.offset + a.width]
+                .chunks(16)
+                .map(hex_bytes)
+                .join("\n")
+        };
+
+        let (field, value) = if a.fields.is_empty() {
+            ("<unknown>".to_string(), String::new())
+        } else {
+            (
+                a.fields.iter().map(|f| f.0).join("\n"),
+                a.fields
+                    .iter()
+                    .map(|f| f.1.clone().unwrap_or_default())
+                    .join("\n"),
+            )
+        };
+
+        let unknown_set = data[a.offset..a.offset + a.width]
+            .iter()
+            .any(|b| b & a.unknown_mask != 0);
+
+        let (field, colour) = if a.unknown_mask == 0xff {
+            (field, Some(Color::Red))
+        } else if unknown_set {
+            (
+                format!("{field}\n<unknown bits 0x{:02X}>", a.unknown_mask),
+                Some(Color::Yellow),
+            )
+        } else {
+            (field, None)
+        };
+
+        let cells = vec![format!("0x{:04X}", a.offset), bytes, field, value]
+            .into_iter()
+            .map(|c| match colour {
+                Some(colour) => Cell::new(c).fg(colour),
+                None => Cell::new(c),
+            })
+            .collect::<Vec<_>>();
+
+        table.add_row(cells);
+    }
+
+    println!("{table}");
+
+    Ok(())
+}
+
+fn verify_codeplug(codeplug_image: &Option<std::path::PathBuf>, verbose: bool) -> Result<()> {
+    let mut src = get_source(codeplug_image, verbose)?;
+    let cp = read_codeplug(&mut src, verbose)?;
+
+    cp.verify()?;
+
+    println!("Verification finished.");
+
+    Ok(())
+}
+
+fn write_custom_codeplug(
+    default_buttons: bool,
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.mutate_cp().context("Could not build the custom codeplug")?;
+
+    if default_buttons {
+        cp.apply_default_buttons()?;
+    }
+
+    cp.write_codeplug(&mut dst)
+}
+
+fn delete_channel(
+    name: &str,
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    let chan = cp.find_channel(name)?;
+    cp.delete_channel(chan)?;
+    cp.verify().context("Codeplug is inconsistent after deleting channel")?;
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)
+}
+
+fn import_repeaters(
+    repeaters_csv: &std::path::Path,
+    talkgroups: &std::path::Path,
+    zones: &LocationZones,
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let file = File::open(repeaters_csv).context("Could not open repeater CSV")?;
+    let repeaters = Repeater::parse_csv(file).context("Could not parse repeater CSV")?;
+    let file = File::open(talkgroups).context("Could not open talkgroup profile")?;
+    let profile = ProfileTalkgroup::parse_csv(file).context("Could not parse talkgroup profile")?;
+
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    let report = cp.import_repeaters(&repeaters, &profile, zones)?;
+    cp.verify()
+        .context("Codeplug is inconsistent after importing repeaters")?;
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)?;
+
+    println!("{}", report);
+
+    Ok(())
+}
+
+fn generate_roam_list(
+    talkgroup: u32,
+    timeslot: Timeslot,
+    name: Option<&str>,
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    let generated = cp.generate_roam_list(talkgroup, timeslot, name)?;
+    cp.verify()
+        .context("Codeplug is inconsistent after generating roam list")?;
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)?;
+
+    println!("{}", generated);
+
+    Ok(())
+}
+
+fn generate_scan_lists(
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    let report = cp.generate_zone_scan_lists()?;
+    cp.verify()
+        .context("Codeplug is inconsistent after generating scan lists")?;
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)?;
+
+    println!("{}", report);
+
+    Ok(())
+}
+
+fn set_zone_default(
+    zone: &str,
+    channel: &str,
+    b: bool,
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    let idx = cp.find_zone(zone)?;
+    let chan = cp.find_channel(channel)?;
+
+    let list = &cp.zones.channels.data[idx];
+    let set = if b {
+        cp.zones.data.data[idx].set_default_channel_b(list, chan)
+    } else {
+        cp.zones.data.data[idx].set_default_channel(list, chan)
+    };
+
+    set.with_context(|| format!("Channel '{}' isn't in zone '{}'", channel, zone))?;
+    cp.verify()
+        .context("Codeplug is inconsistent after setting zone default")?;
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)
+}
+
+/// Convert milliseconds to the 100ms units scan lists store times in.
+fn to_100ms(ms: u32) -> Result<u8> {
+    if !ms.is_multiple_of(100) {
+        bail!("{}ms isn't a multiple of 100ms", ms);
+    }
+
+    u8::try_from(ms / 100).with_context(|| format!("{}ms is too long", ms))
+}
+
+fn set_scan_list(
+    name: &str,
+    params: ScanListParams,
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    let idx = cp.find_scan_list(name)?;
+    let list = &cp.scan_list.channels.data[idx];
+    let mut priority = cp.scan_list.data.data[idx].priority_channels(list);
+
+    for (p, arg) in priority
+        .iter_mut()
+        .zip([&params.priority_1, &params.priority_2])
+    {
+        *p = match arg.as_deref() {
+            None => *p,
+            Some("none") => None,
+            Some("selected") => Some(ChannelPointer::Selected),
+            Some(chan) => Some(cp.find_channel(chan)?),
+        };
+    }
+
+    let scan = &mut cp.scan_list.data.data[idx];
+
+    scan.set_priority_channels(list, priority)?;
+
+    if let Some(scan_type) = params.scan_type {
+        scan.scan_type = scan_type;
+    }
+
+    if let Some(ms) = params.hang_time {
+        scan.hang_time = to_100ms(ms).context("Invalid hang time")?;
+    }
+
+    if let Some(ms) = params.priority_sample_time {
+        scan.priority_sample_time = to_100ms(ms).context("Invalid priority sample time")?;
+    }
+
+    if let Some(threshold) = params.vote_threshold {
+        scan.vote_rssi_threshold = threshold;
+    }
+
+    if let Some(hysteresis) = params.vote_hysteresis {
+        scan.vote_rssi_hysteresis = hysteresis;
+    }
+
+    if let Some(ms) = params.vote_sample_time {
+        scan.vote_sample_time = to_100ms(ms).context("Invalid vote sample time")?;
+    }
+
+    if let Some(secs) = params.vote_hold_time {
+        scan.vote_hold_time = secs;
+    }
+
+    cp.verify()
+        .context("Codeplug is inconsistent after changing scan list")?;
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)
+}
+
+fn set_analog_tones(
+    channel: &str,
+    rx_tone: Option<Tone>,
+    tx_tone: Option<Tone>,
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    if rx_tone.is_none() && tx_tone.is_none() {
+        bail!("Give an RX or TX tone to set");
+    }
+
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    cp.set_analog_tones(channel, rx_tone, tx_tone)?;
+
+    cp.verify()
+        .context("Codeplug is inconsistent after setting tones")?;
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)
+}
+
+fn selective_call(
+    channel: &str,
+    two_tone: Option<usize>,
+    dtmf: Option<usize>,
+    dtmf_id: Option<String>,
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let signalling = match (two_tone, dtmf) {
+        (Some(0), _) | (_, Some(0)) => bail!("Signalling systems are numbered from 1"),
+        (Some(n), _) => Signalling::TwoTone(n - 1),
+        (_, Some(n)) => Signalling::Dtmf(n - 1),
+        (None, None) => Signalling::None,
+    };
+
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    cp.set_selective_call(channel, signalling)?;
+
+    if let (Signalling::Dtmf(idx), Some(id)) = (signalling, dtmf_id) {
+        cp.set_dtmf_id(idx, &id)?;
+    }
+
+    cp.verify()
+        .context("Codeplug is inconsistent after setting selective call")?;
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)
+}
+
+fn add_emergency_system(
+    name: String,
+    params: EmergencySystemParams,
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    let talkgroup = params.talkgroup;
+    let contact_idx = match cp.contacts.data.iter().position(|c| c.id == talkgroup) {
+        Some(i) => i,
+        None => bail!("No contact with ID {}", talkgroup),
+    };
+
+    let mut es = EmergencySystem::new(name, params.alarm_type, contact_idx as u16);
+    if let Some(channel) = params.revert_channel {
+        es.revert_channel = cp.find_channel(&channel)?;
+    }
+
+    let es_idx = cp.add_emergency_system(es)?;
+
+    if let Some(zone) = params.zone {
+        let zone_idx = cp.find_zone(&zone)?;
+
+        for chan in cp.zones.channels.data[zone_idx].channels.clone() {
+            cp.assign_emergency_system(chan, Some(es_idx))?;
+        }
+    }
+
+    cp.verify()
+        .context("Codeplug is inconsistent after adding the emergency system")?;
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)
+}
+
+fn set_settings(
+    radio_id: Option<u32>,
+    alias: Option<String>,
+    intro_line_1: Option<String>,
+    intro_line_2: Option<String>,
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    let settings = cp.settings_mut()?;
+
+    if let Some(radio_id) = radio_id {
+        if radio_id == 0 {
+            bail!("Radio ID must be at least 1");
+        }
+
+        settings.radio_id = radio_id;
+    }
+
+    if let Some(alias) = alias {
+        settings.radio_alias = alias;
+    }
+
+    if let Some(line) = intro_line_1 {
+        settings.intro_line_1 = line;
+    }
+
+    if let Some(line) = intro_line_2 {
+        settings.intro_line_2 = line;
+    }
+
+    cp.verify().context("Invalid settings")?;
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)
+}
+
+fn import_keys(
+    keys_file: &std::path::Path,
+    codeplug_image: &Option<std::path::PathBuf>,
+    output_file: &Option<std::path::PathBuf>,
+    verbose: bool,
+) -> Result<()> {
+    let keys = std::fs::read_to_string(keys_file).context("Could not read keys file")?;
+    let keys = KeysFile::parse(&keys).context("Could not parse keys file")?;
+
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    cp.import_keys(keys)?;
+    cp.verify().context("Codeplug is inconsistent after importing keys")?;
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)
+}
+
+fn contacts(action: ContactsAction, verbose: bool) -> Result<()> {
+    match action {
+        ContactsAction::ImportUsers {
+            users_csv,
+            country,
+            region,
+            prefix,
+            digital_contact_list,
+            codeplug_image,
+            output_file,
+        } => {
+            let file = File::open(&users_csv).context("Could not open user CSV")?;
+            let csv = UsersCsv::parse(file).context("Could not parse user CSV")?;
+            let filter = UserFilter {
+                countries: country,
+                regions: region,
+                prefixes: prefix,
+            };
+
+            let mut src = get_source(&codeplug_image, verbose)?;
+            let mut cp = read_codeplug(&mut src, verbose)?;
+
+            drop(src);
+
+            let mut report = cp.import_users(&csv.users, &filter, digital_contact_list)?;
+            report.invalid += csv.invalid;
+
+            cp.verify()
+                .context("Codeplug is inconsistent after importing users")?;
+
+            let mut dst = get_sink(&output_file, verbose).context("Could not open output")?;
+
+            cp.write_codeplug(&mut dst)?;
+
+            println!("{}", report);
+
+            Ok(())
+        }
+        ContactsAction::ImportTalkgroups {
+            talkgroups,
+            range,
+            pattern,
+            name_format,
+            codeplug_image,
+            output_file,
+        } => {
+            let text = std::fs::read_to_string(&talkgroups)
+                .context("Could not open talkgroup list")?;
+            let list = Talkgroups::parse(&text).context("Could not parse talkgroup list")?;
+            let filter = TalkgroupFilter {
+                ranges: range,
+                patterns: pattern,
+            };
+
+            let mut src = get_source(&codeplug_image, verbose)?;
+            let mut cp = read_codeplug(&mut src, verbose)?;
+
+            drop(src);
+
+            let mut report = cp.import_talkgroups(&list.talkgroups, &filter, &name_format)?;
+            report.invalid += list.invalid;
+
+            cp.verify()
+                .context("Codeplug is inconsistent after importing talkgroups")?;
+
+            let mut dst = get_sink(&output_file, verbose).context("Could not open output")?;
+
+            cp.write_codeplug(&mut dst)?;
+
+            println!("{}", report);
+
+            Ok(())
+        }
+    }
+}
+
+fn quick_text(action: QuickTextAction, verbose: bool) -> Result<()> {
+    let (codeplug_image, output_file) = match &action {
+        QuickTextAction::List { codeplug_image } => (codeplug_image, &None),
+        QuickTextAction::Add {
+            codeplug_image,
+            output_file,
+            ..
+        }
+        | QuickTextAction::Remove {
+            codeplug_image,
+            output_file,
+            ..
+        } => (codeplug_image, output_file),
+    };
+
+    let mut src = get_source(codeplug_image, verbose)?;
+    let mut cp = read_codeplug(&mut src, verbose)?;
+
+    drop(src);
+
+    match &action {
+        QuickTextAction::List { .. } => {
+            let quick_texts = cp
+                .quick_texts
+                .as_ref()
+                .context("Codeplug has no quick text message section")?;
+
+            for (i, qt) in quick_texts.data.iter().enumerate() {
+                println!("{}: {}", i + 1, qt.body);
+            }
+
+            return Ok(());
+        }
+        QuickTextAction::Add { text, .. } => {
+            cp.add_quick_text(text)?;
+        }
+        QuickTextAction::Remove { number, .. } => {
+            if *number == 0 {
+                bail!("Quick text messages are numbered from 1");
+            }
+
+            cp.remove_quick_text(number - 1)
+                .with_context(|| format!("Could not remove message {}", number))?;
+        }
+    }
+
+    let mut dst = get_sink(output_file, verbose).context("Could not open output")?;
+
+    cp.write_codeplug(&mut dst)
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    match args.command {
+        Commands::DumpCPMemory { path } => dump_codeplug_image(&path, args.verbose),
+        Commands::WriteCPMemory { path } => write_codeplug_image(&path, args.verbose),
+        Commands::FwDumpCPMemory { path } => fw_dump_codeplug_image(&path, args.verbose),
+        Commands::FwWriteCPMemory { path } => fw_write_codeplug_image(&path, args.verbose),
+        Commands::PrintSections { codeplug_image } => print_sections(&codeplug_image, args.verbose),
+        Commands::PrintCodeplug { codeplug_image } => print_codeplug(&codeplug_image, args.verbose),
+        Commands::ExportChannels {
+            output_csv,
+            digital,
+            codeplug_image,
+        } => export_channels(&output_csv, digital, &codeplug_image, args.verbose),
+        Commands::Disect {
+            output_directory,
+            codeplug_image,
+        } => disect_codeplug(&codeplug_image, &output_directory, args.verbose),
+        Commands::Assemble {
+            input_directory,
+            base_image,
+            output_image,
+        } => assemble_codeplug(&input_directory, &base_image, &output_image),
+        Commands::ReDiff { before, after } => re_diff_codeplug(&before, &after),
+        Commands::Hexdump {
+            section,
+            element,
+            codeplug_image,
+        } => hexdump_element(&codeplug_image, section, element, args.verbose),
+        Commands::Verify { codeplug_image } => verify_codeplug(&codeplug_image, args.verbose),
+        Commands::WriteCustomCodeplug {
+            default_buttons,
+            codeplug_image,
+            output_file,
+        } => write_custom_codeplug(default_buttons, &codeplug_image, &output_file, args.verbose),
+        Commands::DeleteChannel {
+            name,
+            codeplug_image,
+            output_file,
+        } => delete_channel(&name, &codeplug_image, &output_file, args.verbose),
+        Commands::ImportRepeaters {
+            repeaters_csv,
+            talkgroups,
+            qth,
+            radius,
+            grid_zones,
+            codeplug_image,
+            output_file,
+        } => import_repeaters(
+            &repeaters_csv,
+            &talkgroups,
+            &LocationZones {
+                qth,
+                radius_km: radius,
+                grid_squares: grid_zones,
+            },
+            &codeplug_image,
+            &output_file,
+            args.verbose,
+        ),
+        Commands::GenerateRoamList {
+            talkgroup,
+            timeslot,
+            name,
+            codeplug_image,
+            output_file,
+        } => generate_roam_list(
+            talkgroup,
+            timeslot,
+            name.as_deref(),
+            &codeplug_image,
+            &output_file,
+            args.verbose,
+        ),
+        Commands::GenerateScanLists {
+            codeplug_image,
+            output_file,
+        } => generate_scan_lists(&codeplug_image, &output_file, args.verbose),
+        Commands::SetZoneDefault {
+            zone,
+            channel,
+            b,
+            codeplug_image,
+            output_file,
+        } => set_zone_default(
+            &zone,
+            &channel,
+            b,
+            &codeplug_image,
+            &output_file,
+            args.verbose,
+        ),
+        Commands::SetScanList {
+            name,
+            params,
+            codeplug_image,
+            output_file,
+        } => set_scan_list(&name, params, &codeplug_image, &output_file, args.verbose),
+        Commands::SetAnalogTones {
+            channel,
+            rx_tone,
+            tx_tone,
+            codeplug_image,
+            output_file,
+        } => set_analog_tones(
+            &channel,
+            rx_tone,
+            tx_tone,
+            &codeplug_image,
+            &output_file,
+            args.verbose,
+        ),
+        Commands::SelectiveCall {
+            channel,
+            two_tone,
+            dtmf,
+            dtmf_id,
+            codeplug_image,
+            output_file,
+        } => selective_call(
+            &channel,
+            two_tone,
+            dtmf,
+            dtmf_id,
+            &codeplug_image,
+            &output_file,
+            args.verbose,
+        ),
+        Commands::AddEmergencySystem {
+            name,
+            params,
+            codeplug_image,
+            output_file,
+        } => add_emergency_system(name, params, &codeplug_image, &output_file, args.verbose),
+        Commands::ImportKeys {
+            keys_file,
+            codeplug_image,
+            output_file,
+        } => import_keys(&keys_file, &codeplug_image, &output_file, args.verbose),
+        Commands::Contacts { action } => contacts(action, args.verbose),
+        Commands::QuickText { action } => quick_text(action, args.verbose),
+        Commands::SetSettings {
+            radio_id,
+            alias,
+            intro_line_1,
+            intro_line_2,
             codeplug_image,
             output_file,
-        } => write_custom_codeplug(&codeplug_image, &output_file, args.verbose),
+        } => set_settings(
+            radio_id,
+            alias,
+            intro_line_1,
+            intro_line_2,
+            &codeplug_image,
+            &output_file,
+            args.verbose,
+        ),
     }
 }
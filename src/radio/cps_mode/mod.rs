@@ -7,6 +7,7 @@ use self::packet::{
 };
 use super::{prog_mode::ProgMode, common::L2};
 use super::Radio;
+use crate::codeplug::radio_model::RadioModel;
 use anyhow::{bail, Context, Result};
 
 mod packet;
@@ -113,10 +114,7 @@ impl ProgMode for CPSMode {
     }
 
     fn get_vid_pid_eps() -> Vec<(u16, u16, u8)> {
-        Vec::from([(0x0a11, 0x04), (0x0a12, 0x02), (0x0a21, 0x04)])
-            .iter()
-            .map(|x| (0x238b, x.0, x.1))
-            .collect()
+        RadioModel::all_usb_ids()
     }
 
     fn get_chunk_sz() -> usize {
@@ -1,6 +1,16 @@
-use super::codeplug::cp_data::RawCPData;
+use super::codeplug::{
+    cp_data::RawCPData,
+    layout::{CPLayout, Field},
+    section::{Section, SectionMappings, SECTION_TABLE_ADDR},
+    Codeplug,
+};
+use byteorder::{LittleEndian, WriteBytesExt};
 use proptest::prelude::*;
-use std::io::Seek;
+use std::{
+    convert::TryFrom,
+    fmt::Debug,
+    io::{Cursor, Seek},
+};
 
 pub fn check_serde<T: RawCPData + std::fmt::Debug + std::cmp::PartialEq>(
     obj: &T,
@@ -18,3 +28,303 @@ pub fn check_serde<T: RawCPData + std::fmt::Debug + std::cmp::PartialEq>(
 
     Ok(())
 }
+
+/// Values for a field holding the undecoded bits `mask` of a bitfield.
+pub fn unknown_bits<T>(mask: T) -> impl Strategy<Value = T>
+where
+    T: Into<u32> + TryFrom<u32> + Copy + Debug,
+{
+    let mask: u32 = mask.into();
+
+    any::<u32>().prop_map(move |v| {
+        let bits = (v & mask) >> mask.trailing_zeros();
+        T::try_from(bits).unwrap_or_else(|_| unreachable!("bits of the mask fit its type"))
+    })
+}
+
+/// Bits of the byte at `offset` claimed by the fields of `layout`.
+fn layout_mask(layout: &[Field], offset: usize) -> u8 {
+    layout
+        .iter()
+        .filter(|f| f.contains(offset))
+        .fold(0, |acc, f| acc | f.mask)
+}
+
+/// Check the fields of `layout` cover every bit of `data` exactly once.
+fn check_coverage(layout: &[Field], data: &[u8]) -> Result<(), TestCaseError> {
+    for f in layout {
+        prop_assert!(
+            f.offset + f.width <= data.len(),
+            "{} ends past the {} stored bytes",
+            f.name,
+            data.len()
+        );
+    }
+
+    for offset in 0..data.len() {
+        let fields: Vec<_> = layout.iter().filter(|f| f.contains(offset)).collect();
+        let overlap = fields
+            .iter()
+            .enumerate()
+            .any(|(i, f)| fields[i + 1..].iter().any(|g| f.mask & g.mask != 0));
+
+        prop_assert!(!overlap, "fields overlap at byte {}", offset);
+        prop_assert_eq!(
+            layout_mask(layout, offset),
+            0xff,
+            "byte {} isn't fully covered",
+            offset
+        );
+    }
+
+    Ok(())
+}
+
+/// Check `T::LAYOUT` describes how `obj` is stored: its fields cover the
+/// stored bytes exactly, and flipping any bit of a field, bitfields
+/// included, changes nothing outside that field, or the other parts of its
+/// value, once reloaded and stored again. Flips giving bytes `T` refuses to
+/// load are skipped.
+pub fn check_layout<T: RawCPData + CPLayout>(obj: &T) -> Result<(), TestCaseError> {
+    let mut data = vec![];
+    obj.store(&mut data).unwrap();
+
+    check_coverage(T::LAYOUT, &data)?;
+
+    for f in T::LAYOUT {
+        let parts: Vec<_> = T::LAYOUT
+            .iter()
+            .filter(|g| g.name == f.name)
+            .copied()
+            .collect();
+
+        for offset in f.offset..f.offset + f.width {
+            for bit in (0..8).map(|b| 1u8 << b).filter(|b| f.mask & b != 0) {
+                let mut flipped = data.clone();
+                flipped[offset] ^= bit;
+
+                let reloaded = match T::load(&mut Cursor::new(&flipped)) {
+                    Ok(o) => o,
+                    Err(_) => continue,
+                };
+                let mut stored = vec![];
+                reloaded.store(&mut stored).unwrap();
+
+                prop_assert_eq!(stored.len(), data.len());
+                for (i, (a, b)) in data.iter().zip(&stored).enumerate() {
+                    let changed = a ^ b;
+                    let outside = !layout_mask(&parts, i);
+
+                    prop_assert!(
+                        changed & outside == 0,
+                        "flipping bit 0x{:02X} of {} at byte {} changed byte {} by 0x{:02X}",
+                        bit,
+                        f.name,
+                        offset,
+                        i,
+                        changed
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check `T::LAYOUT` covers a channel pointer list holding as many
+/// channels as it can, as the section stores lists padded to that size.
+/// The mappings index into the channel data, so flipping a field isn't
+/// expected to stay within it.
+pub fn check_list_layout<T: RawCPData + CPLayout>(full: &T) -> Result<(), TestCaseError> {
+    let mut data = vec![];
+    full.store(&mut data).unwrap();
+
+    check_coverage(T::LAYOUT, &data)
+}
+
+/// An empty section with room for `capacity` elements of `elm_sz` bytes.
+fn empty_section(image: &mut Vec<u8>, section_type: u16, capacity: u16, elm_sz: u32) {
+    let byte_size = capacity as u32 * elm_sz;
+
+    image.write_u16::<LittleEndian>(section_type).unwrap();
+    image.write_u16::<LittleEndian>(capacity).unwrap();
+    image.write_u16::<LittleEndian>(0).unwrap();
+    image.write_u32::<LittleEndian>(0x20).unwrap();
+    image.write_u32::<LittleEndian>(0).unwrap();
+    image.write_u32::<LittleEndian>(byte_size).unwrap();
+    image.write_u32::<LittleEndian>(byte_size + 0x16).unwrap();
+    image.resize(image.len() + byte_size as usize, 0);
+
+    for i in 0..capacity {
+        image.write_u16::<LittleEndian>(i).unwrap();
+        image.write_u32::<LittleEndian>(i as u32 * elm_sz).unwrap();
+    }
+}
+
+/// Size of a channel pointer list element holding `n` pointers of `sz`
+/// bytes.
+const fn pointer_list_sz(n: u32, sz: u32) -> u32 {
+    0xe + n * sz + n * 6
+}
+
+/// A PD785G image with no contacts or channels, room for `channels`
+/// contacts, digital contacts and channels of each type and for `lists`
+/// zones, scan lists, roam lists, RX group lists and emergency systems.
+pub fn empty_image(channels: u16, lists: u16) -> Vec<u8> {
+    let mut image = vec![0; SECTION_TABLE_ADDR as usize + 4];
+    image[0x3c..0x42].copy_from_slice(b"PD785G");
+
+    empty_section(&mut image, 0x2a, channels, 48);
+    empty_section(&mut image, 0x2f, channels, 36);
+    empty_section(&mut image, 0x26, channels, 74);
+    empty_section(&mut image, 0x27, channels, 68);
+    empty_section(&mut image, 0x24, lists, 40);
+    empty_section(&mut image, 0x23, lists, pointer_list_sz(512, 4));
+    empty_section(&mut image, 0x6d, lists, 49);
+    empty_section(&mut image, 0x4d, lists, pointer_list_sz(32, 4));
+    empty_section(&mut image, 0x7a, lists, 40);
+    empty_section(&mut image, 0x79, lists, pointer_list_sz(32, 2));
+    empty_section(&mut image, 0x2c, lists, 40);
+    empty_section(&mut image, 0x2b, lists, pointer_list_sz(32, 2));
+    empty_section(&mut image, 0x2d, lists, 41);
+
+    let end = image.len() as u32;
+    (&mut image[SECTION_TABLE_ADDR as usize..])
+        .write_u32::<LittleEndian>(end)
+        .unwrap();
+    image.extend_from_slice(&[0xff; 16]);
+
+    image
+}
+
+/// `empty_image` loaded as a codeplug.
+pub fn empty_codeplug(channels: u16, lists: u16) -> Codeplug {
+    Codeplug::read_codeplug(&mut Cursor::new(empty_image(channels, lists))).unwrap()
+}
+
+#[test]
+fn assemble_round_trip() {
+    let dir = std::env::temp_dir().join(format!("hyrps-assemble-{}", std::process::id()));
+    let (base, parts, out) = (dir.join("base.img"), dir.join("parts"), dir.join("out.img"));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&parts).unwrap();
+
+    // Two contacts whose data chunks are stored in reverse order.
+    let mut image = empty_image(4, 2);
+    let mut sections = Section::load_sections(&mut Cursor::new(&image)).unwrap();
+    let contacts = sections.get_mut(&0x2a).unwrap();
+    contacts.header.elements_in_use = 2;
+    contacts.data[..48].fill(1);
+    contacts.data[48..96].fill(2);
+    contacts.mappings[0] = SectionMappings { idx: 1, offset: 48 };
+    contacts.mappings[1] = SectionMappings { idx: 0, offset: 0 };
+    contacts.write(&mut Cursor::new(&mut image)).unwrap();
+    std::fs::write(&base, &image).unwrap();
+
+    crate::disect_codeplug(&Some(base.clone()), &parts, false).unwrap();
+    crate::assemble_codeplug(&parts, &base, &out).unwrap();
+
+    let before = Section::load_sections(&mut Cursor::new(&image)).unwrap();
+    let after = Section::load_sections(&mut Cursor::new(std::fs::read(&out).unwrap())).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    for (kind, sec) in before.iter() {
+        let assembled = &after[kind];
+        let elements = |s: &Section| -> Vec<Vec<u8>> {
+            (0..s.header.elements_in_use)
+                .map(|i| s.get_data_chunk(i).unwrap().to_vec())
+                .collect()
+        };
+
+        assert_eq!(elements(assembled), elements(sec), "section 0x{:X}", kind);
+
+        for (i, m) in assembled.mappings.iter().enumerate() {
+            assert_eq!(*m, assembled.get_mapping(i as u16));
+        }
+    }
+
+    let contacts = &after[&0x2a];
+    assert_eq!(contacts.header.elements_in_use, 2);
+    assert!(contacts.data[..48].iter().all(|b| *b == 2));
+    assert!(contacts.data[48..96].iter().all(|b| *b == 1));
+}
+
+mod layouts {
+    use super::{check_layout, check_list_layout};
+    use crate::codeplug::{
+        analog_channel::AnalogChannel,
+        buttons::Buttons,
+        channel_pointer::{digi_chan_pointer::DigiChannelPointer, pointer::ChannelPointer},
+        contact::{pointer::ContactPointer, user_contact::UserContact, Contact},
+        digital_channel::DigitalChannel,
+        emergency_system::EmergencySystem,
+        phone_system::PhoneSystem,
+        privacy::{BasicKey, EnhancedKey},
+        quick_text::QuickText,
+        roam::{list::RoamList, Roam},
+        rx_group::{list::RxGroupList, RxGroup},
+        scan::Scan,
+        scan_list::{self, ScanList},
+        settings::Settings,
+        signalling::{dtmf_system::DtmfSystem, two_tone::TwoToneSystem},
+        zone::Zone,
+        zone_list::{self, ZoneList},
+    };
+    use proptest::{collection::vec, prelude::*};
+
+    macro_rules! layout_tests {
+        ($($name:ident: $ty:ty,)*) => {
+            proptest! {
+                $(
+                    #[test]
+                    fn $name(obj in any::<$ty>()) {
+                        check_layout(&obj)?;
+                    }
+                )*
+            }
+        };
+    }
+
+    layout_tests! {
+        settings: Settings,
+        zone: Zone,
+        digital_channel: DigitalChannel,
+        analog_channel: AnalogChannel,
+        contact: Contact,
+        user_contact: UserContact,
+        rx_group: RxGroup,
+        emergency_system: EmergencySystem,
+        phone_system: PhoneSystem,
+        basic_key: BasicKey,
+        enhanced_key: EnhancedKey,
+        quick_text: QuickText,
+        buttons: Buttons,
+        two_tone_system: TwoToneSystem,
+        dtmf_system: DtmfSystem,
+        scan: Scan,
+        roam: Roam,
+    }
+
+    proptest! {
+        #[test]
+        fn zone_list(channels in vec(any::<ChannelPointer>(), zone_list::MAX_CHANNELS)) {
+            check_list_layout(&ZoneList { channels })?;
+        }
+
+        #[test]
+        fn scan_list(channels in vec(any::<ChannelPointer>(), scan_list::MAX_CHANNELS)) {
+            check_list_layout(&ScanList { channels })?;
+        }
+
+        #[test]
+        fn roam_list(channels in vec(any::<DigiChannelPointer>(), 32)) {
+            check_list_layout(&RoamList { channels })?;
+        }
+
+        #[test]
+        fn rx_group_list(contacts in vec(any::<ContactPointer>(), 32)) {
+            check_list_layout(&RxGroupList { contacts })?;
+        }
+    }
+}